@@ -7,7 +7,12 @@ import {
   writeTextFile,
 } from "@tauri-apps/api/fs";
 import { writeText } from "@tauri-apps/api/clipboard";
-import type { KeyOptions, KeywichApi, VerifyResponse } from "./api/types";
+import type {
+  KeyOptions,
+  KeywichApi,
+  TemplateKeyRequest,
+  VerifyResponse,
+} from "./api/types";
 
 export * from "./api/types";
 
@@ -18,23 +23,63 @@ function upload_icon(path: string): Promise<string> {
 }
 
 export const Api: KeywichApi = {
+  bulk_update_keys: function (ids, action) {
+    return invoke("bulk_update_keys", { key_ids: ids, action: action });
+  },
+
   delete_key: function (id) {
     return invoke("delete_key", { key_id: id });
   },
 
+  empty_trash: function () {
+    return invoke("empty_trash");
+  },
+
   get_key_by_id: function (id) {
     return invoke("get_key_by_id", { key_id: id });
   },
 
-  get_keys: function () {
-    return invoke("get_keys");
+  get_key_history: function (id) {
+    return invoke("get_key_history", { key_id: id });
+  },
+
+  get_key_rotations: function (id) {
+    return invoke("get_key_rotations", { key_id: id });
+  },
+
+  get_keys: function (options) {
+    return invoke("get_keys", { options: options });
+  },
+
+  get_keys_due_for_rotation: function (within_days) {
+    return invoke("get_keys_due_for_rotation", { within_days: within_days });
   },
 
   get_pinned_keys: function () {
     return invoke("get_pinned_keys");
   },
 
-  insert_key: async function (data) {
+  get_rotation_policies: function () {
+    return invoke("get_rotation_policies");
+  },
+
+  get_stale_keys: function (unused_days) {
+    return invoke("get_stale_keys", { unused_days: unused_days });
+  },
+
+  find_keys_for_url: function (url) {
+    return invoke("find_keys_for_url", { url: url });
+  },
+
+  find_duplicate_keys: function () {
+    return invoke("find_duplicate_keys");
+  },
+
+  get_trashed_keys: function () {
+    return invoke("get_trashed_keys");
+  },
+
+  insert_key: async function (data, options) {
     let icon_name: string | undefined = undefined;
 
     if (!is_null_or_empty(data.custom_icon)) {
@@ -51,24 +96,55 @@ export const Api: KeywichApi = {
       target_size: data.target_size,
       revision: or_default(data.revision, 0),
       custom_icon: icon_name,
+      exclude_lookalikes: or_default(data.exclude_lookalikes, false),
+      urls: or_default(data.urls, []),
+      fields: or_default(data.fields, []),
+      kind: or_default(data.kind, "derived"),
+      secret: data.secret,
     };
 
-    return invoke("insert_key", { data: key_data });
+    return invoke("insert_key", { data: key_data, options: options });
   },
 
   pin_key: function (id) {
     return invoke("pin_key", { key_id: id });
   },
 
-  search_keys: function (query) {
-    return invoke("search_keys", { query: query });
+  purge_key: function (id) {
+    return invoke("purge_key", { key_id: id });
+  },
+
+  restore_key: function (id) {
+    return invoke("restore_key", { key_id: id });
+  },
+
+  rotate_key: function (id) {
+    return invoke("rotate_key", { key_id: id });
+  },
+
+  search_keys: function (query, options) {
+    return invoke("search_keys", { query: query, options: options });
+  },
+
+  set_key_rotation: function (id, rotation_days) {
+    return invoke("set_key_rotation", {
+      key_id: id,
+      rotation_days: rotation_days,
+    });
+  },
+
+  set_tag_rotation: function (tag, rotation_days) {
+    return invoke("set_tag_rotation", {
+      tag: tag,
+      rotation_days: rotation_days,
+    });
   },
 
   unpin_key: function (id) {
     return invoke("unpin_key", { key_id: id });
   },
 
-  update_key: async function (id, data) {
+  update_key: async function (id, data, options) {
     let icon_name: string | undefined = undefined;
 
     if (data.custom_icon) {
@@ -92,15 +168,28 @@ export const Api: KeywichApi = {
       target_size: data.target_size,
       revision: or_default(data.revision, 0),
       custom_icon: icon_name,
+      exclude_lookalikes: or_default(data.exclude_lookalikes, false),
+      urls: or_default(data.urls, []),
+      fields: or_default(data.fields, []),
+      kind: or_default(data.kind, "derived"),
+      secret: data.secret,
     };
 
-    return invoke("update_key", { key_id: id, data: key_data });
+    return invoke("update_key", {
+      key_id: id,
+      data: key_data,
+      options: options,
+    });
   },
 
   generate_password_from: function (request) {
     return invoke("generate_password_from", { request: request });
   },
 
+  generate_password_from_history: function (request) {
+    return invoke("generate_password_from_history", { request: request });
+  },
+
   generate_password: function (request) {
     return invoke("generate_password", { request: request });
   },
@@ -132,14 +221,134 @@ export const Api: KeywichApi = {
     return invoke("insert_charset", { charset: charset });
   },
 
-  delete_charset: function (name) {
-    return invoke("delete_charset", { name: name });
+  update_charset: function (charset) {
+    return invoke("update_charset", { charset: charset });
+  },
+
+  rename_charset: function (name, new_name) {
+    return invoke("rename_charset", { name: name, new_name: new_name });
+  },
+
+  delete_charset: function (name, force) {
+    return invoke("delete_charset", { name: name, force: force });
   },
 
   get_charsets: function () {
     return invoke("get_charsets");
   },
 
+  get_charset_usage: function () {
+    return invoke("get_charset_usage");
+  },
+
+  analyze_charset: function (charset) {
+    return invoke("analyze_charset", { charset: charset });
+  },
+
+  get_tags: function () {
+    return invoke("get_tags");
+  },
+
+  rename_tag: function (name, new_name) {
+    return invoke("rename_tag", { name: name, new_name: new_name });
+  },
+
+  merge_tags: function (sources, target) {
+    return invoke("merge_tags", { sources: sources, target: target });
+  },
+
+  delete_tag: function (name) {
+    return invoke("delete_tag", { name: name });
+  },
+
+  get_collections: function () {
+    return invoke("get_collections");
+  },
+
+  create_collection: function (name, parent_id) {
+    return invoke("create_collection", { name: name, parent_id: parent_id });
+  },
+
+  create_collection_path: function (path) {
+    return invoke("create_collection_path", { path: path });
+  },
+
+  rename_collection: function (collection_id, name) {
+    return invoke("rename_collection", {
+      collection_id: collection_id,
+      name: name,
+    });
+  },
+
+  move_collection: function (collection_id, parent_id) {
+    return invoke("move_collection", {
+      collection_id: collection_id,
+      parent_id: parent_id,
+    });
+  },
+
+  delete_collection: function (collection_id) {
+    return invoke("delete_collection", { collection_id: collection_id });
+  },
+
+  move_keys_to_collection: function (key_ids, collection_id) {
+    return invoke("move_keys_to_collection", {
+      key_ids: key_ids,
+      collection_id: collection_id,
+    });
+  },
+
+  get_templates: function () {
+    return invoke("get_templates");
+  },
+
+  insert_template: function (data) {
+    return invoke("insert_template", {
+      data: {
+        ...data,
+        version: or_default(data.version, DEFAULT_HASH_VERSION),
+      },
+    });
+  },
+
+  update_template: function (template_id, data) {
+    return invoke("update_template", {
+      template_id: template_id,
+      data: {
+        ...data,
+        version: or_default(data.version, DEFAULT_HASH_VERSION),
+      },
+    });
+  },
+
+  delete_template: function (template_id) {
+    return invoke("delete_template", { template_id: template_id });
+  },
+
+  insert_key_from_template: async function (template_id, data) {
+    let icon_name: string | undefined = undefined;
+
+    if (!is_null_or_empty(data.custom_icon)) {
+      icon_name = await upload_icon(data.custom_icon);
+    }
+
+    const key_data: TemplateKeyRequest = {
+      domain: data.domain,
+      username: data.username,
+      notes: data.notes,
+      custom_icon: icon_name,
+      revision: or_default(data.revision, 0),
+      tags: or_default(data.tags, []),
+      urls: or_default(data.urls, []),
+      fields: or_default(data.fields, []),
+    };
+
+    return invoke("insert_key_from_template", {
+      template_id: template_id,
+      data: key_data,
+    });
+  },
+
   copy_to_clipboard: function (value) {
     return writeText(value);
   },
@@ -230,6 +439,14 @@ export const Api: KeywichApi = {
     return await invoke("verify_backup", { import_path: target });
   },
 
+  verify_search_index: function () {
+    return invoke("verify_search_index");
+  },
+
+  rebuild_search_index: function () {
+    return invoke("rebuild_search_index");
+  },
+
   select_file: async function (
     extensions?: string[] | undefined,
   ): Promise<string | undefined> {
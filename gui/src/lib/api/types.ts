@@ -1,7 +1,21 @@
+/**
+ * Error reported by keywich_lib. `code` is a stable machine-readable identifier such as
+ * `key_not_found` or `backup`, remaining fields are context for the specific error.
+ */
+export type LibErrorDetails = {
+  code: string;
+  message: string;
+  details?: string;
+  key_id?: number;
+  path?: string;
+  name?: string;
+};
+
 export type BaseErrorResponse = {
   message: string;
   code: string;
   details?: string;
+  source?: LibErrorDetails;
 };
 
 export type FieldErrorTypes =
@@ -21,10 +35,34 @@ export type ValidationErrorResponse = BaseErrorResponse & {
   fields: Record<string, FieldError[]>;
 };
 
+export type CharsetParseErrorReason =
+  | "mixed_class_range"
+  | "reversed_range"
+  | "unsupported_range"
+  | "trailing_range";
+
+export type CharsetParseError = {
+  reason: CharsetParseErrorReason;
+  token: string;
+  start: number;
+  end: number;
+  char_start: number;
+  char_end: number;
+  /** Replacement for the token, e.g. `a..z` for `z..a`. */
+  suggestion?: string;
+};
+
+export type CharsetErrorResponse = BaseErrorResponse & {
+  charset: CharsetParseError;
+};
+
 /**
  * Represents the structure of an error response.
  */
-export type ErrorResponse = BaseErrorResponse | ValidationErrorResponse;
+export type ErrorResponse =
+  | BaseErrorResponse
+  | ValidationErrorResponse
+  | CharsetErrorResponse;
 
 export type KeyOptions = {
   target_size: number;
@@ -36,6 +74,38 @@ export type KeyOptions = {
   tags?: string[];
   version?: string;
   custom_icon?: string;
+  exclude_lookalikes?: boolean;
+  urls?: KeyUrl[];
+  fields?: KeyField[];
+  kind?: KeyKind;
+  secret?: string;
+};
+
+/** `stored` keys return their stored secret instead of a generated password. */
+export type KeyKind = "derived" | "stored";
+
+export type UrlMatchRule = "exact_host" | "base_domain" | "prefix";
+
+export type KeyUrl = {
+  /** Host name, URL or app id. */
+  url: string;
+  /** Defaults to `base_domain`. */
+  match_rule?: UrlMatchRule;
+};
+
+export type KeyFieldType =
+  | "text"
+  | "url"
+  | "email"
+  | "number"
+  | "date"
+  | "hidden";
+
+export type KeyField = {
+  name: string;
+  /** Defaults to `text`. Hidden field values are not searchable. */
+  field_type?: KeyFieldType;
+  value: string;
 };
 
 export type PasswordOutputType = "Json" | "Base64" | "Text" | "Qr";
@@ -45,6 +115,11 @@ export type PasswordRequest = {
   output_type: PasswordOutputType;
 };
 
+export type HistoryPasswordRequest = {
+  history_id: number;
+  output_type: PasswordOutputType;
+};
+
 export type PasswordGenerateRequest = {
   content: string;
   revision: number;
@@ -54,6 +129,7 @@ export type PasswordGenerateRequest = {
   version: string;
   target_len: number;
   output_type: PasswordOutputType;
+  exclude_lookalikes?: boolean;
 };
 
 export type CharsetItem = {
@@ -64,10 +140,36 @@ export type CharsetItem = {
 
 export type CharsetOptions = CharsetItem;
 
+/** Keys with the same charset symbols, trashed keys are excluded. */
+export type CharsetUsage = {
+  name: string;
+  key_ids: number[];
+};
+
+export type CharsetAnalysis = {
+  /** Normalized charset definition, e.g. `0..9A..Za..z` for `a..zA..Z0..9`. */
+  canonical: string;
+  symbols: string;
+  size: number;
+  classes: {
+    lowercase: number;
+    uppercase: number;
+    numeric: number;
+    symbol: number;
+  };
+  /** Entropy of a single password symbol in bits. */
+  entropy_per_symbol: number;
+  ranges: { start: string; end: string }[];
+  /** Accepted definition problems, e.g. reversed ranges adding no symbols. */
+  warnings: CharsetParseError[];
+};
+
 export type ConfigFile = {
   is_light_theme?: boolean;
   color_theme?: string;
   locale?: string;
+  /** Days before deleted keys are purged from trash. */
+  trash_retention_days?: number;
 };
 
 export type AppConfig = {
@@ -102,6 +204,21 @@ type BaseKeyRequest = {
 
   /** Optional password generator version. */
   version?: string;
+
+  /** Removes look-alike symbols such as 0/O, 1/l/I and 5/S from the charset. */
+  exclude_lookalikes?: boolean;
+
+  /** Optional alternative hosts, URLs and app ids. The domain is still used for the password. */
+  urls?: KeyUrl[];
+
+  /** Optional custom fields such as account numbers or recovery emails. */
+  fields?: KeyField[];
+
+  /** Defaults to `derived`. */
+  kind?: KeyKind;
+
+  /** Secret of a `stored` key. Leave empty on updates to keep the current secret. */
+  secret?: string;
 };
 
 export type KeyRequest = BaseKeyRequest & {
@@ -130,6 +247,189 @@ export type KeyItem = {
   created_at: number;
   tags: string[];
   custom_icon?: string;
+  exclude_lookalikes: boolean;
+  /** Unix timestamp of the last password generation. */
+  last_used_at: number | null;
+  use_count: number;
+  /** Unix timestamp of the last update. */
+  updated_at: number | null;
+  /** Unix timestamp of the move to trash. */
+  deleted_at: number | null;
+  /** Rotation interval in days, overrides the tag rotation policies. */
+  rotation_days: number | null;
+  collection_id: number | null;
+  urls: KeyUrl[];
+  fields: KeyField[];
+  kind: KeyKind;
+};
+
+export type KeyHistoryItem = {
+  id: number;
+  key_id: number;
+  target_size: number;
+  revision: number;
+  charset: string;
+  domain: string;
+  username: string;
+  version: string;
+  exclude_lookalikes: boolean;
+  kind: KeyKind;
+  /** Unix timestamp of the update that replaced these parameters. */
+  replaced_at: number;
+};
+
+export type KeyRotationItem = {
+  id: number;
+  key_id: number;
+  revision: number;
+  rotated_at: number;
+};
+
+export type RotationPolicy = {
+  tag: string;
+  rotation_days: number;
+};
+
+export type RotationDueItem = KeyItem & {
+  /** Effective rotation interval of the key. */
+  rotation_days: number;
+  /** Unix timestamp of the last revision change. */
+  last_rotated_at: number;
+  due_at: number;
+};
+
+export type TagItem = {
+  name: string;
+  /** Number of keys using the tag, excluding the keys in trash. */
+  key_count: number;
+};
+
+export type UrlMatchKind = "exact_host" | "subdomain" | "base_domain";
+
+export type UrlMatchItem = KeyItem & {
+  match_kind: UrlMatchKind;
+};
+
+/** `same_derivation` keys generate the same password, `same_account` keys only share the domain and username. */
+export type DuplicateReason = "same_derivation" | "same_account";
+
+export type DuplicateGroup = {
+  reason: DuplicateReason;
+  keys: KeyItem[];
+};
+
+export type UniquenessCheck = "off" | "derivation" | "account";
+
+export type KeyWriteOptions = {
+  /**
+   * Rejects the key when an existing key conflicts with it, error code `00008` for `account` and
+   * `00012` for `derivation` conflicts. Defaults to `off`.
+   */
+  uniqueness?: UniquenessCheck;
+};
+
+export type KeyTemplateItem = {
+  id: number;
+  name: string;
+  charset: string;
+  target_size: number;
+  version: string;
+  exclude_lookalikes: boolean;
+  uniqueness: UniquenessCheck;
+  /** Tags added to the keys created from the template. */
+  tags: string[];
+  /** Rotation interval of the keys created from the template. */
+  rotation_days: number | null;
+  created_at: number;
+};
+
+export type KeyTemplateData = {
+  name: string;
+  charset: string;
+  target_size: number;
+  version?: string;
+  exclude_lookalikes?: boolean;
+  uniqueness?: UniquenessCheck;
+  tags?: string[];
+  rotation_days?: number;
+};
+
+/** Account details of a key created from a template, password parameters come from the template. */
+export type TemplateKeyRequest = {
+  domain: string;
+  username: string;
+  notes?: string;
+  custom_icon?: string;
+  revision?: number;
+  /** Added along with the template tags. */
+  tags?: string[];
+  urls?: KeyUrl[];
+  fields?: KeyField[];
+};
+
+export type CollectionItem = {
+  id: number;
+  name: string;
+  /** Parent collection, `null` for root collections. */
+  parent_id: number | null;
+  created_at: number;
+  /** Collection names from the root collection joined with `/`. */
+  path: string;
+};
+
+export type KeyBulkAction =
+  | { type: "add_tags"; value: string[] }
+  | { type: "remove_tags"; value: string[] }
+  | { type: "pin" }
+  | { type: "unpin" }
+  | { type: "delete" }
+  | { type: "bump_revision" }
+  | { type: "set_charset"; value: string };
+
+export type KeySort =
+  | "created_at"
+  | "domain"
+  | "username"
+  | "pinned_first"
+  | "frecency";
+
+export type KeyListOptions = {
+  /** `next_cursor` value of the previous page. */
+  cursor?: string;
+  limit?: number;
+  /** Defaults to `created_at` for listing and to relevance order for search. */
+  sort?: KeySort;
+  pinned_only?: boolean;
+  /** Limits the keys to the collection and its sub collections. */
+  collection_id?: number;
+};
+
+export type HighlightSegment = {
+  text: string;
+  matched: boolean;
+};
+
+export type SearchHighlights = {
+  domain: HighlightSegment[];
+  username: HighlightSegment[];
+  /** Short fragment of the notes around the best match. */
+  notes: HighlightSegment[] | null;
+  /** Tags joined with a single space. */
+  tags: HighlightSegment[];
+};
+
+export type SearchResult = KeyItem & {
+  /** Relevance score, higher is more relevant. */
+  score: number;
+  highlights: SearchHighlights;
+};
+
+export type KeyPage<T = KeyItem> = {
+  items: T[];
+  /** Cursor for the next page, `null` when there are no more keys. */
+  next_cursor: string | null;
+  /** Number of keys matching the filters. */
+  total: number;
 };
 
 export type VerifyResponse = {
@@ -137,17 +437,55 @@ export type VerifyResponse = {
   path: string;
 };
 
+/** Key ids with search index problems, keys in trash are included. */
+export type SearchIndexReport = {
+  missing: number[];
+  stale: number[];
+  /** Index rows of keys that no longer exist. */
+  orphaned: number[];
+};
+
+/** Payload of the `profile_event` event emitted after each profile change. */
+export type ProfileEvent =
+  | { type: "key_created"; key_id: number }
+  | { type: "key_updated"; key_id: number }
+  | { type: "key_deleted"; key_id: number }
+  | { type: "key_pinned"; key_id: number; pinned: boolean }
+  | { type: "charset_changed"; name: string }
+  | { type: "restore_completed" };
+
 export interface KeyApi {
+  bulk_update_keys(ids: number[], action: KeyBulkAction): Promise<number>;
   delete_key(id: number): Promise<void>;
+  empty_trash(): Promise<void>;
   get_key_by_id(id: number): Promise<KeyItem>;
-  get_keys(): Promise<KeyItem[]>;
-  get_keys(): Promise<KeyItem[]>;
+  get_key_history(id: number): Promise<KeyHistoryItem[]>;
+  get_key_rotations(id: number): Promise<KeyRotationItem[]>;
+  get_keys(options?: KeyListOptions): Promise<KeyPage>;
+  get_keys_due_for_rotation(within_days?: number): Promise<RotationDueItem[]>;
   get_pinned_keys(): Promise<KeyItem[]>;
-  insert_key(request: KeyRequest): Promise<number>;
+  get_rotation_policies(): Promise<RotationPolicy[]>;
+  get_stale_keys(unused_days: number): Promise<KeyItem[]>;
+  find_keys_for_url(url: string): Promise<UrlMatchItem[]>;
+  find_duplicate_keys(): Promise<DuplicateGroup[]>;
+  get_trashed_keys(): Promise<KeyItem[]>;
+  insert_key(request: KeyRequest, options?: KeyWriteOptions): Promise<number>;
   pin_key(id: number): Promise<void>;
-  search_keys(query: string): Promise<KeyItem[]>;
+  purge_key(id: number): Promise<void>;
+  restore_key(id: number): Promise<void>;
+  rotate_key(id: number): Promise<number>;
+  search_keys(
+    query: string,
+    options?: KeyListOptions,
+  ): Promise<KeyPage<SearchResult>>;
+  set_key_rotation(id: number, rotation_days: number | null): Promise<void>;
+  set_tag_rotation(tag: string, rotation_days: number | null): Promise<void>;
   unpin_key(id: number): Promise<void>;
-  update_key(id: number, request: KeyUpdateRequest): Promise<void>;
+  update_key(
+    id: number,
+    request: KeyUpdateRequest,
+    options?: KeyWriteOptions,
+  ): Promise<void>;
 }
 
 export interface AccountApi {
@@ -164,8 +502,44 @@ export interface ConfigApi {
 
 export interface CharsetApi {
   insert_charset(charset: CharsetOptions): Promise<string>;
-  delete_charset(name: string): Promise<void>;
+  update_charset(charset: CharsetOptions): Promise<void>;
+  rename_charset(name: string, new_name: string): Promise<void>;
+  /** Fails with error code `00011` when keys use the charset, unless `force` is set. */
+  delete_charset(name: string, force?: boolean): Promise<void>;
   get_charsets(): Promise<CharsetItem[]>;
+  get_charset_usage(): Promise<CharsetUsage[]>;
+  analyze_charset(charset: string): Promise<CharsetAnalysis>;
+}
+
+export interface TagApi {
+  get_tags(): Promise<TagItem[]>;
+  rename_tag(name: string, new_name: string): Promise<number>;
+  merge_tags(sources: string[], target: string): Promise<number>;
+  delete_tag(name: string): Promise<number>;
+}
+
+export interface CollectionApi {
+  get_collections(): Promise<CollectionItem[]>;
+  create_collection(name: string, parent_id?: number): Promise<number>;
+  create_collection_path(path: string): Promise<number>;
+  rename_collection(collection_id: number, name: string): Promise<void>;
+  move_collection(collection_id: number, parent_id?: number): Promise<void>;
+  delete_collection(collection_id: number): Promise<void>;
+  move_keys_to_collection(
+    key_ids: number[],
+    collection_id?: number,
+  ): Promise<number>;
+}
+
+export interface TemplateApi {
+  get_templates(): Promise<KeyTemplateItem[]>;
+  insert_template(data: KeyTemplateData): Promise<number>;
+  update_template(template_id: number, data: KeyTemplateData): Promise<void>;
+  delete_template(template_id: number): Promise<void>;
+  insert_key_from_template(
+    template_id: number,
+    request: TemplateKeyRequest,
+  ): Promise<number>;
 }
 
 export interface UtilityApi {
@@ -180,8 +554,16 @@ export interface UtilityApi {
   verify_backup(path?: string): Promise<VerifyResponse>;
 }
 
+export interface MaintenanceApi {
+  verify_search_index(): Promise<SearchIndexReport>;
+  rebuild_search_index(): Promise<void>;
+}
+
 export interface PasswordApi {
   generate_password_from(request: PasswordRequest): Promise<string>;
+  generate_password_from_history(
+    request: HistoryPasswordRequest,
+  ): Promise<string>;
   generate_password(request: PasswordGenerateRequest): Promise<string>;
 }
 
@@ -190,5 +572,9 @@ export interface KeywichApi
     PasswordApi,
     UtilityApi,
     CharsetApi,
+    TagApi,
+    CollectionApi,
+    TemplateApi,
+    MaintenanceApi,
     ConfigApi,
     AccountApi {}
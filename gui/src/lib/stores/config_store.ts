@@ -13,6 +13,7 @@ const CONFIG_DEFAULTS: ConfigFile = {
   color_theme: "crimson",
   is_light_theme: false,
   locale: "en",
+  trash_retention_days: 30,
 };
 
 export const ThemeOptions = [
@@ -67,6 +68,15 @@ function set_theme(theme: ThemeOptionType) {
   });
 }
 
+function set_trash_retention(days: number) {
+  update((current) => {
+    current.trash_retention_days = days;
+    write_scheduler.update(current);
+
+    return current;
+  });
+}
+
 function set_dark_mode() {
   setMode(false);
   update((current) => {
@@ -130,4 +140,5 @@ export const configStore = {
   set_dark_mode,
   set_theme,
   set_locale,
+  set_trash_retention,
 };
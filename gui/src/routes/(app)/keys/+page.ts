@@ -1,14 +1,33 @@
 import type { PageLoad } from "./$types";
 import { Api, is_null_or_empty } from "$lib";
 
+const URL_PATTERN = /^[a-z][a-z0-9+.-]*:\/\/\S+$/i;
+
 export const load: PageLoad = async ({ url }) => {
   const search_query = url.searchParams.get("s");
-  const keys = is_null_or_empty(search_query)
+
+  // Pasted links list the keys of the address, and fall back to text search without a match.
+  if (search_query && URL_PATTERN.test(search_query.trim())) {
+    const matches = await Api.find_keys_for_url(search_query);
+
+    if (matches.length > 0) {
+      return {
+        keys: matches,
+        next_cursor: null,
+        total: matches.length,
+        search_query: search_query,
+      };
+    }
+  }
+
+  const page = is_null_or_empty(search_query)
     ? await Api.get_keys()
     : await Api.search_keys(search_query);
 
   return {
-    keys: keys,
+    keys: page.items,
+    next_cursor: page.next_cursor,
+    total: page.total,
     search_query: search_query,
   };
 };
@@ -4,9 +4,11 @@ import { Api } from "$lib";
 export const load: PageLoad = async ({ url }) => {
   const section = url.searchParams.get("section");
   const charsets = await Api.get_charsets();
+  const trashed_keys = await Api.get_trashed_keys();
 
   return {
     charsets: charsets,
+    trashed_keys: trashed_keys,
     section: section ?? undefined,
   };
 };
@@ -0,0 +1,112 @@
+//! Key listing benchmark against a 100k key profile.
+//!
+//! Run with `cargo bench --bench key_listing --features profile`.
+
+use keywich_lib::profile::keys::{KeyData, KeyKind, KeyListOptions, KeySort, SearchQuery};
+use keywich_lib::profile::utils::tag_list::TagList;
+use keywich_lib::profile::ProfileDB;
+use std::time::{Duration, Instant};
+
+const KEY_COUNT: usize = 100_000;
+const ITERATIONS: u32 = 20;
+
+async fn create_profile() -> ProfileDB {
+  let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+
+  for idx in 0..KEY_COUNT {
+    profile_db
+      .insert_key(KeyData {
+        notes: Some(format!("notes for key {}", idx)),
+        domain: format!("domain-{}.example.com", idx % 5000),
+        version: "v1".into(),
+        custom_icon: None,
+        username: format!("user{}", idx),
+        charset: "a..zA..Z0..9".into(),
+        revision: 0,
+        target_size: 16,
+        tags: TagList::from([format!("tag{}", idx % 100), String::from("bench")]),
+        exclude_lookalikes: false,
+        urls: Vec::new(),
+        fields: Vec::new(),
+        kind: KeyKind::Derived,
+        secret: None,
+      })
+      .await
+      .unwrap();
+  }
+
+  profile_db
+}
+
+fn report(name: &str, elapsed: Duration) {
+  println!("{:<32} {:>10.3?} / iter", name, elapsed / ITERATIONS);
+}
+
+async fn bench_pages(profile_db: &ProfileDB, name: &str, sort: Option<KeySort>) {
+  let mut cursor = None;
+  let started = Instant::now();
+
+  for _ in 0..ITERATIONS {
+    let page = profile_db
+      .get_keys_page(KeyListOptions {
+        cursor: cursor.take(),
+        sort,
+        ..KeyListOptions::default()
+      })
+      .await
+      .unwrap();
+
+    cursor = page.next_cursor;
+  }
+
+  report(name, started.elapsed());
+}
+
+fn main() {
+  let runtime = tokio::runtime::Builder::new_current_thread()
+    .enable_all()
+    .build()
+    .unwrap();
+
+  runtime.block_on(async {
+    let started = Instant::now();
+    let profile_db = create_profile().await;
+    println!("{} keys created in {:.3?}", KEY_COUNT, started.elapsed());
+
+    let started = Instant::now();
+    for _ in 0..ITERATIONS {
+      profile_db.get_keys(false).await.unwrap();
+    }
+    report("get_keys (all)", started.elapsed());
+
+    bench_pages(&profile_db, "get_keys_page created_at", None).await;
+    bench_pages(&profile_db, "get_keys_page domain", Some(KeySort::Domain)).await;
+    bench_pages(
+      &profile_db,
+      "get_keys_page username",
+      Some(KeySort::Username),
+    )
+    .await;
+    bench_pages(
+      &profile_db,
+      "get_keys_page pinned_first",
+      Some(KeySort::PinnedFirst),
+    )
+    .await;
+
+    let started = Instant::now();
+    for _ in 0..ITERATIONS {
+      profile_db
+        .search_keys_page(SearchQuery::new("tag:tag42"), KeyListOptions::default())
+        .await
+        .unwrap();
+    }
+    report("search_keys_page", started.elapsed());
+
+    let started = Instant::now();
+    for _ in 0..ITERATIONS {
+      profile_db.count_keys(false).await.unwrap();
+    }
+    report("count_keys", started.elapsed());
+  });
+}
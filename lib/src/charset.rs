@@ -4,8 +4,22 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
+pub mod analysis;
 pub mod parser;
 
+/// Symbols that are easily mistaken for each other when a password is read aloud or typed from
+/// another screen (`0/O`, `1/l/I` and `5/S`).
+pub const LOOKALIKE_SYMBOLS: &[char] = &['0', 'O', '1', 'l', 'I', '5', 'S'];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CharsetOptions {
+  /// Removes [LOOKALIKE_SYMBOLS] from the parsed charset.
+  pub exclude_lookalikes: bool,
+}
+
+/// Parsed charset. Equality and hashing are based on the charset symbols, so different spellings
+/// of the same charset such as `a..d` and `dcba` are equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Charset {
   charset: Box<str>,
 }
@@ -16,6 +30,22 @@ impl Charset {
     Ok(charset)
   }
 
+  pub fn with_options(dictionary_text: &str, options: CharsetOptions) -> Result<Charset, Error> {
+    let mut characters = parse(dictionary_text)?;
+
+    if options.exclude_lookalikes {
+      characters.retain(|symbol| !LOOKALIKE_SYMBOLS.contains(&symbol));
+    }
+
+    if characters.is_empty() {
+      return Err(Error::EmptyCharset);
+    }
+
+    Ok(Charset {
+      charset: characters.into_boxed_str(),
+    })
+  }
+
   pub fn transform_str(&self, content: &str) -> String {
     self.transform_bytes(content.as_bytes())
   }
@@ -41,10 +71,7 @@ impl TryFrom<&str> for Charset {
   type Error = Error;
 
   fn try_from(value: &str) -> Result<Self, Self::Error> {
-    let characters = parse(value)?;
-    Ok(Charset {
-      charset: characters.into_boxed_str(),
-    })
+    Self::with_options(value, CharsetOptions::default())
   }
 }
 
@@ -54,9 +81,62 @@ impl Display for Charset {
   }
 }
 
+#[cfg(any(feature = "json", feature = "profile"))]
+impl serde::Serialize for Charset {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    serializer.serialize_str(&self.canonical())
+  }
+}
+
+#[cfg(any(feature = "json", feature = "profile"))]
+impl<'de> serde::Deserialize<'de> for Charset {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: serde::Deserializer<'de>,
+  {
+    let dictionary_text = <String as serde::Deserialize>::deserialize(deserializer)?;
+    Charset::new(&dictionary_text).map_err(serde::de::Error::custom)
+  }
+}
+
 pub(crate) fn validate_charset(value: &str) -> Result<(), validator::ValidationError> {
-  match Charset::try_from(value) {
+  validate_charset_with(value, CharsetOptions::default())
+}
+
+pub(crate) fn validate_charset_with(
+  value: &str,
+  options: CharsetOptions,
+) -> Result<(), validator::ValidationError> {
+  match Charset::with_options(value, options) {
     Ok(_) => Ok(()),
+    Err(Error::EmptyCharset) => Err(validator::ValidationError {
+      params: HashMap::new(),
+      message: Some(Cow::Borrowed("Charset has no usable symbols.")),
+      code: Cow::Borrowed("charset"),
+    }),
+    Err(Error::ParserInvalidRange(err)) => {
+      let mut validation_error = validator::ValidationError {
+        params: HashMap::new(),
+        message: Some(Cow::Owned(err.to_string())),
+        code: Cow::Borrowed("charset"),
+      };
+
+      validation_error.add_param(Cow::Borrowed("reason"), &err.reason.code());
+      validation_error.add_param(Cow::Borrowed("token"), &err.token);
+      validation_error.add_param(Cow::Borrowed("start"), &err.start);
+      validation_error.add_param(Cow::Borrowed("end"), &err.end);
+      validation_error.add_param(Cow::Borrowed("char_start"), &err.char_start);
+      validation_error.add_param(Cow::Borrowed("char_end"), &err.char_end);
+
+      if let Some(suggestion) = &err.suggestion {
+        validation_error.add_param(Cow::Borrowed("suggestion"), suggestion);
+      }
+
+      Err(validation_error)
+    }
     Err(_) => Err(validator::ValidationError {
       params: HashMap::new(),
       message: Some(Cow::Borrowed("Invalid charset.")),
@@ -0,0 +1,177 @@
+use crate::charset::parser::{parse_with_warnings, ParseError};
+use crate::charset::Charset;
+use crate::errors::Error;
+
+/// Number of symbols in each character class.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(feature = "json", feature = "profile"), derive(serde::Serialize))]
+pub struct CharsetClasses {
+  pub lowercase: usize,
+  pub uppercase: usize,
+  pub numeric: usize,
+  pub symbol: usize,
+}
+
+/// Continuous run of symbols from the same character class. Single symbols have the same start and
+/// end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(feature = "json", feature = "profile"), derive(serde::Serialize))]
+pub struct CharsetRange {
+  pub start: char,
+  pub end: char,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "json", feature = "profile"), derive(serde::Serialize))]
+pub struct CharsetAnalysis {
+  /// Normalized charset definition, e.g. `0..9A..Za..z` for `a..zA..Z0..9`.
+  pub canonical: String,
+  /// All symbols in the charset ordered by their code points.
+  pub symbols: String,
+  pub size: usize,
+  pub classes: CharsetClasses,
+  /// Entropy of a single password symbol in bits.
+  pub entropy_per_symbol: f64,
+  pub ranges: Vec<CharsetRange>,
+  /// Accepted problems of the charset definition, such as reversed ranges adding no symbols.
+  pub warnings: Vec<ParseError>,
+}
+
+/// Parses and analyzes the charset definition, including the definition warnings.
+pub fn analyze_charset(dictionary_text: &str) -> Result<CharsetAnalysis, Error> {
+  let (_, warnings) = parse_with_warnings(dictionary_text)?;
+  let charset = Charset::new(dictionary_text)?;
+
+  Ok(CharsetAnalysis {
+    warnings,
+    ..charset.analyze()
+  })
+}
+
+#[derive(PartialEq, Eq)]
+enum SymbolClass {
+  Lowercase,
+  Uppercase,
+  Numeric,
+  Symbol,
+}
+
+impl SymbolClass {
+  fn of(symbol: char) -> Self {
+    match symbol {
+      'a'..='z' => SymbolClass::Lowercase,
+      'A'..='Z' => SymbolClass::Uppercase,
+      '0'..='9' => SymbolClass::Numeric,
+      _ => SymbolClass::Symbol,
+    }
+  }
+}
+
+// Shorter runs are written as plain symbols since "ab" is shorter than "a..b".
+const MIN_FOLD_LEN: u32 = 3;
+
+impl CharsetRange {
+  fn len(&self) -> u32 {
+    u32::from(self.end) - u32::from(self.start) + 1
+  }
+}
+
+impl Charset {
+  pub fn symbols(&self) -> &str {
+    &self.charset
+  }
+
+  pub fn len(&self) -> usize {
+    self.charset.chars().count()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.charset.is_empty()
+  }
+
+  pub fn classes(&self) -> CharsetClasses {
+    let mut classes = CharsetClasses::default();
+
+    for symbol in self.charset.chars() {
+      match SymbolClass::of(symbol) {
+        SymbolClass::Lowercase => classes.lowercase += 1,
+        SymbolClass::Uppercase => classes.uppercase += 1,
+        SymbolClass::Numeric => classes.numeric += 1,
+        SymbolClass::Symbol => classes.symbol += 1,
+      }
+    }
+
+    classes
+  }
+
+  pub fn entropy_per_symbol(&self) -> f64 {
+    (self.len() as f64).log2()
+  }
+
+  /// Folds symbols back into ranges. Only letters and digits are folded, other symbols are always
+  /// returned as single symbol ranges.
+  pub fn ranges(&self) -> Vec<CharsetRange> {
+    let mut ranges: Vec<CharsetRange> = Vec::new();
+
+    for symbol in self.charset.chars() {
+      let class = SymbolClass::of(symbol);
+
+      match ranges.last_mut() {
+        Some(range)
+          if class != SymbolClass::Symbol
+            && SymbolClass::of(range.end) == class
+            && u32::from(range.end) + 1 == u32::from(symbol) =>
+        {
+          range.end = symbol;
+        }
+        _ => ranges.push(CharsetRange {
+          start: symbol,
+          end: symbol,
+        }),
+      }
+    }
+
+    ranges
+  }
+
+  /// Returns the normalized charset definition. Charsets with the same symbols always have the same
+  /// canonical definition regardless of how they are written.
+  ///
+  /// `.` is written first, it can be part of a range operator when placed next to other symbols.
+  pub fn canonical(&self) -> String {
+    let mut canonical = String::new();
+
+    if self.charset.contains('.') {
+      canonical.push('.');
+    }
+
+    for range in self.ranges() {
+      if range.start == '.' {
+        continue;
+      }
+
+      // Ranges never contain '.', only letters and digits are folded.
+      if range.len() >= MIN_FOLD_LEN {
+        canonical.push(range.start);
+        canonical.push_str("..");
+        canonical.push(range.end);
+      } else {
+        canonical.extend(range.start..=range.end);
+      }
+    }
+
+    canonical
+  }
+
+  pub fn analyze(&self) -> CharsetAnalysis {
+    CharsetAnalysis {
+      canonical: self.canonical(),
+      symbols: self.charset.to_string(),
+      size: self.len(),
+      classes: self.classes(),
+      entropy_per_symbol: self.entropy_per_symbol(),
+      ranges: self.ranges(),
+      warnings: Vec::new(),
+    }
+  }
+}
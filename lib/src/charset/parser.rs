@@ -1,5 +1,6 @@
 use crate::errors::Error;
 use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
 
 // "A..Za..z0..9-=#./,£$%~[](){} "
 trait SymbolCollection {
@@ -32,22 +33,144 @@ enum SymbolType {
   Range { start: char, end: char },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "json", feature = "profile"),
+  derive(serde::Serialize),
+  serde(rename_all = "snake_case")
+)]
+pub enum ParseErrorReason {
+  /// Range bounds belong to different classes, e.g. `a..Z` or `A..9`.
+  MixedClassRange,
+  /// Range start is greater than the range end, e.g. `z..a`. Reversed ranges are accepted as an
+  /// empty range for the existing keys and only reported as a warning, see [parse_with_warnings].
+  ReversedRange,
+  /// Range bounds are not ASCII letters or digits, e.g. `!..~`.
+  UnsupportedRange,
+  /// Range operator has no end symbol, e.g. `a..`.
+  TrailingRange,
+}
+
+impl ParseErrorReason {
+  pub fn code(&self) -> &'static str {
+    match self {
+      ParseErrorReason::MixedClassRange => "mixed_class_range",
+      ParseErrorReason::ReversedRange => "reversed_range",
+      ParseErrorReason::UnsupportedRange => "unsupported_range",
+      ParseErrorReason::TrailingRange => "trailing_range",
+    }
+  }
+}
+
+/// Charset parser error with the offending token and its location in the charset text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "json", feature = "profile"), derive(serde::Serialize))]
+pub struct ParseError {
+  pub reason: ParseErrorReason,
+  pub token: String,
+  /// Byte offset of the token start.
+  pub start: usize,
+  /// Byte offset of the token end (exclusive).
+  pub end: usize,
+  /// Character offset of the token start.
+  pub char_start: usize,
+  /// Character offset of the token end (exclusive).
+  pub char_end: usize,
+  /// Replacement for the token, e.g. `a..z` for the reversed `z..a` range.
+  #[cfg_attr(
+    any(feature = "json", feature = "profile"),
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub suggestion: Option<String>,
+}
+
+impl Display for ParseError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self.reason {
+      ParseErrorReason::MixedClassRange => write!(
+        f,
+        "Range '{}' at position {} mixes different character classes.",
+        self.token,
+        self.char_start + 1
+      ),
+      ParseErrorReason::ReversedRange => write!(
+        f,
+        "Range '{}' at position {} is reversed.",
+        self.token,
+        self.char_start + 1
+      ),
+      ParseErrorReason::UnsupportedRange => write!(
+        f,
+        "Range '{}' at position {} is not supported, only letters and digits can be used as range.",
+        self.token,
+        self.char_start + 1
+      ),
+      ParseErrorReason::TrailingRange => write!(
+        f,
+        "Range '{}' at position {} has no end symbol.",
+        self.token,
+        self.char_start + 1
+      ),
+    }?;
+
+    if let Some(suggestion) = &self.suggestion {
+      write!(f, " Did you mean '{}'?", suggestion)?;
+    }
+
+    Ok(())
+  }
+}
+
 pub fn parse(dictionary_text: &str) -> Result<String, Error> {
-  let mut iter = dictionary_text.chars().peekable();
+  let (symbols, _) = parse_with_warnings(dictionary_text)?;
+  Ok(symbols)
+}
+
+/// Parses the charset and returns the accepted problems as warnings. Reversed ranges such as `z..a`
+/// add no symbols, charsets created before the range checks may still contain them.
+pub fn parse_with_warnings(dictionary_text: &str) -> Result<(String, Vec<ParseError>), Error> {
+  let mut iter = dictionary_text.char_indices().enumerate().peekable();
   let mut set: BTreeSet<char> = BTreeSet::new();
+  let mut warnings: Vec<ParseError> = Vec::new();
 
-  while let Some(input_char) = iter.next() {
+  while let Some((char_start, (start, input_char))) = iter.next() {
     let current_symbol = parse_symbol(input_char);
 
-    if let Some('.') = iter.peek() {
+    if let Some((_, (_, '.'))) = iter.peek() {
       iter.next();
-      if let Some('.') = iter.peek() {
+      if let Some((_, (_, '.'))) = iter.peek() {
         iter.next(); //skipping second '.'
-        if let Some(char) = iter.next() {
-          let range_token: SymbolType = parse_range((input_char, char))?;
-          set.insert_symbol(range_token);
+        if let Some((char_end, (end, char))) = iter.next() {
+          let end = end + char.len_utf8();
+          let range_error = |reason| ParseError {
+            reason,
+            token: String::from(&dictionary_text[start..end]),
+            start,
+            end,
+            char_start,
+            char_end: char_end + 1,
+            suggestion: suggest_range(reason, input_char, char),
+          };
+
+          match parse_range((input_char, char)) {
+            Ok(range_token) => {
+              set.insert_symbol(range_token);
+            }
+            Err(ParseErrorReason::ReversedRange) => {
+              warnings.push(range_error(ParseErrorReason::ReversedRange));
+            }
+            Err(reason) => return Err(Error::ParserInvalidRange(range_error(reason))),
+          }
         } else {
-          return Err(Error::ParserInvalidRange);
+          return Err(Error::ParserInvalidRange(ParseError {
+            reason: ParseErrorReason::TrailingRange,
+            token: String::from(&dictionary_text[start..]),
+            start,
+            end: dictionary_text.len(),
+            char_start,
+            char_end: char_start + 3,
+            suggestion: Some(format!("{}.", input_char)),
+          }));
         }
       } else {
         set.insert_symbol(current_symbol);
@@ -58,24 +181,75 @@ pub fn parse(dictionary_text: &str) -> Result<String, Error> {
     }
   }
 
-  Ok(String::from_iter(set))
+  Ok((String::from_iter(set), warnings))
+}
+
+fn class_bounds(symbol: char) -> Option<(char, char)> {
+  match symbol {
+    'a'..='z' => Some(('a', 'z')),
+    'A'..='Z' => Some(('A', 'Z')),
+    '0'..='9' => Some(('0', '9')),
+    _ => None,
+  }
+}
+
+fn format_range(start: char, end: char) -> String {
+  if start == end {
+    String::from(start)
+  } else {
+    format!("{}..{}", start, end)
+  }
+}
+
+/// Returns a valid definition close to the rejected range.
+fn suggest_range(reason: ParseErrorReason, start: char, end: char) -> Option<String> {
+  match reason {
+    ParseErrorReason::ReversedRange => Some(format_range(end, start)),
+    // Splits the range at the class boundary, `a..Z` becomes `a..zA..Z`.
+    ParseErrorReason::MixedClassRange => {
+      let (_, start_class_end) = class_bounds(start)?;
+      let (end_class_start, _) = class_bounds(end)?;
+
+      Some(format!(
+        "{}{}",
+        format_range(start, start_class_end),
+        format_range(end_class_start, end)
+      ))
+    }
+    // Lists the symbols of the range, `!..%` becomes `!"#$%`.
+    ParseErrorReason::UnsupportedRange => {
+      let (start, end) = (start.min(end), start.max(end));
+
+      if start.is_ascii_graphic() && end.is_ascii_graphic() {
+        Some(String::from_iter(start..=end))
+      } else {
+        None
+      }
+    }
+    ParseErrorReason::TrailingRange => None,
+  }
 }
 
-fn parse_range(range: (char, char)) -> Result<SymbolType, Error> {
+fn parse_range(range: (char, char)) -> Result<SymbolType, ParseErrorReason> {
   match range {
-    (range_start @ 'a'..='z', range_end @ 'a'..='z') => Ok(SymbolType::Range {
-      start: range_start,
-      end: range_end,
-    }),
-    (range_start @ 'A'..='Z', range_end @ 'A'..='Z') => Ok(SymbolType::Range {
-      start: range_start,
-      end: range_end,
-    }),
-    (range_start @ '0'..='9', range_end @ '0'..='9') => Ok(SymbolType::Range {
-      start: range_start,
-      end: range_end,
-    }),
-    _ => Err(Error::ParserInvalidRange),
+    (range_start @ 'a'..='z', range_end @ 'a'..='z')
+    | (range_start @ 'A'..='Z', range_end @ 'A'..='Z')
+    | (range_start @ '0'..='9', range_end @ '0'..='9') => {
+      if range_start <= range_end {
+        Ok(SymbolType::Range {
+          start: range_start,
+          end: range_end,
+        })
+      } else {
+        Err(ParseErrorReason::ReversedRange)
+      }
+    }
+    (range_start, range_end)
+      if range_start.is_ascii_alphanumeric() && range_end.is_ascii_alphanumeric() =>
+    {
+      Err(ParseErrorReason::MixedClassRange)
+    }
+    _ => Err(ParseErrorReason::UnsupportedRange),
   }
 }
 
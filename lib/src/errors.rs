@@ -1,27 +1,146 @@
+use crate::charset::parser::ParseError;
 use std::fmt::{Debug, Display, Formatter};
 use std::path::PathBuf;
 
+/// Underlying error that caused an [Error], accessible via [std::error::Error::source].
+pub type ErrorSource = Box<dyn std::error::Error + Send + Sync + 'static>;
+
 #[derive(Debug)]
 pub enum Error {
   InvalidHashOutput,
-  ParserInvalidRange,
+  ParserInvalidRange(ParseError),
+  EmptyCharset,
+  DuplicateCharset(String),
+  DuplicateCharsetName(String),
+  CharsetNotFound(String),
+  /// Charset is used by keys and cannot be deleted without force.
+  CharsetInUse {
+    name: String,
+    key_count: usize,
+  },
   InvalidInput,
-  DatabaseError(String),
-  DatabaseMigrateError(String),
+  DatabaseError(ErrorSource),
+  DatabaseMigrateError(ErrorSource),
   InvalidDatabasePath(PathBuf),
-  InvalidTime(String),
+  InvalidTime(ErrorSource),
   InvalidHashFuncVersion,
-  InvalidJsonError(String),
-  InvalidQrError(String),
+  InvalidJsonError(ErrorSource),
+  InvalidQrError(ErrorSource),
   ValidationError(validator::ValidationErrors),
-  BackupError(String),
+  KeyNotFound {
+    key_id: i64,
+  },
+  /// Existing key conflicting with the uniqueness check of an insert or update.
+  #[cfg(feature = "profile")]
+  DuplicateKey {
+    key_id: i64,
+    check: crate::profile::keys::UniquenessCheck,
+  },
+  InvalidCursor(String),
+  CollectionNotFound {
+    collection_id: i64,
+  },
+  DuplicateCollection(String),
+  /// Collection cannot be moved under itself or one of its sub collections.
+  InvalidCollectionParent {
+    collection_id: i64,
+  },
+  TemplateNotFound {
+    template_id: i64,
+  },
+  DuplicateTemplate(String),
+  BackupError {
+    reason: String,
+    path: Option<PathBuf>,
+    source: Option<ErrorSource>,
+  },
+}
+
+impl Error {
+  /// Stable machine-readable error code. Codes are never changed or reused once released, the UI
+  /// and localization files can safely depend on them.
+  pub fn code(&self) -> &'static str {
+    match self {
+      Error::InvalidHashOutput => "invalid_hash_output",
+      Error::ParserInvalidRange(_) => "invalid_charset_range",
+      Error::EmptyCharset => "empty_charset",
+      Error::DuplicateCharset(_) => "duplicate_charset",
+      Error::DuplicateCharsetName(_) => "duplicate_charset_name",
+      Error::CharsetNotFound(_) => "charset_not_found",
+      Error::CharsetInUse { .. } => "charset_in_use",
+      Error::InvalidInput => "invalid_input",
+      Error::DatabaseError(_) => "database",
+      Error::DatabaseMigrateError(_) => "database_migrate",
+      Error::InvalidDatabasePath(_) => "invalid_database_path",
+      Error::InvalidTime(_) => "invalid_time",
+      Error::InvalidHashFuncVersion => "invalid_hash_func_version",
+      Error::InvalidJsonError(_) => "invalid_json",
+      Error::InvalidQrError(_) => "invalid_qr",
+      Error::ValidationError(_) => "validation",
+      Error::KeyNotFound { .. } => "key_not_found",
+      #[cfg(feature = "profile")]
+      Error::DuplicateKey { .. } => "duplicate_key",
+      Error::InvalidCursor(_) => "invalid_cursor",
+      Error::CollectionNotFound { .. } => "collection_not_found",
+      Error::DuplicateCollection(_) => "duplicate_collection",
+      Error::InvalidCollectionParent { .. } => "invalid_collection_parent",
+      Error::TemplateNotFound { .. } => "template_not_found",
+      Error::DuplicateTemplate(_) => "duplicate_template",
+      Error::BackupError { .. } => "backup",
+    }
+  }
+}
+
+#[cfg(feature = "profile")]
+impl Error {
+  pub(crate) fn backup(reason: impl Into<String>) -> Self {
+    Error::BackupError {
+      reason: reason.into(),
+      path: None,
+      source: None,
+    }
+  }
+
+  pub(crate) fn backup_from(reason: impl Into<String>, source: impl Into<ErrorSource>) -> Self {
+    Error::BackupError {
+      reason: reason.into(),
+      path: None,
+      source: Some(source.into()),
+    }
+  }
+
+  /// Attaches the file path context. Only applies to backup errors, other variants are returned
+  /// unchanged.
+  pub(crate) fn with_path(self, target_path: impl Into<PathBuf>) -> Self {
+    match self {
+      Error::BackupError { reason, source, .. } => Error::BackupError {
+        reason,
+        path: Some(target_path.into()),
+        source,
+      },
+      err => err,
+    }
+  }
 }
 
 impl Display for Error {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     match self {
       Error::InvalidHashOutput => write!(f, "Generated input output length is not valid."),
-      Error::ParserInvalidRange => write!(f, "Charset range is not valid."),
+      Error::ParserInvalidRange(err) => write!(f, "Charset range is not valid. {}", err),
+      Error::EmptyCharset => write!(f, "Charset does not contain any usable symbol."),
+      Error::DuplicateCharset(name) => {
+        write!(
+          f,
+          "Charset has the same symbols with the existing '{}' charset.",
+          name
+        )
+      }
+      Error::DuplicateCharsetName(name) => write!(f, "Charset '{}' already exists.", name),
+      Error::CharsetNotFound(name) => write!(f, "Charset '{}' does not exist.", name),
+      Error::CharsetInUse { name, key_count } => {
+        write!(f, "Charset '{}' is used by {} key(s).", name, key_count)
+      }
       Error::InvalidInput => write!(f, "Hash input options are not valid."),
       Error::DatabaseError(err) => write!(f, "Database action failed. Reason: {}", err),
       Error::DatabaseMigrateError(err) => write!(f, "Database migration failed. Reason: {}", err),
@@ -33,8 +152,135 @@ impl Display for Error {
       }
       Error::InvalidQrError(err) => write!(f, "Password qr generation failed. Reason: {}", err),
       Error::ValidationError(err) => write!(f, "Input validation failed, {}", err),
-      Error::BackupError(err) => write!(f, "Backup zip generation failed, {}", err),
+      Error::KeyNotFound { key_id } => write!(f, "Key {} does not exist.", key_id),
+      #[cfg(feature = "profile")]
+      Error::DuplicateKey { key_id, check } => {
+        use crate::profile::keys::UniquenessCheck;
+
+        match check {
+          UniquenessCheck::Derivation => {
+            write!(f, "Key {} already generates the same password.", key_id)
+          }
+          UniquenessCheck::Account | UniquenessCheck::Off => write!(
+            f,
+            "Key {} already exists with the same domain and username.",
+            key_id
+          ),
+        }
+      }
+      Error::InvalidCursor(cursor) => write!(f, "Page cursor '{}' is not valid.", cursor),
+      Error::CollectionNotFound { collection_id } => {
+        write!(f, "Collection {} does not exist.", collection_id)
+      }
+      Error::DuplicateCollection(name) => {
+        write!(
+          f,
+          "Collection '{}' already exists in the parent collection.",
+          name
+        )
+      }
+      Error::InvalidCollectionParent { collection_id } => write!(
+        f,
+        "Collection {} cannot be moved under its own sub collection.",
+        collection_id
+      ),
+      Error::TemplateNotFound { template_id } => {
+        write!(f, "Key template {} does not exist.", template_id)
+      }
+      Error::DuplicateTemplate(name) => {
+        write!(f, "Key template '{}' already exists.", name)
+      }
+      Error::BackupError {
+        reason,
+        path,
+        source,
+      } => {
+        write!(f, "Backup action failed, {}", reason)?;
+
+        if let Some(path) = path {
+          write!(f, " Path: {:?}", path)?;
+        }
+
+        if let Some(source) = source {
+          write!(f, " Reason: {}", source)?;
+        }
+
+        Ok(())
+      }
+    }
+  }
+}
+
+impl std::error::Error for Error {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Error::DatabaseError(source)
+      | Error::DatabaseMigrateError(source)
+      | Error::InvalidTime(source)
+      | Error::InvalidJsonError(source)
+      | Error::InvalidQrError(source) => Some(source.as_ref()),
+      Error::BackupError {
+        source: Some(source),
+        ..
+      } => Some(source.as_ref()),
+      Error::ValidationError(errors) => Some(errors),
+      _ => None,
+    }
+  }
+}
+
+/// Serializes as `{ "code": "...", "message": "..." }` with the variant context fields, e.g.
+/// `key_id`, `path` or `fields`.
+#[cfg(any(feature = "json", feature = "profile"))]
+impl serde::Serialize for Error {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    use serde::ser::SerializeStruct;
+
+    let mut error_obj = serializer.serialize_struct("Error", 4)?;
+    error_obj.serialize_field("code", self.code())?;
+    error_obj.serialize_field("message", &self.to_string())?;
+
+    match self {
+      Error::ParserInvalidRange(details) => error_obj.serialize_field("charset", details)?,
+      Error::DuplicateCharset(name)
+      | Error::DuplicateCharsetName(name)
+      | Error::CharsetNotFound(name) => error_obj.serialize_field("name", name)?,
+      Error::CharsetInUse { name, key_count } => {
+        error_obj.serialize_field("name", name)?;
+        error_obj.serialize_field("key_count", key_count)?;
+      }
+      Error::InvalidDatabasePath(path) => error_obj.serialize_field("path", path)?,
+      Error::ValidationError(errors) => error_obj.serialize_field("fields", errors)?,
+      Error::KeyNotFound { key_id } => error_obj.serialize_field("key_id", key_id)?,
+      #[cfg(feature = "profile")]
+      Error::DuplicateKey { key_id, check } => {
+        error_obj.serialize_field("key_id", key_id)?;
+        error_obj.serialize_field("check", check)?;
+      }
+      Error::CollectionNotFound { collection_id }
+      | Error::InvalidCollectionParent { collection_id } => {
+        error_obj.serialize_field("collection_id", collection_id)?
+      }
+      Error::DuplicateCollection(name) | Error::DuplicateTemplate(name) => {
+        error_obj.serialize_field("name", name)?
+      }
+      Error::TemplateNotFound { template_id } => {
+        error_obj.serialize_field("template_id", template_id)?
+      }
+      Error::BackupError {
+        path: Some(path), ..
+      } => error_obj.serialize_field("path", path)?,
+      _ => {}
+    };
+
+    if let Some(source) = std::error::Error::source(self) {
+      error_obj.serialize_field("details", &source.to_string())?;
     }
+
+    error_obj.end()
   }
 }
 
@@ -47,38 +293,21 @@ impl From<validator::ValidationErrors> for Error {
 #[cfg(feature = "profile")]
 impl From<sqlx::Error> for Error {
   fn from(value: sqlx::Error) -> Self {
-    match value {
-      sqlx::Error::RowNotFound => Self::DatabaseError("Row not found".into()),
-      sqlx::Error::TypeNotFound { type_name } => {
-        Self::DatabaseError(format!("type {} not found.", type_name))
-      }
-      sqlx::Error::ColumnIndexOutOfBounds { index, len } => Self::DatabaseError(format!(
-        "Column index out of bounds. index {} len {}",
-        index, len
-      )),
-      sqlx::Error::ColumnDecode { index, source } => Self::DatabaseError(format!(
-        "Column decode failed. source {}, index {}",
-        source, index
-      )),
-      sqlx::Error::PoolTimedOut => Self::DatabaseError("Pool timed out.".into()),
-      sqlx::Error::PoolClosed => Self::DatabaseError("Pool closed.".into()),
-      sqlx::Error::WorkerCrashed => Self::DatabaseError("Worker crashed.".into()),
-      err => Self::DatabaseError(err.to_string()),
-    }
+    Self::DatabaseError(Box::new(value))
   }
 }
 
 #[cfg(feature = "profile")]
 impl From<sqlx::migrate::MigrateError> for Error {
   fn from(value: sqlx::migrate::MigrateError) -> Self {
-    Self::DatabaseMigrateError(value.to_string())
+    Self::DatabaseMigrateError(Box::new(value))
   }
 }
 
 #[cfg(any(feature = "profile", feature = "json"))]
 impl From<serde_json::Error> for Error {
   fn from(value: serde_json::Error) -> Self {
-    Self::InvalidJsonError(value.to_string())
+    Self::InvalidJsonError(Box::new(value))
   }
 }
 
@@ -86,12 +315,8 @@ impl From<serde_json::Error> for Error {
 impl From<zip::result::ZipError> for Error {
   fn from(value: zip::result::ZipError) -> Self {
     match value {
-      zip::result::ZipError::Io(err) => Self::BackupError(err.to_string()),
-      zip::result::ZipError::InvalidArchive(err) => Self::BackupError(err.to_string()),
-      zip::result::ZipError::UnsupportedArchive(err) => Self::BackupError(err.to_string()),
-      zip::result::ZipError::FileNotFound => {
-        Self::BackupError(String::from("Target zip file not found."))
-      }
+      zip::result::ZipError::FileNotFound => Self::backup("Target zip file not found."),
+      err => Self::backup_from("Zip archive action failed.", err),
     }
   }
 }
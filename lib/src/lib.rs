@@ -7,7 +7,7 @@ pub use scrypt;
 #[cfg(feature = "profile")]
 pub mod profile;
 
-use crate::charset::{validate_charset, Charset};
+use crate::charset::{validate_charset, Charset, CharsetOptions};
 use crate::errors::Error;
 use crate::hash::{HashAlgorithm, HashConfig, HashGenerator};
 use std::fmt;
@@ -42,7 +42,7 @@ impl PasswordResult {
   #[cfg(feature = "json")]
   pub fn to_json(self) -> Result<String, Error> {
     let json_text =
-      serde_json::to_string(&self).map_err(|err| Error::InvalidJsonError(err.to_string()))?;
+      serde_json::to_string(&self).map_err(|err| Error::InvalidJsonError(Box::new(err)))?;
     Ok(json_text)
   }
 
@@ -64,7 +64,7 @@ impl PasswordResult {
     };
 
     let qr = qrcode::QrCode::with_version(bytes, qr_options.0, qr_options.1)
-      .map_err(|qr_err| Error::InvalidQrError(qr_err.to_string()))?;
+      .map_err(|qr_err| Error::InvalidQrError(qr_err.to_string().into()))?;
 
     let qr_string = qr
       .render()
@@ -97,10 +97,20 @@ impl Display for PasswordResult {
 pub fn generate_password(
   config: PasswordConfig,
   algorithm: HashAlgorithm,
+) -> Result<PasswordResult, Error> {
+  generate_password_with(config, algorithm, CharsetOptions::default())
+}
+
+/// Same as [generate_password], the charset is parsed with the given options, e.g. to exclude
+/// look-alike symbols.
+pub fn generate_password_with(
+  config: PasswordConfig,
+  algorithm: HashAlgorithm,
+  charset_options: CharsetOptions,
 ) -> Result<PasswordResult, Error> {
   config.validate()?;
 
-  let charset = Charset::new(config.charset)?;
+  let charset = Charset::with_options(config.charset, charset_options)?;
   let generator = algorithm.get_generator();
   let hash = generator.generate_hash(HashConfig::from(config))?;
   let pass = charset.transform_bytes(&hash);
@@ -0,0 +1,7 @@
+ALTER TABLE keys
+    ADD COLUMN exclude_lookalikes INTEGER NOT NULL DEFAULT FALSE;
+
+INSERT OR IGNORE INTO charsets (name, charset, description)
+VALUES ('Alpha Numeric (unambiguous)', 'a..km..zA..HJ..NP..RT..Z2..46..9', 'Alpha Numeric without 0/O, 1/l/I and 5/S.'),
+       ('Numeric (unambiguous)', '2..46..9', 'Numeric without 0, 1 and 5.'),
+       ('Alpha (unambiguous)', 'a..km..zA..HJ..NP..RT..Z', 'Alpha without O, l/I and S.');
@@ -0,0 +1,2 @@
+-- Covers the pinned-first key listing order.
+CREATE INDEX idx_keys_pinned_created_at ON keys (pinned DESC, created_at DESC);
@@ -0,0 +1,15 @@
+-- Trigram index for substring and typo tolerant search. Values are stored lowercase with
+-- diacritics folded, see `profile::utils::text_fold`.
+CREATE
+VIRTUAL TABLE search_trigram USING fts5
+(
+    domain,
+    username,
+    notes,
+    tags,
+    tokenize = 'trigram'
+);
+
+INSERT INTO search_trigram (ROWID, domain, username, notes, tags)
+SELECT ROWID, lower(domain), lower(username), lower(notes), lower(tags)
+FROM search_index;
@@ -0,0 +1,5 @@
+ALTER TABLE keys ADD COLUMN last_used_at INTEGER NULL;
+ALTER TABLE keys ADD COLUMN use_count INTEGER NOT NULL DEFAULT 0;
+
+-- Covers the stale key lookup.
+CREATE INDEX idx_keys_last_used_at ON keys (last_used_at);
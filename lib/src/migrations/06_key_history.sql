@@ -0,0 +1,18 @@
+ALTER TABLE keys ADD COLUMN updated_at INTEGER NULL;
+
+CREATE TABLE key_history
+(
+    id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+    key_id             INTEGER NOT NULL,
+    target_size        INTEGER NOT NULL,
+    revision           INTEGER NOT NULL,
+    charset            TEXT    NOT NULL,
+    domain             TEXT    NOT NULL,
+    username           TEXT    NOT NULL,
+    version            TEXT    NOT NULL,
+    exclude_lookalikes INTEGER NOT NULL DEFAULT FALSE,
+    replaced_at        INTEGER NOT NULL,
+    FOREIGN KEY (key_id) REFERENCES keys (id)
+);
+
+CREATE INDEX idx_key_history_key_id ON key_history (key_id, replaced_at DESC);
@@ -0,0 +1,3 @@
+ALTER TABLE keys ADD COLUMN deleted_at INTEGER NULL;
+
+CREATE INDEX idx_keys_deleted_at ON keys (deleted_at);
@@ -0,0 +1,19 @@
+ALTER TABLE keys ADD COLUMN rotation_days INTEGER NULL;
+
+CREATE TABLE key_rotations
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    key_id     INTEGER NOT NULL,
+    revision   INTEGER NOT NULL,
+    rotated_at INTEGER NOT NULL,
+    FOREIGN KEY (key_id) REFERENCES keys (id)
+);
+
+CREATE INDEX idx_key_rotations_key_id ON key_rotations (key_id, rotated_at DESC);
+
+-- Tag names are case insensitive, policies must match tags regardless of case.
+CREATE TABLE tag_rotation_policies
+(
+    tag           TEXT PRIMARY KEY NOT NULL COLLATE NOCASE,
+    rotation_days INTEGER          NOT NULL
+);
@@ -0,0 +1,14 @@
+CREATE TABLE collections
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    name       TEXT    NOT NULL COLLATE NOCASE,
+    parent_id  INTEGER NULL,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (parent_id) REFERENCES collections (id)
+);
+
+CREATE UNIQUE INDEX idx_collections_parent_name ON collections (ifnull(parent_id, 0), name);
+
+ALTER TABLE keys ADD COLUMN collection_id INTEGER NULL REFERENCES collections (id);
+
+CREATE INDEX idx_keys_collection_id ON keys (collection_id);
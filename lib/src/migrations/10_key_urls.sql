@@ -0,0 +1,49 @@
+CREATE TABLE key_urls
+(
+    key_id     INTEGER NOT NULL,
+    url        TEXT    NOT NULL COLLATE NOCASE,
+    match_rule TEXT    NOT NULL DEFAULT 'base_domain' CHECK (match_rule IN ('exact_host', 'base_domain', 'prefix')),
+    FOREIGN KEY (key_id) REFERENCES keys (id),
+    PRIMARY KEY (key_id, url)
+);
+
+CREATE INDEX idx_key_urls_url ON key_urls (url COLLATE NOCASE);
+
+-- FTS5 tables cannot add columns, both indexes are rebuilt with the `urls` column.
+CREATE
+VIRTUAL TABLE search_index_urls USING fts5
+(
+    domain,
+    username,
+    notes,
+    tags,
+    urls
+);
+
+INSERT INTO search_index_urls (ROWID, domain, username, notes, tags)
+SELECT ROWID, domain, username, notes, tags
+FROM search_index;
+
+DROP TABLE search_index;
+ALTER TABLE search_index_urls RENAME TO search_index;
+
+INSERT INTO search_index(search_index, rank)
+VALUES ('rank', 'bm25(5.0, 7.0, 1.0, 10.0, 5.0)');
+
+CREATE
+VIRTUAL TABLE search_trigram_urls USING fts5
+(
+    domain,
+    username,
+    notes,
+    tags,
+    urls,
+    tokenize = 'trigram'
+);
+
+INSERT INTO search_trigram_urls (ROWID, domain, username, notes, tags)
+SELECT ROWID, domain, username, notes, tags
+FROM search_trigram;
+
+DROP TABLE search_trigram;
+ALTER TABLE search_trigram_urls RENAME TO search_trigram;
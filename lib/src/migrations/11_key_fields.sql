@@ -0,0 +1,50 @@
+CREATE TABLE key_fields
+(
+    key_id     INTEGER NOT NULL,
+    name       TEXT    NOT NULL COLLATE NOCASE,
+    field_type TEXT    NOT NULL DEFAULT 'text' CHECK (field_type IN ('text', 'url', 'email', 'number', 'date', 'hidden')),
+    value      TEXT    NOT NULL,
+    FOREIGN KEY (key_id) REFERENCES keys (id),
+    PRIMARY KEY (key_id, name)
+);
+
+-- FTS5 tables cannot add columns, both indexes are rebuilt with the `fields` column.
+CREATE
+VIRTUAL TABLE search_index_fields USING fts5
+(
+    domain,
+    username,
+    notes,
+    tags,
+    urls,
+    fields
+);
+
+INSERT INTO search_index_fields (ROWID, domain, username, notes, tags, urls)
+SELECT ROWID, domain, username, notes, tags, urls
+FROM search_index;
+
+DROP TABLE search_index;
+ALTER TABLE search_index_fields RENAME TO search_index;
+
+INSERT INTO search_index(search_index, rank)
+VALUES ('rank', 'bm25(5.0, 7.0, 1.0, 10.0, 5.0, 1.0)');
+
+CREATE
+VIRTUAL TABLE search_trigram_fields USING fts5
+(
+    domain,
+    username,
+    notes,
+    tags,
+    urls,
+    fields,
+    tokenize = 'trigram'
+);
+
+INSERT INTO search_trigram_fields (ROWID, domain, username, notes, tags, urls)
+SELECT ROWID, domain, username, notes, tags, urls
+FROM search_trigram;
+
+DROP TABLE search_trigram;
+ALTER TABLE search_trigram_fields RENAME TO search_trigram;
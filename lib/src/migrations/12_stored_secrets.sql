@@ -0,0 +1,19 @@
+ALTER TABLE keys ADD COLUMN kind TEXT NOT NULL DEFAULT 'derived' CHECK (kind IN ('derived', 'stored'));
+
+-- Secrets are kept apart from the key list queries and never copied into the search indexes.
+CREATE TABLE key_secrets
+(
+    key_id INTEGER PRIMARY KEY NOT NULL,
+    secret TEXT                NOT NULL,
+    FOREIGN KEY (key_id) REFERENCES keys (id)
+);
+
+ALTER TABLE key_history ADD COLUMN kind TEXT NOT NULL DEFAULT 'derived' CHECK (kind IN ('derived', 'stored'));
+
+-- Previous secrets of stored keys, kept apart from the history list queries like key_secrets.
+CREATE TABLE key_history_secrets
+(
+    history_id INTEGER PRIMARY KEY NOT NULL,
+    secret     TEXT                NOT NULL,
+    FOREIGN KEY (history_id) REFERENCES key_history (id)
+);
@@ -0,0 +1,7 @@
+-- Normalized domain and username used by the account uniqueness check. Values are written by the
+-- application since SQLite `lower()` only folds ASCII letters, rows of existing keys are filled
+-- once after upgrading.
+ALTER TABLE keys ADD COLUMN account_domain TEXT NULL;
+ALTER TABLE keys ADD COLUMN account_username TEXT NULL;
+
+CREATE INDEX idx_keys_account ON keys (account_domain, account_username);
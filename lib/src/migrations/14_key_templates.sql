@@ -0,0 +1,14 @@
+CREATE TABLE key_templates
+(
+    id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+    name               TEXT    NOT NULL UNIQUE COLLATE NOCASE,
+    charset            TEXT    NOT NULL,
+    target_size        INTEGER NOT NULL,
+    version            TEXT    NOT NULL,
+    exclude_lookalikes INTEGER NOT NULL DEFAULT FALSE,
+    uniqueness         TEXT    NOT NULL DEFAULT 'off' CHECK (uniqueness IN ('off', 'derivation', 'account')),
+    -- JSON array of the default tags.
+    tags               TEXT    NOT NULL DEFAULT '[]',
+    rotation_days      INTEGER NULL,
+    created_at         INTEGER NOT NULL
+);
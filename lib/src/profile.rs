@@ -1,17 +1,27 @@
 use crate::errors::Error;
+use events::{event_channel, ProfileEvent};
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
 use sqlx::sqlx_macros::migrate;
 use std::str::FromStr;
 use std::time::Duration;
+use tokio::sync::broadcast::Sender;
 
 pub mod backup;
 pub mod charsets;
+pub mod collections;
+pub mod events;
+pub mod key_history;
 pub mod keys;
+pub mod search_query;
+pub mod store;
+pub mod tags;
+pub mod templates;
 pub mod utils;
 
-// TODO: Create a trait and implement other databases when next development phase begins for remote api.
+/// SQLCipher backed profile store.
 pub struct ProfileDB {
   pool: SqlitePool,
+  events: Sender<ProfileEvent>,
 }
 
 #[derive(Debug)]
@@ -77,11 +87,35 @@ impl ProfileDB {
 
     let pool = SqlitePool::connect_with(options).await?;
 
+    let mut needs_backfill = false;
+
     if !disable_migrate {
+      needs_backfill = !table_exists(&pool, "search_trigram").await?;
       let migrator = migrate!("src/migrations");
       migrator.run(&pool).await?;
     }
 
-    Ok(ProfileDB { pool })
+    let profile_db = ProfileDB {
+      pool,
+      events: event_channel(),
+    };
+
+    // Migrations can only fold text with SQL `lower()`, the folded search index and account
+    // values are written once after upgrading from a profile without the trigram index.
+    if needs_backfill {
+      profile_db.rebuild_search_index().await?;
+      profile_db.backfill_account_params().await?;
+    }
+
+    Ok(profile_db)
   }
 }
+
+async fn table_exists(pool: &SqlitePool, name: &str) -> Result<bool, Error> {
+  let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE name = ?")
+    .bind(name)
+    .fetch_one(pool)
+    .await?;
+
+  Ok(count > 0)
+}
@@ -1,19 +1,32 @@
 use self::{
   file_backup::{FileBackupReader, FileBackupWriter},
-  reader::BackupReader,
+  reader::{BackupReader, IconDetails},
   writer::BackupWriter,
 };
-use super::{charsets::CharsetItem, keys::KeyItem, ProfileDB};
+use super::{
+  charsets::CharsetItem,
+  collections::CollectionItem,
+  events::ProfileEvent,
+  key_history::{create_key_history_secret_query, KeyHistoryItem, KeyHistorySecretItem},
+  keys::{
+    account_params, create_key_field_query, create_key_secret_query, create_key_url_query,
+    field_index_text, url_index_text, KeyItem, KeyRotationItem, KeySecretItem, RotationPolicy,
+  },
+  templates::KeyTemplateItem,
+  utils::text_fold::fold_text,
+  ProfileDB,
+};
 use crate::errors::Error;
 use log::error;
 use serde::{Deserialize, Serialize};
 use sqlx::{query, Acquire, QueryBuilder, Sqlite};
 use std::{
-  fs::OpenOptions,
+  fs::{File, OpenOptions},
   io::{BufReader, BufWriter},
-  path::PathBuf,
+  path::{Path, PathBuf},
 };
 
+mod encryption;
 pub mod file_backup;
 pub mod reader;
 pub mod writer;
@@ -41,6 +54,21 @@ impl Default for BackupManifest {
   }
 }
 
+/// Content of the encrypted secrets section.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct BackupSecrets {
+  pub keys: Vec<KeySecretItem>,
+  /// Previous secrets of stored keys, kept with their history snapshots.
+  #[serde(default)]
+  pub history: Vec<KeyHistorySecretItem>,
+}
+
+impl BackupSecrets {
+  pub fn is_empty(&self) -> bool {
+    self.keys.is_empty() && self.history.is_empty()
+  }
+}
+
 pub enum BackupTarget {
   File(PathBuf),
   // Http Soon™
@@ -55,6 +83,8 @@ pub struct BackupOptions {
 pub struct RestoreOptions {
   pub content_dir: PathBuf,
   pub target: BackupTarget,
+  /// Key the backup is signed with, decrypts the stored secrets.
+  pub sign_key: Box<[u8]>,
 }
 
 impl ProfileDB {
@@ -65,36 +95,30 @@ impl ProfileDB {
       sign_key,
     } = options;
 
-    let keys = self.get_keys(false).await?;
-    let charsets = self.get_charsets().await?;
-    let icons: Vec<(String, PathBuf)> = keys
-      .iter()
-      .flat_map(|x| match &x.custom_icon {
-        Some(icon_name) if !icon_name.is_empty() => {
-          Some((String::from(icon_name), content_dir.join(icon_name)))
-        }
-        _ => None,
-      })
-      .collect();
-
-    match target {
-      BackupTarget::File(path) => {
-        let open_options = &mut std::fs::OpenOptions::new();
-        let fs_opt = open_options.create(true).write(true);
-        let fd = fs_opt.open(path).map_err(|err| {
-          error!("Unable to create target file, {}", err);
-          Error::BackupError(err.to_string())
-        })?;
-
-        let writer = BufWriter::new(fd);
-        let mut backup_writer = FileBackupWriter::new(writer, &sign_key)?;
-
-        backup_writer.write_keys(&keys)?;
-        backup_writer.write_charsets(&charsets)?;
-        backup_writer.write_icons(&icons)?;
-        backup_writer.finish()?;
-      }
+    let keys = self.get_all_keys().await?;
+    let key_history = self.get_all_key_history().await?;
+    let key_rotations = self.get_all_key_rotations().await?;
+    let key_secrets = BackupSecrets {
+      keys: self.get_all_key_secrets().await?,
+      history: self.get_all_key_history_secrets().await?,
     };
+    let rotation_policies = self.get_rotation_policies().await?;
+    let charsets = self.get_charsets().await?;
+    let collections = self.get_collections().await?;
+    let templates = self.get_templates().await?;
+    let icons = icon_paths(&keys, &content_dir);
+
+    let mut backup_writer = open_backup_writer(&target, &sign_key)?;
+    backup_writer.write_keys(&keys)?;
+    backup_writer.write_key_history(&key_history)?;
+    backup_writer.write_key_rotations(&key_rotations)?;
+    backup_writer.write_key_secrets(&key_secrets)?;
+    backup_writer.write_rotation_policies(&rotation_policies)?;
+    backup_writer.write_charsets(&charsets)?;
+    backup_writer.write_collections(&collections)?;
+    backup_writer.write_templates(&templates)?;
+    backup_writer.write_icons(&icons)?;
+    backup_writer.finish()?;
 
     Ok(())
   }
@@ -103,30 +127,22 @@ impl ProfileDB {
     let RestoreOptions {
       target,
       content_dir,
+      sign_key,
     } = options;
 
     if !content_dir.is_dir() {
-      return Err(Error::BackupError(String::from(
-        "Content target isn't directory.",
-      )));
+      return Err(Error::backup("Content target isn't directory.").with_path(content_dir));
     }
 
-    let mut backup_reader = match target {
-      BackupTarget::File(path) => {
-        let open_options = &mut std::fs::OpenOptions::new();
-        let fs_opt = open_options.read(true);
-        let fd = fs_opt.open(path).map_err(|err| {
-          error!("Unable to create target file, {}", err);
-          Error::BackupError(err.to_string())
-        })?;
-
-        let reader = BufReader::new(fd);
-        FileBackupReader::new(reader)?
-      }
-    };
-
+    let mut backup_reader = open_backup_reader(&target)?;
     let keys = backup_reader.keys()?;
+    let key_history = backup_reader.key_history()?;
+    let key_rotations = backup_reader.key_rotations()?;
+    let key_secrets = backup_reader.key_secrets(&sign_key)?;
+    let rotation_policies = backup_reader.rotation_policies()?;
     let charsets = backup_reader.charsets()?;
+    let collections = backup_reader.collections()?;
+    let templates = backup_reader.templates()?;
     let icons = backup_reader.icons()?;
 
     let mut conn = self.pool.acquire().await?;
@@ -134,49 +150,183 @@ impl ProfileDB {
 
     query!(
       "DELETE FROM tags;
+       DELETE FROM key_urls;
+       DELETE FROM key_fields;
+       DELETE FROM key_secrets;
+       DELETE FROM key_history_secrets;
+       DELETE FROM key_history;
+       DELETE FROM key_rotations;
+       DELETE FROM tag_rotation_policies;
        DELETE FROM keys;
+       DELETE FROM collections;
+       DELETE FROM key_templates;
        DELETE FROM charsets;
-       DELETE FROM search_index;"
+       DELETE FROM search_index;
+       DELETE FROM search_trigram;"
     )
     .execute(&mut *transaction)
     .await?;
 
+    if !collections.is_empty() {
+      let mut collection_query = create_collection_query(&collections);
+      collection_query.build().execute(&mut *transaction).await?;
+    }
+
     let mut key_query = create_key_query(&keys);
     key_query.build().execute(&mut *transaction).await?;
 
     let mut tag_query = create_tag_query(&keys);
     tag_query.build().execute(&mut *transaction).await?;
 
+    if keys.iter().any(|key_item| !key_item.urls.is_empty()) {
+      let key_urls = keys
+        .iter()
+        .flat_map(|key_item| key_item.urls.iter().map(|url| (key_item.id, url)));
+      let mut url_query = create_key_url_query(key_urls);
+      url_query.build().execute(&mut *transaction).await?;
+    }
+
+    if keys.iter().any(|key_item| !key_item.fields.is_empty()) {
+      let key_fields = keys
+        .iter()
+        .flat_map(|key_item| key_item.fields.iter().map(|field| (key_item.id, field)));
+      let mut field_query = create_key_field_query(key_fields);
+      field_query.build().execute(&mut *transaction).await?;
+    }
+
+    if !key_secrets.keys.is_empty() {
+      let mut secret_query = create_key_secret_query(&key_secrets.keys);
+      secret_query.build().execute(&mut *transaction).await?;
+    }
+
+    if !key_history.is_empty() {
+      let mut history_query = create_key_history_query(&key_history);
+      history_query.build().execute(&mut *transaction).await?;
+    }
+
+    if !key_secrets.history.is_empty() {
+      let mut history_secret_query = create_key_history_secret_query(&key_secrets.history);
+      history_secret_query
+        .build()
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    if !key_rotations.is_empty() {
+      let mut rotation_query = create_key_rotation_query(&key_rotations);
+      rotation_query.build().execute(&mut *transaction).await?;
+    }
+
+    if !rotation_policies.is_empty() {
+      let mut policy_query = create_rotation_policy_query(&rotation_policies);
+      policy_query.build().execute(&mut *transaction).await?;
+    }
+
     let mut index_query = create_index_query(&keys);
     index_query.build().execute(&mut *transaction).await?;
 
+    let mut trigram_query = create_trigram_index_query(&keys);
+    trigram_query.build().execute(&mut *transaction).await?;
+
     let mut charset_query = create_charset_query(&charsets);
     charset_query.build().execute(&mut *transaction).await?;
 
+    if !templates.is_empty() {
+      let mut template_query = create_template_query(&templates)?;
+      template_query.build().execute(&mut *transaction).await?;
+    }
+
     transaction.commit().await?;
+    restore_icons(&mut backup_reader, &icons, &content_dir)?;
+    self.notify(ProfileEvent::RestoreCompleted);
 
-    for icon in icons {
-      let fs_path = content_dir.join(&icon.name);
-      let fd = OpenOptions::new()
-        .create(true)
-        .truncate(true)
-        .write(true)
-        .open(fs_path)
-        .map_err(|err| {
-          error!("Icon {} {}", &icon.name, err);
-          Error::BackupError(format!("Unable to open content file, {}", err))
-        })?;
-
-      backup_reader.copy_section_to(&icon.full_name, fd)?;
+    Ok(())
+  }
+}
+
+/// Returns the custom icon names of the keys with their paths in the content directory.
+pub(in crate::profile) fn icon_paths(
+  keys: &[KeyItem],
+  content_dir: &Path,
+) -> Vec<(String, PathBuf)> {
+  keys
+    .iter()
+    .flat_map(|x| match &x.custom_icon {
+      Some(icon_name) if !icon_name.is_empty() => {
+        Some((String::from(icon_name), content_dir.join(icon_name)))
+      }
+      _ => None,
+    })
+    .collect()
+}
+
+pub(in crate::profile) fn open_backup_writer(
+  target: &BackupTarget,
+  sign_key: &[u8],
+) -> Result<FileBackupWriter<BufWriter<File>>, Error> {
+  match target {
+    BackupTarget::File(path) => {
+      let open_options = &mut std::fs::OpenOptions::new();
+      let fs_opt = open_options.create(true).write(true);
+      let fd = fs_opt.open(path).map_err(|err| {
+        error!("Unable to create target file, {}", err);
+        Error::backup_from("Unable to create target file.", err).with_path(path)
+      })?;
+
+      let writer = BufWriter::new(fd);
+      FileBackupWriter::new(writer, sign_key)
     }
+  }
+}
 
-    Ok(())
+pub(in crate::profile) fn open_backup_reader(
+  target: &BackupTarget,
+) -> Result<FileBackupReader<BufReader<File>>, Error> {
+  match target {
+    BackupTarget::File(path) => {
+      let open_options = &mut std::fs::OpenOptions::new();
+      let fs_opt = open_options.read(true);
+      let fd = fs_opt.open(path).map_err(|err| {
+        error!("Unable to create target file, {}", err);
+        Error::backup_from("Unable to create target file.", err).with_path(path)
+      })?;
+
+      let reader = BufReader::new(fd);
+      FileBackupReader::new(reader)
+    }
   }
 }
 
+/// Copies the icon sections of the backup into the content directory.
+pub(in crate::profile) fn restore_icons<T>(
+  backup_reader: &mut T,
+  icons: &[IconDetails],
+  content_dir: &Path,
+) -> Result<(), Error>
+where
+  T: BackupReader<ReaderError = Error>,
+{
+  for icon in icons {
+    let fs_path = content_dir.join(&icon.name);
+    let fd = OpenOptions::new()
+      .create(true)
+      .truncate(true)
+      .write(true)
+      .open(&fs_path)
+      .map_err(|err| {
+        error!("Icon {} {}", &icon.name, err);
+        Error::backup_from("Unable to open content file.", err).with_path(&fs_path)
+      })?;
+
+    backup_reader.copy_section_to(&icon.full_name, fd)?;
+  }
+
+  Ok(())
+}
+
 fn create_key_query(keys: &[KeyItem]) -> QueryBuilder<'_, Sqlite> {
   let mut query_builder: QueryBuilder<Sqlite> =
-    QueryBuilder::new("INSERT INTO keys (id, pinned, target_size, revision, charset, domain, username, notes, created_at, custom_icon, version) ");
+    QueryBuilder::new("INSERT INTO keys (id, pinned, target_size, revision, charset, domain, username, notes, created_at, custom_icon, version, exclude_lookalikes, last_used_at, use_count, updated_at, deleted_at, rotation_days, collection_id, kind, account_domain, account_username) ");
 
   query_builder.push_values(keys.iter(), |mut b, key_item| {
     b.push_bind(key_item.id);
@@ -190,6 +340,107 @@ fn create_key_query(keys: &[KeyItem]) -> QueryBuilder<'_, Sqlite> {
     b.push_bind(key_item.created_at);
     b.push_bind(&key_item.custom_icon);
     b.push_bind(&key_item.version);
+    b.push_bind(key_item.exclude_lookalikes);
+    b.push_bind(key_item.last_used_at);
+    b.push_bind(key_item.use_count);
+    b.push_bind(key_item.updated_at);
+    b.push_bind(key_item.deleted_at);
+    b.push_bind(key_item.rotation_days);
+    b.push_bind(key_item.collection_id);
+    b.push_bind(key_item.kind);
+
+    let (account_domain, account_username) = account_params(&key_item.domain, &key_item.username);
+    b.push_bind(account_domain);
+    b.push_bind(account_username);
+  });
+
+  query_builder
+}
+
+fn create_collection_query(collections: &[CollectionItem]) -> QueryBuilder<'_, Sqlite> {
+  let mut query_builder: QueryBuilder<Sqlite> =
+    QueryBuilder::new("INSERT INTO collections (id, name, parent_id, created_at) ");
+
+  query_builder.push_values(collections.iter(), |mut b, collection_item| {
+    b.push_bind(collection_item.id);
+    b.push_bind(&collection_item.name);
+    b.push_bind(collection_item.parent_id);
+    b.push_bind(collection_item.created_at);
+  });
+
+  query_builder
+}
+
+fn create_template_query(templates: &[KeyTemplateItem]) -> Result<QueryBuilder<'_, Sqlite>, Error> {
+  let tags = templates
+    .iter()
+    .map(|template_item| serde_json::to_string(&template_item.tags))
+    .collect::<Result<Vec<String>, serde_json::Error>>()?;
+
+  let mut query_builder: QueryBuilder<Sqlite> =
+    QueryBuilder::new("INSERT INTO key_templates (id, name, charset, target_size, version, exclude_lookalikes, uniqueness, tags, rotation_days, created_at) ");
+
+  query_builder.push_values(
+    templates.iter().zip(tags),
+    |mut b, (template_item, tags)| {
+      b.push_bind(template_item.id);
+      b.push_bind(&template_item.name);
+      b.push_bind(&template_item.charset);
+      b.push_bind(template_item.target_size);
+      b.push_bind(&template_item.version);
+      b.push_bind(template_item.exclude_lookalikes);
+      b.push_bind(template_item.uniqueness);
+      b.push_bind(tags);
+      b.push_bind(template_item.rotation_days);
+      b.push_bind(template_item.created_at);
+    },
+  );
+
+  Ok(query_builder)
+}
+
+fn create_key_history_query(key_history: &[KeyHistoryItem]) -> QueryBuilder<'_, Sqlite> {
+  let mut query_builder: QueryBuilder<Sqlite> =
+    QueryBuilder::new("INSERT INTO key_history (id, key_id, target_size, revision, charset, domain, username, version, exclude_lookalikes, kind, replaced_at) ");
+
+  query_builder.push_values(key_history.iter(), |mut b, history_item| {
+    b.push_bind(history_item.id);
+    b.push_bind(history_item.key_id);
+    b.push_bind(history_item.target_size);
+    b.push_bind(history_item.revision);
+    b.push_bind(&history_item.charset);
+    b.push_bind(&history_item.domain);
+    b.push_bind(&history_item.username);
+    b.push_bind(&history_item.version);
+    b.push_bind(history_item.exclude_lookalikes);
+    b.push_bind(history_item.kind);
+    b.push_bind(history_item.replaced_at);
+  });
+
+  query_builder
+}
+
+fn create_key_rotation_query(rotations: &[KeyRotationItem]) -> QueryBuilder<'_, Sqlite> {
+  let mut query_builder: QueryBuilder<Sqlite> =
+    QueryBuilder::new("INSERT INTO key_rotations (id, key_id, revision, rotated_at) ");
+
+  query_builder.push_values(rotations.iter(), |mut b, rotation_item| {
+    b.push_bind(rotation_item.id);
+    b.push_bind(rotation_item.key_id);
+    b.push_bind(rotation_item.revision);
+    b.push_bind(rotation_item.rotated_at);
+  });
+
+  query_builder
+}
+
+fn create_rotation_policy_query(policies: &[RotationPolicy]) -> QueryBuilder<'_, Sqlite> {
+  let mut query_builder: QueryBuilder<Sqlite> =
+    QueryBuilder::new("INSERT INTO tag_rotation_policies (tag, rotation_days) ");
+
+  query_builder.push_values(policies.iter(), |mut b, policy| {
+    b.push_bind(&policy.tag);
+    b.push_bind(policy.rotation_days);
   });
 
   query_builder
@@ -208,9 +459,10 @@ fn create_tag_query(keys: &[KeyItem]) -> QueryBuilder<'_, Sqlite> {
   query_builder
 }
 
-fn create_index_query(keys: &[KeyItem]) -> QueryBuilder<'_, Sqlite> {
-  let mut query_builder: QueryBuilder<Sqlite> =
-    QueryBuilder::new("INSERT INTO search_index (ROWID, domain, username, tags, notes) ");
+pub(in crate::profile) fn create_index_query(keys: &[KeyItem]) -> QueryBuilder<'_, Sqlite> {
+  let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "INSERT INTO search_index (ROWID, domain, username, tags, notes, urls, fields) ",
+  );
 
   query_builder.push_values(keys.iter(), |mut b, key_item| {
     b.push_bind(key_item.id);
@@ -218,6 +470,26 @@ fn create_index_query(keys: &[KeyItem]) -> QueryBuilder<'_, Sqlite> {
     b.push_bind(&key_item.username);
     b.push_bind(key_item.tags.join(' '));
     b.push_bind(&key_item.notes);
+    b.push_bind(url_index_text(&key_item.urls));
+    b.push_bind(field_index_text(&key_item.fields));
+  });
+
+  query_builder
+}
+
+pub(in crate::profile) fn create_trigram_index_query(keys: &[KeyItem]) -> QueryBuilder<'_, Sqlite> {
+  let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "INSERT INTO search_trigram (ROWID, domain, username, tags, notes, urls, fields) ",
+  );
+
+  query_builder.push_values(keys.iter(), |mut b, key_item| {
+    b.push_bind(key_item.id);
+    b.push_bind(fold_text(&key_item.domain));
+    b.push_bind(fold_text(&key_item.username));
+    b.push_bind(fold_text(&key_item.tags.join(' ')));
+    b.push_bind(key_item.notes.as_deref().map(fold_text));
+    b.push_bind(fold_text(&url_index_text(&key_item.urls)));
+    b.push_bind(fold_text(&field_index_text(&key_item.fields)));
   });
 
   query_builder
@@ -0,0 +1,113 @@
+use crate::errors::Error;
+use hmac::Mac;
+use log::error;
+use salsa20::cipher::{KeyIvInit, StreamCipher};
+use salsa20::XSalsa20;
+use scrypt::{scrypt, Params};
+
+type Hmac256 = hmac::Hmac<sha2::Sha256>;
+
+const FORMAT_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const MAC_LEN: usize = 32;
+const KEY_LEN: usize = 32;
+const HEADER_LEN: usize = 1 + SALT_LEN + NONCE_LEN + MAC_LEN;
+
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Encrypts a backup section with XSalsa20 and authenticates it with HMAC-SHA256. Both keys are
+/// derived from the backup key with scrypt and a random salt.
+///
+/// Layout is `version | salt | nonce | mac | ciphertext`, the MAC covers everything except itself.
+pub(super) fn encrypt_section(key: &[u8], plain_text: &[u8]) -> Result<Vec<u8>, Error> {
+  let mut salt = [0u8; SALT_LEN];
+  let mut nonce = [0u8; NONCE_LEN];
+  getrandom::getrandom(&mut salt).map_err(|err| {
+    error!("{}", err);
+    Error::backup_from(
+      "Unable to generate backup encryption salt.",
+      err.to_string(),
+    )
+  })?;
+  getrandom::getrandom(&mut nonce).map_err(|err| {
+    error!("{}", err);
+    Error::backup_from(
+      "Unable to generate backup encryption nonce.",
+      err.to_string(),
+    )
+  })?;
+
+  let (cipher_key, mac_key) = derive_keys(key, &salt)?;
+  let mut cipher_text = plain_text.to_vec();
+  XSalsa20::new(&cipher_key.into(), &nonce.into()).apply_keystream(&mut cipher_text);
+
+  let mac = section_mac(&mac_key, &salt, &nonce, &cipher_text)?
+    .finalize()
+    .into_bytes();
+
+  let mut output = Vec::with_capacity(HEADER_LEN + cipher_text.len());
+  output.push(FORMAT_VERSION);
+  output.extend_from_slice(&salt);
+  output.extend_from_slice(&nonce);
+  output.extend_from_slice(&mac);
+  output.extend_from_slice(&cipher_text);
+
+  Ok(output)
+}
+
+/// Reverses [encrypt_section]. Fails without decrypting when the key or the content does not match
+/// the MAC.
+pub(super) fn decrypt_section(key: &[u8], section: &[u8]) -> Result<Vec<u8>, Error> {
+  if section.len() < HEADER_LEN || section[0] != FORMAT_VERSION {
+    return Err(Error::backup("Encrypted backup section is malformed."));
+  }
+
+  let (salt, rest) = section[1..].split_at(SALT_LEN);
+  let (nonce, rest) = rest.split_at(NONCE_LEN);
+  let (expected_mac, cipher_text) = rest.split_at(MAC_LEN);
+
+  let (cipher_key, mac_key) = derive_keys(key, salt)?;
+  section_mac(&mac_key, salt, nonce, cipher_text)?
+    .verify_slice(expected_mac)
+    .map_err(|_| Error::backup("Unable to decrypt backup section, backup key does not match."))?;
+
+  let mut plain_text = cipher_text.to_vec();
+  let nonce: [u8; NONCE_LEN] = nonce.try_into().expect("nonce length is fixed");
+  XSalsa20::new(&cipher_key.into(), &nonce.into()).apply_keystream(&mut plain_text);
+
+  Ok(plain_text)
+}
+
+fn derive_keys(key: &[u8], salt: &[u8]) -> Result<([u8; KEY_LEN], [u8; KEY_LEN]), Error> {
+  let params = Params::new(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P, KEY_LEN * 2)?;
+  let mut output = [0u8; KEY_LEN * 2];
+  scrypt(key, salt, &params, &mut output)?;
+
+  let mut cipher_key = [0u8; KEY_LEN];
+  let mut mac_key = [0u8; KEY_LEN];
+  cipher_key.copy_from_slice(&output[..KEY_LEN]);
+  mac_key.copy_from_slice(&output[KEY_LEN..]);
+
+  Ok((cipher_key, mac_key))
+}
+
+fn section_mac(
+  mac_key: &[u8],
+  salt: &[u8],
+  nonce: &[u8],
+  cipher_text: &[u8],
+) -> Result<Hmac256, Error> {
+  let mut mac = Hmac256::new_from_slice(mac_key).map_err(|err| {
+    error!("{}", err);
+    Error::backup_from("Invalid backup encryption key.", err.to_string())
+  })?;
+  mac.update(&[FORMAT_VERSION]);
+  mac.update(salt);
+  mac.update(nonce);
+  mac.update(cipher_text);
+
+  Ok(mac)
+}
@@ -1,14 +1,22 @@
 use super::{
+  encryption::{decrypt_section, encrypt_section},
   reader::{BackupReader, IconDetails},
   writer::BackupWriter,
-  BackupManifest,
+  BackupManifest, BackupSecrets,
 };
 use crate::{
   errors::Error,
-  profile::{charsets::CharsetItem, keys::KeyItem},
+  profile::{
+    charsets::CharsetItem,
+    collections::CollectionItem,
+    key_history::KeyHistoryItem,
+    keys::{KeyItem, KeyRotationItem, RotationPolicy},
+    templates::KeyTemplateItem,
+  },
 };
 use hmac::Mac;
 use log::{debug, error};
+use serde::{de::DeserializeOwned, Serialize};
 use std::{
   io::{ErrorKind, Read, Seek, Write},
   path::PathBuf,
@@ -18,8 +26,14 @@ use zip::{write::FileOptions, ZipArchive, ZipWriter};
 type Hmac256 = hmac::Hmac<sha2::Sha256>;
 
 const SECTION_KEYS: &str = "keys";
+const SECTION_KEY_HISTORY: &str = "key_history";
+const SECTION_KEY_ROTATIONS: &str = "key_rotations";
+const SECTION_KEY_SECRETS: &str = "key_secrets";
+const SECTION_ROTATION_POLICIES: &str = "rotation_policies";
 const SECTION_CONTENTS: &str = "contents";
 const SECTION_CHARSETS: &str = "charsets";
+const SECTION_COLLECTIONS: &str = "collections";
+const SECTION_TEMPLATES: &str = "key_templates";
 const SECTION_MANIFEST: &str = "manifest";
 
 pub struct FileBackupWriter<T>
@@ -29,6 +43,8 @@ where
   inner: ZipWriter<T>,
   sign_gen: Hmac256,
   manifest: BackupManifest,
+  /// Sign key, also used to derive the encryption key of the stored secrets.
+  key: Box<[u8]>,
 }
 
 impl<T> Write for FileBackupWriter<T>
@@ -52,61 +68,98 @@ where
     let zip_writer = ZipWriter::new(writer);
     let mac = Hmac256::new_from_slice(key).map_err(|err| {
       error!("{}", err);
-      Error::BackupError(err.to_string())
+      Error::backup_from("Invalid backup sign key.", err.to_string())
     })?;
 
     Ok(Self {
       inner: zip_writer,
       manifest: BackupManifest::default(),
       sign_gen: mac,
+      key: Box::from(key),
     })
   }
-}
 
-impl<T> BackupWriter for FileBackupWriter<T>
-where
-  T: Write + Seek,
-{
-  type WriterError = Error;
+  /// Writes the value as a JSON section and adds it to the manifest and the signature.
+  fn write_json_section<S>(&mut self, name: &str, value: &S) -> Result<(), Error>
+  where
+    S: Serialize + ?Sized,
+  {
+    let bytes = serde_json::to_vec(value)?;
+    self.write_section(name, &bytes)
+  }
 
-  fn write_charsets(&mut self, charsets: &[CharsetItem]) -> Result<(), Self::WriterError> {
+  fn write_section(&mut self, name: &str, bytes: &[u8]) -> Result<(), Error> {
     let zip_options = FileOptions::default()
       .compression_method(zip::CompressionMethod::DEFLATE)
       .unix_permissions(0o644);
 
-    let bytes = serde_json::to_vec(charsets)?;
-
-    self.sign_gen.update(&bytes);
-    self.manifest.files.push(SECTION_CHARSETS.to_string());
-    self.inner.start_file(SECTION_CHARSETS, zip_options)?;
-    self.inner.write_all(&bytes).map_err(|err| {
+    self.sign_gen.update(bytes);
+    self.manifest.files.push(name.to_string());
+    self.inner.start_file(name, zip_options)?;
+    self.inner.write_all(bytes).map_err(|err| {
       error!("{}", err);
-      Error::BackupError(err.to_string())
+      Error::backup_from(
+        format!("Unable to write {} section.", name.replace('_', " ")),
+        err,
+      )
     })?;
 
-    debug!("Charsets written to backup file.");
+    debug!("Section {} written to backup file.", name);
 
     Ok(())
   }
+}
+
+impl<T> BackupWriter for FileBackupWriter<T>
+where
+  T: Write + Seek,
+{
+  type WriterError = Error;
+
+  fn write_charsets(&mut self, charsets: &[CharsetItem]) -> Result<(), Self::WriterError> {
+    self.write_json_section(SECTION_CHARSETS, charsets)
+  }
 
   fn write_keys(&mut self, keys: &[KeyItem]) -> Result<(), Self::WriterError> {
-    let zip_options = FileOptions::default()
-      .compression_method(zip::CompressionMethod::DEFLATE)
-      .unix_permissions(0o644);
+    self.write_json_section(SECTION_KEYS, keys)
+  }
 
-    let bytes = serde_json::to_vec(keys)?;
+  fn write_key_history(&mut self, history: &[KeyHistoryItem]) -> Result<(), Self::WriterError> {
+    self.write_json_section(SECTION_KEY_HISTORY, history)
+  }
 
-    self.sign_gen.update(&bytes);
-    self.manifest.files.push(SECTION_KEYS.to_string());
-    self.inner.start_file(SECTION_KEYS, zip_options)?;
-    self.inner.write_all(&bytes).map_err(|err| {
-      error!("{}", err);
-      Error::BackupError(err.to_string())
-    })?;
+  fn write_key_rotations(
+    &mut self,
+    rotations: &[KeyRotationItem],
+  ) -> Result<(), Self::WriterError> {
+    self.write_json_section(SECTION_KEY_ROTATIONS, rotations)
+  }
 
-    debug!("Keys written to backup file.");
+  /// Secrets are encrypted, the backup file itself is a plain zip archive. The section is skipped
+  /// when there are no secrets, so the key derivation only runs when needed.
+  fn write_key_secrets(&mut self, secrets: &BackupSecrets) -> Result<(), Self::WriterError> {
+    if secrets.is_empty() {
+      return Ok(());
+    }
 
-    Ok(())
+    let bytes = serde_json::to_vec(secrets)?;
+    let encrypted = encrypt_section(&self.key, &bytes)?;
+    self.write_section(SECTION_KEY_SECRETS, &encrypted)
+  }
+
+  fn write_rotation_policies(
+    &mut self,
+    policies: &[RotationPolicy],
+  ) -> Result<(), Self::WriterError> {
+    self.write_json_section(SECTION_ROTATION_POLICIES, policies)
+  }
+
+  fn write_collections(&mut self, collections: &[CollectionItem]) -> Result<(), Self::WriterError> {
+    self.write_json_section(SECTION_COLLECTIONS, collections)
+  }
+
+  fn write_templates(&mut self, templates: &[KeyTemplateItem]) -> Result<(), Self::WriterError> {
+    self.write_json_section(SECTION_TEMPLATES, templates)
   }
 
   fn write_icons(&mut self, paths: &[(String, PathBuf)]) -> Result<(), Self::WriterError> {
@@ -119,7 +172,7 @@ where
     for (name, path) in paths {
       let mut fd = std::fs::File::open(path).map_err(|err| {
         error!("Unable to open icon file, {}", err);
-        Error::BackupError(err.to_string())
+        Error::backup_from("Unable to open icon file.", err).with_path(path)
       })?;
 
       let mut buffer = [0u8; 4096];
@@ -143,7 +196,7 @@ where
                 Err(err) if err.kind() == ErrorKind::Interrupted => continue 'writer,
                 Err(err) => {
                   error!("{}", err);
-                  return Err(Error::BackupError(err.to_string()));
+                  return Err(Error::backup_from("Unable to write icon section.", err));
                 }
                 Ok(()) => {
                   self.sign_gen.update(buf);
@@ -156,7 +209,9 @@ where
           Err(err) if err.kind() == ErrorKind::Interrupted => continue 'reader,
           Err(err) => {
             error!("Unable to read icon file content, {}", err);
-            return Err(Error::BackupError(err.to_string()));
+            return Err(
+              Error::backup_from("Unable to read icon file content.", err).with_path(path),
+            );
           }
         }
       }
@@ -206,6 +261,24 @@ where
       manifest,
     })
   }
+
+  /// Reads a JSON list section. Sections missing from older backups are read as empty lists.
+  fn read_json_section<D>(&mut self, name: &str) -> Result<Vec<D>, Error>
+  where
+    D: DeserializeOwned,
+  {
+    match self.inner.by_name(name) {
+      Ok(file) => {
+        let items: Vec<D> = serde_json::from_reader(file)?;
+        Ok(items)
+      }
+      Err(zip::result::ZipError::FileNotFound) => Ok(Vec::new()),
+      Err(err) => {
+        error!("Reading the {} backup section failed, {}", name, err);
+        Err(err.into())
+      }
+    }
+  }
 }
 
 impl<T> BackupReader for FileBackupReader<T>
@@ -218,32 +291,58 @@ where
     Ok(self.manifest.clone())
   }
 
-  fn charsets(&mut self) -> Result<Vec<crate::profile::charsets::CharsetItem>, Self::ReaderError> {
-    match self.inner.by_name(SECTION_CHARSETS) {
-      Ok(file) => {
-        let charsets: Vec<CharsetItem> = serde_json::from_reader(file)?;
-        Ok(charsets)
-      }
-      Err(zip::result::ZipError::FileNotFound) => Ok(Vec::new()),
-      Err(err) => {
-        error!("Reading the charset backup failed, {}", err);
-        Err(err.into())
-      }
-    }
+  fn charsets(&mut self) -> Result<Vec<CharsetItem>, Self::ReaderError> {
+    self.read_json_section(SECTION_CHARSETS)
   }
 
-  fn keys(&mut self) -> Result<Vec<crate::profile::keys::KeyItem>, Self::ReaderError> {
-    match self.inner.by_name(SECTION_KEYS) {
-      Ok(file) => {
-        let keys: Vec<KeyItem> = serde_json::from_reader(file)?;
-        Ok(keys)
+  fn keys(&mut self) -> Result<Vec<KeyItem>, Self::ReaderError> {
+    self.read_json_section(SECTION_KEYS)
+  }
+
+  fn key_history(&mut self) -> Result<Vec<KeyHistoryItem>, Self::ReaderError> {
+    self.read_json_section(SECTION_KEY_HISTORY)
+  }
+
+  fn key_rotations(&mut self) -> Result<Vec<KeyRotationItem>, Self::ReaderError> {
+    self.read_json_section(SECTION_KEY_ROTATIONS)
+  }
+
+  fn key_secrets(&mut self, key: &[u8]) -> Result<BackupSecrets, Self::ReaderError> {
+    let encrypted = match self.inner.by_name(SECTION_KEY_SECRETS) {
+      Ok(mut file) => {
+        let mut buf: Vec<u8> = Vec::new();
+        file.read_to_end(&mut buf).map_err(|err| {
+          error!("{}", &err);
+          Error::backup_from("Unable to read backup content.", err)
+        })?;
+        buf
       }
-      Err(zip::result::ZipError::FileNotFound) => Ok(Vec::new()),
+      Err(zip::result::ZipError::FileNotFound) => return Ok(BackupSecrets::default()),
       Err(err) => {
-        error!("Reading the charset backup failed, {}", err);
-        Err(err.into())
+        error!(
+          "Reading the {} backup section failed, {}",
+          SECTION_KEY_SECRETS, err
+        );
+        return Err(err.into());
       }
-    }
+    };
+
+    let bytes = decrypt_section(key, &encrypted)?;
+    let secrets: BackupSecrets = serde_json::from_slice(&bytes)?;
+
+    Ok(secrets)
+  }
+
+  fn rotation_policies(&mut self) -> Result<Vec<RotationPolicy>, Self::ReaderError> {
+    self.read_json_section(SECTION_ROTATION_POLICIES)
+  }
+
+  fn collections(&mut self) -> Result<Vec<CollectionItem>, Self::ReaderError> {
+    self.read_json_section(SECTION_COLLECTIONS)
+  }
+
+  fn templates(&mut self) -> Result<Vec<KeyTemplateItem>, Self::ReaderError> {
+    self.read_json_section(SECTION_TEMPLATES)
   }
 
   fn icons(&mut self) -> Result<Vec<IconDetails>, Self::ReaderError> {
@@ -276,7 +375,7 @@ where
         Ok(0) => {
           target.flush().map_err(|err| {
             error!("{}", err);
-            Error::BackupError(err.to_string())
+            Error::backup_from("Unable to flush target stream.", err)
           })?;
 
           break 'reader;
@@ -284,9 +383,9 @@ where
         Ok(size) => 'writer: loop {
           match target.write(&buf[..size]) {
             Ok(0) => {
-              return Err(Error::BackupError(String::from(
+              return Err(Error::backup(
                 "Cannot write to target stream. Stream might be closed or not accessible.",
-              )));
+              ));
             }
             Ok(written_size) => {
               written += written_size;
@@ -296,10 +395,10 @@ where
             Err(err) if err.kind() == ErrorKind::WouldBlock => continue 'writer,
             Err(err) => {
               error!("{}", err);
-              return Err(Error::BackupError(format!(
-                "unable to write backup content to target writer, {}",
-                err
-              )));
+              return Err(Error::backup_from(
+                "Unable to write backup content to target writer.",
+                err,
+              ));
             }
           };
         },
@@ -307,10 +406,7 @@ where
         Err(err) if err.kind() == ErrorKind::Interrupted => continue 'reader,
         Err(err) => {
           error!("{}", err);
-          return Err(Error::BackupError(format!(
-            "Unable to read backup content, {}",
-            err
-          )));
+          return Err(Error::backup_from("Unable to read backup content.", err));
         }
       };
     }
@@ -323,7 +419,7 @@ where
       Some(manifest_digest) => {
         let mut mac = Hmac256::new_from_slice(key).map_err(|err| {
           error!("{}", err);
-          Error::BackupError(err.to_string())
+          Error::backup_from("Invalid backup sign key.", err.to_string())
         })?;
 
         for file_name in &self.manifest.files {
@@ -332,7 +428,7 @@ where
 
           fd.read_to_end(&mut buf).map_err(|err| {
             error!("{}", &err);
-            Error::BackupError(err.to_string())
+            Error::backup_from("Unable to read backup content.", err)
           })?;
 
           mac.update(&buf);
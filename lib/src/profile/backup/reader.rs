@@ -1,8 +1,11 @@
 use std::io::Write;
 
-use super::BackupManifest;
+use super::{BackupManifest, BackupSecrets};
 use crate::profile::charsets::CharsetItem;
-use crate::profile::keys::KeyItem;
+use crate::profile::collections::CollectionItem;
+use crate::profile::key_history::KeyHistoryItem;
+use crate::profile::keys::{KeyItem, KeyRotationItem, RotationPolicy};
+use crate::profile::templates::KeyTemplateItem;
 
 #[derive(Debug)]
 pub struct IconDetails {
@@ -17,6 +20,19 @@ pub trait BackupReader {
   fn manifest(&mut self) -> Result<BackupManifest, Self::ReaderError>;
   fn charsets(&mut self) -> Result<Vec<CharsetItem>, Self::ReaderError>;
   fn keys(&mut self) -> Result<Vec<KeyItem>, Self::ReaderError>;
+  /// Older backups without key history return an empty list.
+  fn key_history(&mut self) -> Result<Vec<KeyHistoryItem>, Self::ReaderError>;
+  /// Older backups without rotation records return an empty list.
+  fn key_rotations(&mut self) -> Result<Vec<KeyRotationItem>, Self::ReaderError>;
+  /// Decrypts the stored secrets with the key the backup is signed with. Older backups without
+  /// stored secrets return empty lists.
+  fn key_secrets(&mut self, key: &[u8]) -> Result<BackupSecrets, Self::ReaderError>;
+  /// Older backups without rotation policies return an empty list.
+  fn rotation_policies(&mut self) -> Result<Vec<RotationPolicy>, Self::ReaderError>;
+  /// Older backups without collections return an empty list.
+  fn collections(&mut self) -> Result<Vec<CollectionItem>, Self::ReaderError>;
+  /// Older backups without key templates return an empty list.
+  fn templates(&mut self) -> Result<Vec<KeyTemplateItem>, Self::ReaderError>;
   fn icons(&mut self) -> Result<Vec<IconDetails>, Self::ReaderError>;
   fn copy_section_to<T>(&mut self, name: &str, target: T) -> Result<usize, Self::ReaderError>
   where
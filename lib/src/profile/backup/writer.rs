@@ -1,5 +1,9 @@
+use super::BackupSecrets;
 use crate::profile::charsets::CharsetItem;
-use crate::profile::keys::KeyItem;
+use crate::profile::collections::CollectionItem;
+use crate::profile::key_history::KeyHistoryItem;
+use crate::profile::keys::{KeyItem, KeyRotationItem, RotationPolicy};
+use crate::profile::templates::KeyTemplateItem;
 use std::io::Write;
 use std::path::PathBuf;
 
@@ -8,6 +12,16 @@ pub(crate) trait BackupWriter: Write {
 
   fn write_charsets(&mut self, charsets: &[CharsetItem]) -> Result<(), Self::WriterError>;
   fn write_keys(&mut self, keys: &[KeyItem]) -> Result<(), Self::WriterError>;
+  fn write_key_history(&mut self, history: &[KeyHistoryItem]) -> Result<(), Self::WriterError>;
+  fn write_key_rotations(&mut self, rotations: &[KeyRotationItem])
+    -> Result<(), Self::WriterError>;
+  fn write_key_secrets(&mut self, secrets: &BackupSecrets) -> Result<(), Self::WriterError>;
+  fn write_rotation_policies(
+    &mut self,
+    policies: &[RotationPolicy],
+  ) -> Result<(), Self::WriterError>;
+  fn write_collections(&mut self, collections: &[CollectionItem]) -> Result<(), Self::WriterError>;
+  fn write_templates(&mut self, templates: &[KeyTemplateItem]) -> Result<(), Self::WriterError>;
   fn write_icons(&mut self, paths: &[(String, PathBuf)]) -> Result<(), Self::WriterError>;
   fn finish(self) -> Result<(), Self::WriterError>;
 }
@@ -1,11 +1,13 @@
-use crate::charset::validate_charset;
+use crate::charset::{validate_charset, Charset};
 use crate::errors::Error;
+use crate::profile::events::ProfileEvent;
+use crate::profile::keys::KeyItem;
 use crate::profile::ProfileDB;
 use serde::{Deserialize, Serialize};
 use sqlx::{query, query_as, FromRow};
 use validator::Validate;
 
-#[derive(Debug, FromRow, Serialize, Deserialize, Validate)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, Validate)]
 pub struct CharsetItem {
   #[validate(length(min = 1))]
   pub name: String,
@@ -14,6 +16,44 @@ pub struct CharsetItem {
   pub description: Option<String>,
 }
 
+/// Keys using a charset. Keys keep their own copy of the charset text, a key uses the charset when
+/// both definitions have the same symbols.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CharsetUsage {
+  pub name: String,
+  pub key_ids: Vec<i64>,
+}
+
+/// Returns the usages of each charset by the given keys, in the same order with charsets.
+pub(in crate::profile) fn charset_usage(
+  charsets: &[CharsetItem],
+  keys: &[KeyItem],
+) -> Vec<CharsetUsage> {
+  let key_charsets: Vec<(i64, Charset)> = keys
+    .iter()
+    .filter_map(|key| Charset::new(&key.charset).ok().map(|e| (key.id, e)))
+    .collect();
+
+  charsets
+    .iter()
+    .map(|item| {
+      let key_ids = match Charset::new(&item.charset) {
+        Ok(charset) => key_charsets
+          .iter()
+          .filter(|(_, key_charset)| *key_charset == charset)
+          .map(|(key_id, _)| *key_id)
+          .collect(),
+        Err(_) => Vec::new(),
+      };
+
+      CharsetUsage {
+        name: item.name.clone(),
+        key_ids,
+      }
+    })
+    .collect()
+}
+
 impl ProfileDB {
   pub async fn get_charsets(&self) -> Result<Vec<CharsetItem>, Error> {
     let mut conn = self.pool.acquire().await?;
@@ -24,10 +64,47 @@ impl ProfileDB {
     Ok(result)
   }
 
+  pub async fn get_charset(&self, name: &str) -> Result<Option<CharsetItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as!(
+      CharsetItem,
+      "SELECT name,charset,description FROM charsets WHERE name = ?",
+      name
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  /// Returns the keys using each charset, trashed keys are excluded.
+  pub async fn get_charset_usage(&self) -> Result<Vec<CharsetUsage>, Error> {
+    let charsets = self.get_charsets().await?;
+    let keys = self.get_keys(false).await?;
+
+    Ok(charset_usage(&charsets, &keys))
+  }
+
   pub async fn insert_charset(&self, item: CharsetItem) -> Result<String, Error> {
     item.validate()?;
 
-    _ = crate::charset::parser::parse(&item.charset)?;
+    let charset = Charset::new(&item.charset)?;
+    let existing = self.get_charsets().await?;
+
+    if existing
+      .iter()
+      .any(|e| e.name.eq_ignore_ascii_case(&item.name))
+    {
+      return Err(Error::DuplicateCharsetName(item.name));
+    }
+
+    if let Some(duplicate) = existing
+      .into_iter()
+      .find(|e| Charset::new(&e.charset).is_ok_and(|existing| existing == charset))
+    {
+      return Err(Error::DuplicateCharset(duplicate.name));
+    }
+
     let mut conn = self.pool.acquire().await?;
 
     query!(
@@ -39,15 +116,109 @@ impl ProfileDB {
     .execute(&mut *conn)
     .await?;
 
+    self.notify(ProfileEvent::CharsetChanged {
+      name: item.name.clone(),
+    });
+
     Ok(item.name)
   }
 
-  pub async fn delete_charset(&self, name: &str) -> Result<u64, Error> {
+  /// Updates the charset definition and description of the charset with the same name.
+  ///
+  /// Existing keys keep their charset text, their passwords are not changed.
+  pub async fn update_charset(&self, item: CharsetItem) -> Result<(), Error> {
+    item.validate()?;
+
+    let charset = Charset::new(&item.charset)?;
+    let existing = self.get_charsets().await?;
+
+    if let Some(duplicate) = existing.into_iter().find(|e| {
+      !e.name.eq_ignore_ascii_case(&item.name)
+        && Charset::new(&e.charset).is_ok_and(|existing| existing == charset)
+    }) {
+      return Err(Error::DuplicateCharset(duplicate.name));
+    }
+
+    let mut conn = self.pool.acquire().await?;
+    let result = query!(
+      "UPDATE charsets SET charset = ?, description = ? WHERE name = ?",
+      item.charset,
+      item.description,
+      item.name
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if result.rows_affected() == 0 {
+      return Err(Error::CharsetNotFound(item.name));
+    }
+
+    self.notify(ProfileEvent::CharsetChanged { name: item.name });
+
+    Ok(())
+  }
+
+  pub async fn rename_charset(&self, name: &str, new_name: &str) -> Result<(), Error> {
+    let item = self
+      .get_charset(name)
+      .await?
+      .ok_or_else(|| Error::CharsetNotFound(String::from(name)))?;
+    let renamed = CharsetItem {
+      name: String::from(new_name),
+      ..item
+    };
+    renamed.validate()?;
+
+    if !name.eq_ignore_ascii_case(new_name) && self.get_charset(new_name).await?.is_some() {
+      return Err(Error::DuplicateCharsetName(renamed.name));
+    }
+
+    let mut conn = self.pool.acquire().await?;
+    query!(
+      "UPDATE charsets SET name = ? WHERE name = ?",
+      renamed.name,
+      name
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    self.notify(ProfileEvent::CharsetChanged {
+      name: String::from(name),
+    });
+    self.notify(ProfileEvent::CharsetChanged { name: renamed.name });
+
+    Ok(())
+  }
+
+  /// Deletes the charset. Charsets used by keys are only deleted when `force` is set, the keys keep
+  /// their own charset text either way.
+  pub async fn delete_charset(&self, name: &str, force: bool) -> Result<u64, Error> {
+    if !force {
+      if let Some(item) = self.get_charset(name).await? {
+        let keys = self.get_keys(false).await?;
+        let usage = charset_usage(&[item], &keys);
+        let key_count = usage[0].key_ids.len();
+
+        if key_count > 0 {
+          return Err(Error::CharsetInUse {
+            name: String::from(name),
+            key_count,
+          });
+        }
+      }
+    }
+
     let mut conn = self.pool.acquire().await?;
     let result = query!("DELETE FROM charsets WHERE name = ?", name)
       .execute(&mut *conn)
       .await?;
 
+    if result.rows_affected() > 0 {
+      self.notify(ProfileEvent::CharsetChanged {
+        name: String::from(name),
+      });
+    }
+
     Ok(result.rows_affected())
   }
 }
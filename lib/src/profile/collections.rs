@@ -0,0 +1,346 @@
+use crate::errors::Error;
+use crate::profile::events::ProfileEvent;
+use crate::profile::utils::timestamp::get_unix_timestamp;
+use crate::profile::ProfileDB;
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, Connection, FromRow, QueryBuilder, Sqlite, SqliteConnection};
+use validator::{ValidationError, ValidationErrors};
+
+const COLLECTION_PATH_SEPARATOR: char = '/';
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct CollectionItem {
+  pub id: i64,
+  pub name: String,
+  /// Parent collection, `None` for root collections.
+  pub parent_id: Option<i64>,
+  pub created_at: i64,
+  /// Collection names from the root collection joined with `/`, e.g. `clients/acme/prod`.
+  #[serde(default)]
+  pub path: String,
+}
+
+impl ProfileDB {
+  /// Returns all collections ordered by their paths, so parents are always listed before their
+  /// sub collections.
+  pub async fn get_collections(&self) -> Result<Vec<CollectionItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as::<Sqlite, CollectionItem>(
+      "WITH RECURSIVE tree(id, path) AS (
+        SELECT id, name FROM collections WHERE parent_id IS NULL
+        UNION ALL
+        SELECT collections.id, tree.path || '/' || collections.name
+        FROM collections
+        INNER JOIN tree ON collections.parent_id = tree.id
+      )
+      SELECT collections.id, collections.name, collections.parent_id, collections.created_at, tree.path
+      FROM collections
+      INNER JOIN tree ON tree.id = collections.id
+      ORDER BY tree.path",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  pub async fn get_collection(&self, collection_id: i64) -> Result<Option<CollectionItem>, Error> {
+    let collections = self.get_collections().await?;
+    Ok(collections.into_iter().find(|e| e.id == collection_id))
+  }
+
+  pub async fn create_collection(&self, name: &str, parent_id: Option<i64>) -> Result<i64, Error> {
+    let name = name.trim();
+    validate_collection_name(name)?;
+
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+    let collection_id = Self::insert_collection(&mut transaction, name, parent_id).await?;
+    transaction.commit().await?;
+
+    Ok(collection_id)
+  }
+
+  /// Creates the missing collections of a `/` separated path such as `clients/acme/prod`. Returns
+  /// the id of the last collection.
+  pub async fn create_collection_path(&self, path: &str) -> Result<i64, Error> {
+    let names: Vec<&str> = path
+      .split(COLLECTION_PATH_SEPARATOR)
+      .map(str::trim)
+      .filter(|name| !name.is_empty())
+      .collect();
+
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+    let mut parent_id: Option<i64> = None;
+
+    for name in names {
+      let existing = query!(
+        r#"SELECT id as "id!" FROM collections WHERE ifnull(parent_id, 0) = ifnull(?, 0) AND name = ?"#,
+        parent_id,
+        name
+      )
+      .fetch_optional(&mut *transaction)
+      .await?;
+
+      parent_id = match existing {
+        Some(row) => Some(row.id),
+        None => Some(Self::insert_collection(&mut transaction, name, parent_id).await?),
+      };
+    }
+
+    transaction.commit().await?;
+
+    parent_id.ok_or_else(|| collection_name_error().into())
+  }
+
+  pub async fn rename_collection(&self, collection_id: i64, name: &str) -> Result<(), Error> {
+    let name = name.trim();
+    validate_collection_name(name)?;
+
+    let mut conn = self.pool.acquire().await?;
+    let current = query!(
+      "SELECT parent_id FROM collections WHERE id = ?",
+      collection_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(Error::CollectionNotFound { collection_id })?;
+
+    Self::ensure_unique_name(&mut conn, name, current.parent_id, Some(collection_id)).await?;
+
+    query!(
+      "UPDATE collections SET name = ? WHERE id = ?",
+      name,
+      collection_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Moves the collection with its sub collections and keys under another collection. `None`
+  /// moves it to the root.
+  pub async fn move_collection(
+    &self,
+    collection_id: i64,
+    parent_id: Option<i64>,
+  ) -> Result<(), Error> {
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+    let current = query!("SELECT name FROM collections WHERE id = ?", collection_id)
+      .fetch_optional(&mut *transaction)
+      .await?
+      .ok_or(Error::CollectionNotFound { collection_id })?;
+
+    if let Some(parent_id) = parent_id {
+      Self::ensure_collection_exists(&mut transaction, parent_id).await?;
+
+      let subtree = Self::get_subtree_ids(&mut transaction, collection_id).await?;
+      if subtree.contains(&parent_id) {
+        return Err(Error::InvalidCollectionParent { collection_id });
+      }
+    }
+
+    Self::ensure_unique_name(
+      &mut transaction,
+      &current.name,
+      parent_id,
+      Some(collection_id),
+    )
+    .await?;
+
+    query!(
+      "UPDATE collections SET parent_id = ? WHERE id = ?",
+      parent_id,
+      collection_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+  }
+
+  /// Deletes the collection and its sub collections. Their keys, including the ones in trash,
+  /// move to the parent of the deleted collection.
+  pub async fn delete_collection(&self, collection_id: i64) -> Result<(), Error> {
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+    let current = query!(
+      "SELECT parent_id FROM collections WHERE id = ?",
+      collection_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(Error::CollectionNotFound { collection_id })?;
+
+    let subtree = Self::get_subtree_ids(&mut transaction, collection_id).await?;
+
+    let mut query_builder: QueryBuilder<Sqlite> =
+      QueryBuilder::new("UPDATE keys SET collection_id = ");
+    query_builder.push_bind(current.parent_id);
+    query_builder.push(" WHERE keys.collection_id IN (");
+    let mut separated = query_builder.separated(", ");
+    for id in subtree.iter() {
+      separated.push_bind(*id);
+    }
+    separated.push_unseparated(") RETURNING keys.id");
+    let moved_ids: Vec<(i64,)> = query_builder
+      .build_query_as()
+      .fetch_all(&mut *transaction)
+      .await?;
+
+    let mut query_builder: QueryBuilder<Sqlite> =
+      QueryBuilder::new("DELETE FROM collections WHERE id IN (");
+    let mut separated = query_builder.separated(", ");
+    for id in subtree.iter() {
+      separated.push_bind(*id);
+    }
+    separated.push_unseparated(")");
+    query_builder.build().execute(&mut *transaction).await?;
+
+    transaction.commit().await?;
+
+    for (key_id,) in moved_ids {
+      self.notify(ProfileEvent::KeyUpdated { key_id });
+    }
+
+    Ok(())
+  }
+
+  /// Assigns the keys to the collection. `None` removes the keys from their collections. Returns
+  /// the number of updated keys.
+  pub async fn move_keys_to_collection(
+    &self,
+    key_ids: &[i64],
+    collection_id: Option<i64>,
+  ) -> Result<u64, Error> {
+    if key_ids.is_empty() {
+      return Ok(0);
+    }
+
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+
+    if let Some(collection_id) = collection_id {
+      Self::ensure_collection_exists(&mut transaction, collection_id).await?;
+    }
+
+    let mut query_builder: QueryBuilder<Sqlite> =
+      QueryBuilder::new("UPDATE keys SET collection_id = ");
+    query_builder.push_bind(collection_id);
+    query_builder.push(" WHERE keys.id IN (");
+    let mut separated = query_builder.separated(", ");
+    for key_id in key_ids {
+      separated.push_bind(*key_id);
+    }
+    separated.push_unseparated(") RETURNING keys.id");
+    let moved_ids: Vec<(i64,)> = query_builder
+      .build_query_as()
+      .fetch_all(&mut *transaction)
+      .await?;
+
+    transaction.commit().await?;
+
+    for (key_id,) in moved_ids.iter() {
+      self.notify(ProfileEvent::KeyUpdated { key_id: *key_id });
+    }
+
+    Ok(moved_ids.len() as u64)
+  }
+
+  /// Returns the collection id with the ids of all its sub collections.
+  pub(in crate::profile) async fn get_subtree_ids(
+    conn: &mut SqliteConnection,
+    collection_id: i64,
+  ) -> Result<Vec<i64>, Error> {
+    let ids: Vec<(i64,)> = query_as(
+      "WITH RECURSIVE subtree(id) AS (
+        SELECT ?
+        UNION
+        SELECT collections.id FROM collections INNER JOIN subtree ON collections.parent_id = subtree.id
+      )
+      SELECT id FROM subtree",
+    )
+    .bind(collection_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(ids.into_iter().map(|(id,)| id).collect())
+  }
+
+  async fn insert_collection(
+    conn: &mut SqliteConnection,
+    name: &str,
+    parent_id: Option<i64>,
+  ) -> Result<i64, Error> {
+    if let Some(parent_id) = parent_id {
+      Self::ensure_collection_exists(&mut *conn, parent_id).await?;
+    }
+
+    Self::ensure_unique_name(&mut *conn, name, parent_id, None).await?;
+
+    let now: i64 = get_unix_timestamp()?;
+    let result = query!(
+      "INSERT INTO collections (name, parent_id, created_at) VALUES (?, ?, ?)",
+      name,
+      parent_id,
+      now
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+  }
+
+  async fn ensure_collection_exists(
+    conn: &mut SqliteConnection,
+    collection_id: i64,
+  ) -> Result<(), Error> {
+    query!("SELECT id FROM collections WHERE id = ?", collection_id)
+      .fetch_optional(&mut *conn)
+      .await?
+      .ok_or(Error::CollectionNotFound { collection_id })?;
+
+    Ok(())
+  }
+
+  async fn ensure_unique_name(
+    conn: &mut SqliteConnection,
+    name: &str,
+    parent_id: Option<i64>,
+    exclude_id: Option<i64>,
+  ) -> Result<(), Error> {
+    let duplicate = query!(
+      "SELECT id FROM collections
+      WHERE ifnull(parent_id, 0) = ifnull(?, 0) AND name = ? AND id != ifnull(?, 0)",
+      parent_id,
+      name,
+      exclude_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    match duplicate {
+      Some(_) => Err(Error::DuplicateCollection(String::from(name))),
+      None => Ok(()),
+    }
+  }
+}
+
+fn validate_collection_name(name: &str) -> Result<(), ValidationErrors> {
+  if name.is_empty() || name.contains(COLLECTION_PATH_SEPARATOR) {
+    return Err(collection_name_error());
+  }
+
+  Ok(())
+}
+
+fn collection_name_error() -> ValidationErrors {
+  let mut errors = ValidationErrors::new();
+  errors.add("name", ValidationError::new("collection_name"));
+  errors
+}
@@ -0,0 +1,52 @@
+use crate::profile::ProfileDB;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, Sender};
+
+pub use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+/// Number of events kept for slow subscribers. Subscribers falling further behind receive
+/// [broadcast::error::RecvError::Lagged] and should re-fetch the profile state.
+const EVENT_CAPACITY: usize = 128;
+
+/// Change notification published after a profile write is committed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProfileEvent {
+  KeyCreated {
+    key_id: i64,
+  },
+  KeyUpdated {
+    key_id: i64,
+  },
+  /// Key is moved to trash or purged.
+  KeyDeleted {
+    key_id: i64,
+  },
+  KeyPinned {
+    key_id: i64,
+    pinned: bool,
+  },
+  /// Charset is created or deleted.
+  CharsetChanged {
+    name: String,
+  },
+  /// Profile content is replaced from a backup, every view should be reloaded.
+  RestoreCompleted,
+}
+
+pub(in crate::profile) fn event_channel() -> Sender<ProfileEvent> {
+  broadcast::channel(EVENT_CAPACITY).0
+}
+
+impl ProfileDB {
+  /// Returns a receiver for the changes made after this call. Events of a single write arrive in
+  /// order, the receiver is closed when the profile is dropped.
+  pub fn subscribe(&self) -> Receiver<ProfileEvent> {
+    self.events.subscribe()
+  }
+
+  /// Publishes the event, events without subscribers are dropped.
+  pub(in crate::profile) fn notify(&self, event: ProfileEvent) {
+    let _ = self.events.send(event);
+  }
+}
@@ -0,0 +1,224 @@
+use crate::errors::Error;
+use crate::profile::keys::{KeyKind, REDACTED};
+use crate::profile::ProfileDB;
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, FromRow, QueryBuilder, Sqlite, SqliteConnection};
+use std::fmt::{Debug, Formatter};
+
+/// Password parameters of a key before an update. Snapshots keep everything needed to regenerate
+/// the previous password.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct KeyHistoryItem {
+  pub id: i64,
+  pub key_id: i64,
+  pub target_size: i64,
+  pub revision: i64,
+  pub charset: String,
+  pub domain: String,
+  pub username: String,
+  pub version: String,
+  #[serde(default)]
+  pub exclude_lookalikes: bool,
+  /// Stored snapshots keep the previous secret instead, see [ProfileDB::get_key_history_secret].
+  #[serde(default)]
+  pub kind: KeyKind,
+  /// Unix timestamp of the update that replaced these parameters.
+  pub replaced_at: i64,
+}
+
+/// Previous secret of a stored key, attached to its [KeyHistoryItem].
+#[derive(FromRow, Serialize, Deserialize)]
+pub struct KeyHistorySecretItem {
+  pub history_id: i64,
+  pub secret: String,
+}
+
+impl Debug for KeyHistorySecretItem {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("KeyHistorySecretItem")
+      .field("history_id", &self.history_id)
+      .field("secret", &REDACTED)
+      .finish()
+  }
+}
+
+impl ProfileDB {
+  /// Returns the previous parameters of the key, most recent first.
+  pub async fn get_key_history(&self, key_id: i64) -> Result<Vec<KeyHistoryItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as!(
+      KeyHistoryItem,
+      r#"SELECT
+        id as "id!",
+        key_id,
+        target_size,
+        revision,
+        charset,
+        domain,
+        username,
+        version,
+        exclude_lookalikes as "exclude_lookalikes: bool",
+        kind as "kind: KeyKind",
+        replaced_at
+      FROM key_history
+      WHERE key_id = ?
+      ORDER BY replaced_at DESC, id DESC"#,
+      key_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  pub async fn get_key_history_item(
+    &self,
+    history_id: i64,
+  ) -> Result<Option<KeyHistoryItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as!(
+      KeyHistoryItem,
+      r#"SELECT
+        id as "id!",
+        key_id,
+        target_size,
+        revision,
+        charset,
+        domain,
+        username,
+        version,
+        exclude_lookalikes as "exclude_lookalikes: bool",
+        kind as "kind: KeyKind",
+        replaced_at
+      FROM key_history
+      WHERE id = ?"#,
+      history_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  /// Returns the secret a stored key had before the update, `None` for derived snapshots.
+  pub async fn get_key_history_secret(&self, history_id: i64) -> Result<Option<String>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query!(
+      "SELECT key_history_secrets.secret FROM key_history_secrets
+      WHERE key_history_secrets.history_id = ?",
+      history_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(result.map(|row| row.secret))
+  }
+
+  pub(super) async fn get_all_key_history_secrets(
+    &self,
+  ) -> Result<Vec<KeyHistorySecretItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as!(
+      KeyHistorySecretItem,
+      "SELECT history_id, secret FROM key_history_secrets"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  pub(super) async fn get_all_key_history(&self) -> Result<Vec<KeyHistoryItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as!(
+      KeyHistoryItem,
+      r#"SELECT
+        id as "id!",
+        key_id,
+        target_size,
+        revision,
+        charset,
+        domain,
+        username,
+        version,
+        exclude_lookalikes as "exclude_lookalikes: bool",
+        kind as "kind: KeyKind",
+        replaced_at
+      FROM key_history"#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  /// Copies the current password parameters of the key into the history. Stored keys are skipped
+  /// since their parameters do not produce the password.
+  pub(super) async fn insert_key_snapshot(
+    conn: &mut SqliteConnection,
+    key_id: i64,
+    replaced_at: i64,
+  ) -> Result<(), Error> {
+    query!(
+      "INSERT INTO key_history
+        (key_id, target_size, revision, charset, domain, username, version, exclude_lookalikes, replaced_at)
+      SELECT id, target_size, revision, charset, domain, username, version, exclude_lookalikes, ?
+      FROM keys
+      WHERE id = ? AND kind = 'derived'",
+      replaced_at,
+      key_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Copies the current secret of a stored key into the history before it is replaced or removed.
+  pub(super) async fn insert_key_secret_snapshot(
+    conn: &mut SqliteConnection,
+    key_id: i64,
+    replaced_at: i64,
+  ) -> Result<(), Error> {
+    let result = query!(
+      "INSERT INTO key_history
+        (key_id, target_size, revision, charset, domain, username, version, exclude_lookalikes, kind, replaced_at)
+      SELECT id, target_size, revision, charset, domain, username, version, exclude_lookalikes, kind, ?
+      FROM keys
+      WHERE id = ? AND kind = 'stored'",
+      replaced_at,
+      key_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if result.rows_affected() > 0 {
+      let history_id = result.last_insert_rowid();
+      query!(
+        "INSERT INTO key_history_secrets (history_id, secret)
+        SELECT ?, secret FROM key_secrets WHERE key_id = ?",
+        history_id,
+        key_id
+      )
+      .execute(&mut *conn)
+      .await?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Builds a single insert statement for the given history secrets.
+pub(super) fn create_key_history_secret_query(
+  secrets: &[KeyHistorySecretItem],
+) -> QueryBuilder<'_, Sqlite> {
+  let mut query_builder: QueryBuilder<Sqlite> =
+    QueryBuilder::new("INSERT INTO key_history_secrets (history_id, secret) ");
+
+  query_builder.push_values(secrets.iter(), |mut b, secret_item| {
+    b.push_bind(secret_item.history_id);
+    b.push_bind(&secret_item.secret);
+  });
+
+  query_builder
+}
@@ -1,35 +1,76 @@
-use crate::charset::validate_charset;
+use crate::charset::{validate_charset, validate_charset_with, CharsetOptions};
 use crate::errors::Error;
+use crate::hash::HashAlgorithm;
+use crate::profile::events::ProfileEvent;
 use crate::profile::utils::tag_list::TagList;
+use crate::profile::utils::text_fold::fold_text;
 use crate::profile::utils::timestamp::get_unix_timestamp;
 use crate::profile::ProfileDB;
+use fields::validate_key_fields;
+pub(in crate::profile) use secrets::REDACTED;
 use serde::{Deserialize, Serialize};
 use sqlx::{query, query_as, Connection, FromRow, QueryBuilder, Sqlite, SqliteConnection};
-use validator::Validate;
+use std::fmt::{Debug, Formatter};
+use urls::validate_key_urls;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+pub use crate::profile::search_query::SearchQuery;
+pub use bulk::KeyBulkAction;
+pub(in crate::profile) use duplicates::account_params;
+pub use duplicates::{DuplicateGroup, DuplicateReason, KeyWriteOptions, UniquenessCheck};
+pub(in crate::profile) use fields::{create_key_field_query, field_index_text};
+pub use fields::{KeyField, KeyFieldType};
+pub use highlight::{HighlightSegment, SearchHighlights, SearchResult};
+pub use rotation::{KeyRotationItem, RotationDueItem, RotationPolicy};
+pub use search_index::SearchIndexReport;
+pub(in crate::profile) use secrets::create_key_secret_query;
+pub use secrets::{KeyKind, KeySecretItem};
+pub(in crate::profile) use urls::{create_key_url_query, url_index_text};
+pub use urls::{KeyUrl, UrlMatchItem, UrlMatchKind, UrlMatchRule};
+
+mod bulk;
+mod duplicates;
+mod fields;
+mod fuzzy;
+mod highlight;
+mod rotation;
+mod search_index;
+mod secrets;
+mod trash;
+mod urls;
 
 struct SearchIndex {
   domain: String,
+  fields: String,
   id: i64,
   notes: Option<String>,
   tags: String,
+  urls: String,
   username: String,
 }
 
+impl SearchIndex {
+  /// Values stored in the trigram index.
+  fn folded(&self) -> Self {
+    Self {
+      domain: fold_text(&self.domain),
+      fields: fold_text(&self.fields),
+      id: self.id,
+      notes: self.notes.as_deref().map(fold_text),
+      tags: fold_text(&self.tags),
+      urls: fold_text(&self.urls),
+      username: fold_text(&self.username),
+    }
+  }
+}
+
 enum SearchIndexOp {
   Delete(i64),
   Update(SearchIndex),
   Create(SearchIndex),
 }
 
-#[derive(Debug)]
-pub struct SearchQuery {
-  pub username_tokens: Vec<Box<str>>,
-  pub domain_tokens: Vec<Box<str>>,
-  pub tag_tokens: Vec<Box<str>>,
-  pub fts_tokens: Vec<Box<str>>,
-}
-
-#[derive(Debug, FromRow, Serialize, Deserialize)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct KeyItem {
   pub id: i64,
   pub pinned: bool,
@@ -42,14 +83,42 @@ pub struct KeyItem {
   pub created_at: i64,
   pub custom_icon: Option<String>,
   pub version: String,
+  #[serde(default)]
+  pub exclude_lookalikes: bool,
+  /// Unix timestamp of the last password generation, `None` if the key is never used.
+  #[serde(default)]
+  pub last_used_at: Option<i64>,
+  #[serde(default)]
+  pub use_count: i64,
+  /// Unix timestamp of the last update, `None` if the key is never updated.
+  #[serde(default)]
+  pub updated_at: Option<i64>,
+  /// Unix timestamp of the move to trash, `None` if the key is not in trash.
+  #[serde(default)]
+  pub deleted_at: Option<i64>,
+  /// Rotation interval of the key in days. Overrides the tag rotation policies.
+  #[serde(default)]
+  pub rotation_days: Option<i64>,
+  #[serde(default)]
+  pub collection_id: Option<i64>,
+  /// Stored secrets are returned from [ProfileDB::get_key_secret], never with the key.
+  #[serde(default)]
+  pub kind: KeyKind,
 
   // Query default value must be an empty array for tags since current sqlx version does not support nullable json columns.
   // See https://github.com/launchbadge/sqlx/issues/2849
   #[sqlx(json)]
   pub tags: TagList,
+  /// Alternative hosts, URLs and app ids of the key.
+  #[serde(default)]
+  #[sqlx(json)]
+  pub urls: Vec<KeyUrl>,
+  #[serde(default)]
+  #[sqlx(json)]
+  pub fields: Vec<KeyField>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Validate)]
+#[derive(Serialize, Deserialize, Validate)]
 pub struct KeyData {
   #[validate(range(min = 1, max = 64))]
   pub target_size: i64,
@@ -65,13 +134,99 @@ pub struct KeyData {
   #[validate(length(min = 1))]
   pub version: String,
   pub tags: TagList,
+  #[serde(default)]
+  pub exclude_lookalikes: bool,
+  #[serde(default)]
+  #[validate(custom = "validate_key_urls")]
+  pub urls: Vec<KeyUrl>,
+  #[serde(default)]
+  #[validate(custom = "validate_key_fields")]
+  pub fields: Vec<KeyField>,
+  #[serde(default)]
+  pub kind: KeyKind,
+  /// Secret of a [KeyKind::Stored] key. Required when a key becomes a stored key, `None` keeps
+  /// the existing secret on updates.
+  #[serde(default, skip_serializing)]
+  #[validate(length(min = 1))]
+  pub secret: Option<String>,
 }
 
-impl ProfileDB {
-  pub async fn get_key_by_id(&self, key_id: i64) -> Result<Option<KeyItem>, Error> {
-    let mut conn = self.pool.acquire().await?;
-    let row = query_as::<Sqlite, KeyItem>(
-      "SELECT
+impl Debug for KeyData {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("KeyData")
+      .field("target_size", &self.target_size)
+      .field("revision", &self.revision)
+      .field("charset", &self.charset)
+      .field("domain", &self.domain)
+      .field("username", &self.username)
+      .field("notes", &self.notes)
+      .field("custom_icon", &self.custom_icon)
+      .field("version", &self.version)
+      .field("tags", &self.tags)
+      .field("exclude_lookalikes", &self.exclude_lookalikes)
+      .field("urls", &self.urls)
+      .field("fields", &self.fields)
+      .field("kind", &self.kind)
+      .field("secret", &self.secret.as_ref().map(|_| REDACTED))
+      .finish()
+  }
+}
+
+/// Derived key with the default charset and hash algorithm, domain and username must be set
+/// before inserting.
+impl Default for KeyData {
+  fn default() -> Self {
+    Self {
+      target_size: 32,
+      revision: 1,
+      charset: String::from("a..zA..Z0..9"),
+      domain: String::new(),
+      username: String::new(),
+      notes: None,
+      custom_icon: None,
+      version: HashAlgorithm::default().to_string(),
+      tags: TagList::new(),
+      exclude_lookalikes: false,
+      urls: Vec::new(),
+      fields: Vec::new(),
+      kind: KeyKind::Derived,
+      secret: None,
+    }
+  }
+}
+
+impl KeyData {
+  /// Validates fields and checks the charset still has symbols after applying charset options.
+  pub(in crate::profile) fn validate_all(&self) -> Result<(), ValidationErrors> {
+    self.validate()?;
+
+    let options = CharsetOptions {
+      exclude_lookalikes: self.exclude_lookalikes,
+    };
+
+    if let Err(err) = validate_charset_with(&self.charset, options) {
+      let mut errors = ValidationErrors::new();
+      errors.add("charset", err);
+      return Err(errors);
+    }
+
+    Ok(())
+  }
+
+  pub(in crate::profile) fn missing_secret() -> ValidationErrors {
+    let mut errors = ValidationErrors::new();
+    errors.add("secret", ValidationError::new("required"));
+    errors
+  }
+}
+
+const DEFAULT_PAGE_LIMIT: u32 = 50;
+const MAX_PAGE_LIMIT: u32 = 1000;
+const FRECENCY_DECAY_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Columns of [KeyItem]. Tags are selected with a correlated sub-query instead of joining
+/// vw_tag_list, so they are only aggregated for the selected keys.
+pub(in crate::profile) const KEY_ITEM_SELECT: &str = "SELECT
         keys.id,
         keys.pinned,
         keys.target_size,
@@ -83,11 +238,194 @@ impl ProfileDB {
         keys.created_at,
         keys.custom_icon,
         keys.version,
-        ifnull(vw_tag_list.tags, json_array()) as tags
+        keys.exclude_lookalikes,
+        keys.last_used_at,
+        keys.use_count,
+        keys.updated_at,
+        keys.deleted_at,
+        keys.rotation_days,
+        keys.collection_id,
+        keys.kind,
+        (SELECT json_group_array(json_object('url', url, 'match_rule', match_rule)) FROM (SELECT key_urls.url, key_urls.match_rule FROM key_urls WHERE key_urls.key_id = keys.id ORDER BY key_urls.ROWID)) as urls,
+        (SELECT json_group_array(json_object('name', name, 'field_type', field_type, 'value', value)) FROM (SELECT key_fields.name, key_fields.field_type, key_fields.value FROM key_fields WHERE key_fields.key_id = keys.id ORDER BY key_fields.ROWID)) as fields,
+        (SELECT json_group_array(tags.name) FROM tags WHERE tags.key_id = keys.id) as tags";
+
+/// Key list ordering. Every ordering ends with the key id, so pages never skip or repeat keys with
+/// the same sort value.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySort {
+  /// Newest keys first.
+  #[default]
+  CreatedAt,
+  Domain,
+  Username,
+  /// Pinned keys first, then newest keys.
+  PinnedFirst,
+  /// Frequently and recently used keys first. Uses offset cursors since the order changes over
+  /// time.
+  Frecency,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyListOptions {
+  /// `next_cursor` value of the previous page. Cursors are only valid for the same sort order.
+  pub cursor: Option<String>,
+  pub limit: u32,
+  /// Listing defaults to [KeySort::CreatedAt], search results default to relevance order.
+  pub sort: Option<KeySort>,
+  pub pinned_only: bool,
+  /// Limits the keys to the collection and its sub collections.
+  pub collection_id: Option<i64>,
+}
+
+impl Default for KeyListOptions {
+  fn default() -> Self {
+    Self {
+      cursor: None,
+      limit: DEFAULT_PAGE_LIMIT,
+      sort: None,
+      pinned_only: false,
+      collection_id: None,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeyPage<T = KeyItem> {
+  pub items: Vec<T>,
+  /// Cursor for the next page, `None` when there are no more keys.
+  pub next_cursor: Option<String>,
+  /// Number of keys matching the filters, regardless of the cursor.
+  pub total: i64,
+}
+
+/// Decoded form of the opaque page cursor.
+///
+/// Sorted pages use keyset pagination and keep the sort values of the last key on the page, so
+/// inserting or deleting keys does not shift the following pages. Relevance ordered search pages
+/// have no stable sort value and fall back to an offset.
+#[derive(Debug, PartialEq)]
+enum PageCursor {
+  After {
+    sort: KeySort,
+    id: i64,
+    created_at: i64,
+    pinned: bool,
+    text: String,
+  },
+  Offset(i64),
+}
+
+impl KeySort {
+  fn cursor_code(&self) -> &'static str {
+    match self {
+      KeySort::CreatedAt => "c",
+      KeySort::Domain => "d",
+      KeySort::Username => "u",
+      KeySort::PinnedFirst => "p",
+      KeySort::Frecency => "f",
+    }
+  }
+
+  fn order_by(&self) -> &'static str {
+    match self {
+      KeySort::CreatedAt => " ORDER BY keys.created_at DESC, keys.id DESC",
+      KeySort::Domain => " ORDER BY keys.domain ASC, keys.id ASC",
+      KeySort::Username => " ORDER BY keys.username ASC, keys.id ASC",
+      KeySort::PinnedFirst => " ORDER BY keys.pinned DESC, keys.created_at DESC, keys.id DESC",
+      KeySort::Frecency => " ORDER BY frecency DESC, keys.created_at DESC, keys.id DESC",
+    }
+  }
+}
+
+impl PageCursor {
+  fn after(sort: KeySort, item: &KeyItem) -> Self {
+    let text = match sort {
+      KeySort::Domain => item.domain.clone(),
+      KeySort::Username => item.username.clone(),
+      KeySort::CreatedAt | KeySort::PinnedFirst | KeySort::Frecency => String::new(),
+    };
+
+    PageCursor::After {
+      sort,
+      id: item.id,
+      created_at: item.created_at,
+      pinned: item.pinned,
+      text,
+    }
+  }
+
+  fn encode(&self) -> String {
+    match self {
+      PageCursor::After {
+        sort,
+        id,
+        created_at,
+        pinned,
+        text,
+      } => format!(
+        "{}:{}:{}:{}:{}",
+        sort.cursor_code(),
+        id,
+        created_at,
+        u8::from(*pinned),
+        text
+      ),
+      PageCursor::Offset(offset) => format!("o:{}", offset),
+    }
+  }
+
+  fn decode(cursor: &str) -> Result<Self, Error> {
+    let invalid = || Error::InvalidCursor(String::from(cursor));
+
+    if let Some(offset) = cursor.strip_prefix("o:") {
+      let offset: i64 = offset.parse().map_err(|_| invalid())?;
+      return Ok(PageCursor::Offset(offset));
+    }
+
+    let mut parts = cursor.splitn(5, ':');
+    let sort = match parts.next() {
+      Some("c") => KeySort::CreatedAt,
+      Some("d") => KeySort::Domain,
+      Some("u") => KeySort::Username,
+      Some("p") => KeySort::PinnedFirst,
+      _ => return Err(invalid()),
+    };
+    let id: i64 = parts
+      .next()
+      .and_then(|v| v.parse().ok())
+      .ok_or_else(invalid)?;
+    let created_at: i64 = parts
+      .next()
+      .and_then(|v| v.parse().ok())
+      .ok_or_else(invalid)?;
+    let pinned = match parts.next() {
+      Some("0") => false,
+      Some("1") => true,
+      _ => return Err(invalid()),
+    };
+    let text = parts.next().ok_or_else(invalid)?.to_owned();
+
+    Ok(PageCursor::After {
+      sort,
+      id,
+      created_at,
+      pinned,
+      text,
+    })
+  }
+}
+
+impl ProfileDB {
+  pub async fn get_key_by_id(&self, key_id: i64) -> Result<Option<KeyItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let row = query_as::<Sqlite, KeyItem>(&format!(
+      "{KEY_ITEM_SELECT}
       FROM keys
-      LEFT JOIN vw_tag_list ON vw_tag_list.key_id = keys.id
-      WHERE keys.id = ? LIMIT 1;",
-    )
+      WHERE keys.id = ? LIMIT 1;"
+    ))
     .bind(key_id)
     .fetch_one(&mut *conn)
     .await;
@@ -99,103 +437,472 @@ impl ProfileDB {
     }
   }
 
+  /// Returns all keys except the ones in trash.
   pub async fn get_keys(&self, pinned_only: bool) -> Result<Vec<KeyItem>, Error> {
-    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
-      "SELECT
-        keys.id,
-        keys.pinned,
-        keys.target_size,
-        keys.revision,
-        keys.charset,
-        keys.domain,
-        keys.username,
-        keys.notes,
-        keys.created_at,
-        keys.custom_icon,
-        keys.version,
-        ifnull(vw_tag_list.tags, json_array()) as tags
-      FROM keys
-      LEFT JOIN vw_tag_list ON vw_tag_list.key_id = keys.id",
-    );
+    self.fetch_keys(pinned_only, false).await
+  }
+
+  /// Returns all keys including the ones in trash.
+  pub(in crate::profile) async fn get_all_keys(&self) -> Result<Vec<KeyItem>, Error> {
+    self.fetch_keys(false, true).await
+  }
+
+  async fn fetch_keys(&self, pinned_only: bool, with_trash: bool) -> Result<Vec<KeyItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    Self::fetch_keys_with(&mut conn, pinned_only, with_trash).await
+  }
+
+  /// Same as [ProfileDB::fetch_keys], reads the keys with the given connection or transaction.
+  async fn fetch_keys_with(
+    conn: &mut SqliteConnection,
+    pinned_only: bool,
+    with_trash: bool,
+  ) -> Result<Vec<KeyItem>, Error> {
+    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(KEY_ITEM_SELECT);
+    query_builder.push(" FROM keys WHERE TRUE");
+
+    if !with_trash {
+      query_builder.push(" AND keys.deleted_at IS NULL");
+    }
 
     if pinned_only {
       query_builder
-        .push(" WHERE keys.pinned = ")
+        .push(" AND keys.pinned = ")
         .push_bind(pinned_only);
     }
 
     let query = query_builder.build_query_as::<KeyItem>();
-    let mut conn = self.pool.acquire().await?;
     let result: Vec<KeyItem> = query.fetch_all(&mut *conn).await?;
 
     Ok(result)
   }
 
   pub async fn search_keys(&self, search_query: SearchQuery) -> Result<Vec<KeyItem>, Error> {
-    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
-      "SELECT
-        keys.id,
-        keys.pinned,
-        keys.target_size,
-        keys.revision,
-        keys.charset,
-        keys.domain,
-        keys.username,
-        keys.notes,
-        keys.created_at,
-        keys.custom_icon,
-        keys.version,
-        ifnull(vw_tag_list.tags, json_array()) as tags
-      FROM keys
-      LEFT JOIN vw_tag_list on keys.id = vw_tag_list.key_id ",
+    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(KEY_ITEM_SELECT);
+    Self::push_key_source(&mut query_builder, Some(&search_query), false, None);
+
+    if search_query.to_fts_query().is_some() {
+      query_builder.push(" ORDER BY rank");
+    }
+
+    let mut conn = self.pool.acquire().await?;
+    let query = query_builder.build_query_as::<KeyItem>();
+    let result: Vec<KeyItem> = query.fetch_all(&mut *conn).await?;
+
+    if result.is_empty() {
+      let key_ids = self.fuzzy_search(&search_query).await?;
+      return self.get_keys_by_ids(&key_ids).await;
+    }
+
+    Ok(result)
+  }
+
+  /// Returns the keys in the same order as the given ids. Missing keys are skipped.
+  async fn get_keys_by_ids(&self, key_ids: &[i64]) -> Result<Vec<KeyItem>, Error> {
+    if key_ids.is_empty() {
+      return Ok(Vec::new());
+    }
+
+    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(KEY_ITEM_SELECT);
+    query_builder.push(" FROM keys WHERE keys.deleted_at IS NULL AND keys.id IN (");
+    let mut separated = query_builder.separated(", ");
+    for key_id in key_ids {
+      separated.push_bind(*key_id);
+    }
+    separated.push_unseparated(")");
+
+    let mut conn = self.pool.acquire().await?;
+    let mut result: Vec<KeyItem> = query_builder
+      .build_query_as::<KeyItem>()
+      .fetch_all(&mut *conn)
+      .await?;
+
+    result.sort_by_key(|item| key_ids.iter().position(|id| *id == item.id));
+
+    Ok(result)
+  }
+
+  /// Returns a single page of keys. Use [KeyPage::next_cursor] as [KeyListOptions::cursor] to
+  /// fetch the following page.
+  pub async fn get_keys_page(&self, options: KeyListOptions) -> Result<KeyPage, Error> {
+    self.fetch_key_page(None, options).await
+  }
+
+  /// Paginated version of [ProfileDB::search_keys] with match highlights. Typo tolerant search
+  /// results are returned as a single page, [KeyPage::total] still counts all matches beyond the
+  /// page limit.
+  pub async fn search_keys_page(
+    &self,
+    search_query: SearchQuery,
+    options: KeyListOptions,
+  ) -> Result<KeyPage<SearchResult>, Error> {
+    let first_page = options.cursor.is_none();
+    let limit = options.limit.clamp(1, MAX_PAGE_LIMIT) as usize;
+    let pinned_only = options.pinned_only;
+    let collection_id = options.collection_id;
+    let page = self.fetch_key_page(Some(&search_query), options).await?;
+
+    if page.total > 0 || !first_page {
+      return Ok(KeyPage {
+        items: self.search_highlights(&search_query, page.items).await?,
+        next_cursor: page.next_cursor,
+        total: page.total,
+      });
+    }
+
+    let key_ids = self.fuzzy_search(&search_query).await?;
+    let collection_ids = match collection_id {
+      Some(collection_id) => {
+        let mut conn = self.pool.acquire().await?;
+        Some(Self::get_subtree_ids(&mut conn, collection_id).await?)
+      }
+      None => None,
+    };
+    let mut items: Vec<KeyItem> = self
+      .get_keys_by_ids(&key_ids)
+      .await?
+      .into_iter()
+      .filter(|item| item.pinned || !pinned_only)
+      .filter(|item| match (&collection_ids, item.collection_id) {
+        (None, _) => true,
+        (Some(ids), Some(id)) => ids.contains(&id),
+        (Some(_), None) => false,
+      })
+      .collect();
+    let total = items.len() as i64;
+    items.truncate(limit);
+
+    Ok(KeyPage {
+      total,
+      items: self.search_highlights(&search_query, items).await?,
+      next_cursor: None,
+    })
+  }
+
+  pub async fn count_keys(&self, pinned_only: bool) -> Result<i64, Error> {
+    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT COUNT(*)");
+    Self::push_key_source(&mut query_builder, None, pinned_only, None);
+
+    let mut conn = self.pool.acquire().await?;
+    let total: i64 = query_builder
+      .build_query_scalar()
+      .fetch_one(&mut *conn)
+      .await?;
+
+    Ok(total)
+  }
+
+  async fn fetch_key_page(
+    &self,
+    search_query: Option<&SearchQuery>,
+    options: KeyListOptions,
+  ) -> Result<KeyPage, Error> {
+    let limit = options.limit.clamp(1, MAX_PAGE_LIMIT);
+    let cursor = options
+      .cursor
+      .as_deref()
+      .map(PageCursor::decode)
+      .transpose()?;
+    let has_rank = search_query.is_some_and(|q| q.to_fts_query().is_some());
+    let sort = match (has_rank, options.sort) {
+      (true, None) => None,
+      (_, sort) => Some(sort.unwrap_or_default()),
+    };
+
+    let mut count_builder: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT COUNT(*)");
+    Self::push_key_source(
+      &mut count_builder,
+      search_query,
+      options.pinned_only,
+      options.collection_id,
+    );
+
+    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(KEY_ITEM_SELECT);
+
+    if sort == Some(KeySort::Frecency) {
+      // Use count decays with the weeks passed since the last use.
+      query_builder.push(", keys.use_count / (1.0 + (");
+      query_builder.push_bind(get_unix_timestamp()?);
+      query_builder.push(format!(
+        " - ifnull(keys.last_used_at, keys.created_at)) / {}.0) as frecency",
+        FRECENCY_DECAY_SECS
+      ));
+    }
+
+    Self::push_key_source(
+      &mut query_builder,
+      search_query,
+      options.pinned_only,
+      options.collection_id,
     );
 
-    if let Some(query) = search_query.to_fts_query() {
+    let offset = match (sort, cursor) {
+      (None, Some(PageCursor::Offset(offset))) => {
+        query_builder.push(" ORDER BY rank, keys.id LIMIT ");
+        query_builder.push_bind(i64::from(limit) + 1);
+        query_builder.push(" OFFSET ");
+        query_builder.push_bind(offset);
+        offset
+      }
+      (None, None) => {
+        query_builder.push(" ORDER BY rank, keys.id LIMIT ");
+        query_builder.push_bind(i64::from(limit) + 1);
+        0
+      }
+      (Some(KeySort::Frecency), cursor) => {
+        let offset = match cursor {
+          Some(PageCursor::Offset(offset)) => offset,
+          Some(cursor) => return Err(Error::InvalidCursor(cursor.encode())),
+          None => 0,
+        };
+
+        query_builder.push(KeySort::Frecency.order_by());
+        query_builder.push(" LIMIT ");
+        query_builder.push_bind(i64::from(limit) + 1);
+        query_builder.push(" OFFSET ");
+        query_builder.push_bind(offset);
+        offset
+      }
+      (Some(sort), cursor) => {
+        match cursor {
+          Some(PageCursor::After {
+            sort: cursor_sort,
+            id,
+            created_at,
+            pinned,
+            text,
+          }) if cursor_sort == sort => {
+            Self::push_cursor_filter(&mut query_builder, sort, id, created_at, pinned, text);
+          }
+          Some(cursor) => return Err(Error::InvalidCursor(cursor.encode())),
+          None => {}
+        }
+
+        query_builder.push(sort.order_by());
+        query_builder.push(" LIMIT ");
+        query_builder.push_bind(i64::from(limit) + 1);
+        0
+      }
+      (None, Some(cursor)) => return Err(Error::InvalidCursor(cursor.encode())),
+    };
+
+    let mut conn = self.pool.acquire().await?;
+    let total: i64 = count_builder
+      .build_query_scalar()
+      .fetch_one(&mut *conn)
+      .await?;
+    let mut items: Vec<KeyItem> = query_builder
+      .build_query_as::<KeyItem>()
+      .fetch_all(&mut *conn)
+      .await?;
+
+    let next_cursor = if items.len() > limit as usize {
+      items.truncate(limit as usize);
+
+      match (sort, items.last()) {
+        (None | Some(KeySort::Frecency), _) => {
+          Some(PageCursor::Offset(offset + i64::from(limit)).encode())
+        }
+        (Some(sort), Some(last)) => Some(PageCursor::after(sort, last).encode()),
+        _ => None,
+      }
+    } else {
+      None
+    };
+
+    Ok(KeyPage {
+      items,
+      next_cursor,
+      total,
+    })
+  }
+
+  /// Pushes the `FROM` and `WHERE` clauses shared by the page and count queries.
+  fn push_key_source(
+    query_builder: &mut QueryBuilder<Sqlite>,
+    search_query: Option<&SearchQuery>,
+    pinned_only: bool,
+    collection_id: Option<i64>,
+  ) {
+    query_builder.push(" FROM keys");
+
+    if let Some(fts_query) = search_query.and_then(|q| q.to_fts_query()) {
       query_builder.push(
-        "JOIN (SELECT DISTINCT ROWID as s_key, rank
+        " JOIN (SELECT ROWID as s_key, rank
            FROM search_index
            WHERE search_index MATCH ",
       );
-      query_builder.push_bind(query);
-      query_builder.push(" ) ON s_key = keys.id ORDER BY rank;");
+      query_builder.push_bind(fts_query);
+      query_builder.push(" ) ON s_key = keys.id");
+    }
+
+    if pinned_only {
+      query_builder.push(" WHERE keys.deleted_at IS NULL AND keys.pinned = TRUE");
+    } else {
+      query_builder.push(" WHERE keys.deleted_at IS NULL");
+    }
+
+    if let Some(collection_id) = collection_id {
+      query_builder.push(
+        " AND keys.collection_id IN (
+          WITH RECURSIVE subtree(id) AS (SELECT ",
+      );
+      query_builder.push_bind(collection_id);
+      query_builder.push(
+        " UNION
+            SELECT collections.id FROM collections INNER JOIN subtree ON collections.parent_id = subtree.id
+          )
+          SELECT id FROM subtree
+        )",
+      );
+    }
+
+    if let Some(search_query) = search_query {
+      search_query.push_sql_filter(query_builder);
+    }
+  }
+
+  fn push_cursor_filter(
+    query_builder: &mut QueryBuilder<Sqlite>,
+    sort: KeySort,
+    id: i64,
+    created_at: i64,
+    pinned: bool,
+    text: String,
+  ) {
+    match sort {
+      KeySort::CreatedAt => {
+        query_builder.push(" AND (keys.created_at, keys.id) < (");
+        query_builder.push_bind(created_at);
+      }
+      KeySort::Domain => {
+        query_builder.push(" AND (keys.domain, keys.id) > (");
+        query_builder.push_bind(text);
+      }
+      KeySort::Username => {
+        query_builder.push(" AND (keys.username, keys.id) > (");
+        query_builder.push_bind(text);
+      }
+      KeySort::PinnedFirst => {
+        query_builder.push(" AND (keys.pinned, keys.created_at, keys.id) < (");
+        query_builder.push_bind(pinned);
+        query_builder.push(", ");
+        query_builder.push_bind(created_at);
+      }
+      KeySort::Frecency => unreachable!("Frecency pages use offset cursors."),
     }
 
+    query_builder.push(", ");
+    query_builder.push_bind(id);
+    query_builder.push(")");
+  }
+
+  /// Updates the usage statistics of the key. Call it whenever a password is generated from the
+  /// key.
+  pub async fn record_key_usage(&self, key_id: i64) -> Result<(), Error> {
+    let now: i64 = get_unix_timestamp()?;
     let mut conn = self.pool.acquire().await?;
-    let query = query_builder.build_query_as::<KeyItem>();
-    let result: Vec<KeyItem> = query.fetch_all(&mut *conn).await?;
+    let result = query!(
+      "UPDATE keys SET last_used_at = ?, use_count = use_count + 1 WHERE id = ?",
+      now,
+      key_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if result.rows_affected() == 0 {
+      return Err(Error::KeyNotFound { key_id });
+    }
+
+    Ok(())
+  }
+
+  /// Returns the keys not used for the given number of days, least recently used keys first. Keys
+  /// that are never used are compared by their creation time.
+  pub async fn get_stale_keys(&self, unused_days: u32) -> Result<Vec<KeyItem>, Error> {
+    let threshold = get_unix_timestamp()? - i64::from(unused_days) * 24 * 60 * 60;
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as::<Sqlite, KeyItem>(&format!(
+      "{KEY_ITEM_SELECT}
+      FROM keys
+      WHERE keys.deleted_at IS NULL AND ifnull(keys.last_used_at, keys.created_at) < ?
+      ORDER BY ifnull(keys.last_used_at, keys.created_at) ASC, keys.id ASC"
+    ))
+    .bind(threshold)
+    .fetch_all(&mut *conn)
+    .await?;
 
     Ok(result)
   }
 
+  /// Moves the key to trash. Keys in trash are excluded from listing and search, and they can be
+  /// restored with [ProfileDB::restore_key] until they are purged.
   pub async fn delete_key(&self, key_id: i64) -> Result<bool, Error> {
+    let now: i64 = get_unix_timestamp()?;
     let mut conn = self.pool.acquire().await?;
-    let mut transaction = conn.begin().await?;
+    let result = query!(
+      "UPDATE keys SET deleted_at = ? WHERE keys.id = ? AND keys.deleted_at IS NULL",
+      now,
+      key_id
+    )
+    .execute(&mut *conn)
+    .await?;
 
-    query!("DELETE FROM tags WHERE tags.key_id = ?", key_id)
-      .execute(&mut *transaction)
-      .await?;
+    if result.rows_affected() == 0 {
+      return Ok(false);
+    }
 
-    let key_result = query!("DELETE FROM keys WHERE keys.id = ?", key_id)
-      .execute(&mut *transaction)
-      .await?;
+    self.notify(ProfileEvent::KeyDeleted { key_id });
 
-    Self::sync_search_index(&mut transaction, SearchIndexOp::Delete(key_id)).await?;
+    Ok(true)
+  }
+
+  pub async fn insert_key(&self, item: KeyData) -> Result<i64, Error> {
+    self.insert_key_with(item, KeyWriteOptions::default()).await
+  }
 
+  pub async fn insert_key_with(
+    &self,
+    item: KeyData,
+    options: KeyWriteOptions,
+  ) -> Result<i64, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+    let key_id = Self::insert_key_rows(&mut transaction, item, options).await?;
     transaction.commit().await?;
+    self.notify(ProfileEvent::KeyCreated { key_id });
 
-    Ok(key_result.rows_affected() > 0)
+    Ok(key_id)
   }
 
-  pub async fn insert_key(&self, item: KeyData) -> Result<i64, Error> {
-    item.validate()?;
+  /// Validates and inserts the key with its tags, URLs, fields, secret and search index rows.
+  pub(in crate::profile) async fn insert_key_rows(
+    conn: &mut SqliteConnection,
+    item: KeyData,
+    options: KeyWriteOptions,
+  ) -> Result<i64, Error> {
+    item.validate_all()?;
+
+    if item.kind == KeyKind::Stored && item.secret.is_none() {
+      return Err(KeyData::missing_secret().into());
+    }
 
     let now: i64 = get_unix_timestamp()?;
-    let mut conn = self.pool.acquire().await?;
-    let mut transaction = conn.begin().await?;
+
+    if let Some(key_id) =
+      Self::find_conflicting_key(&mut *conn, None, &item, options.uniqueness).await?
+    {
+      return Err(Error::DuplicateKey {
+        key_id,
+        check: options.uniqueness,
+      });
+    }
+
+    let (account_domain, account_username) = account_params(&item.domain, &item.username);
     let key_insert = query!(
       "INSERT INTO keys
-        (pinned, target_size, revision, charset, domain, username, notes, created_at, custom_icon, version) VALUES
-        (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        (pinned, target_size, revision, charset, domain, username, notes, created_at, custom_icon, version, exclude_lookalikes, kind, account_domain, account_username) VALUES
+        (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
       false,
       item.target_size,
       item.revision,
@@ -205,12 +912,17 @@ impl ProfileDB {
       item.notes,
       now,
       item.custom_icon,
-      item.version
+      item.version,
+      item.exclude_lookalikes,
+      item.kind,
+      account_domain,
+      account_username
     )
-      .execute(&mut *transaction)
+      .execute(&mut *conn)
       .await?;
 
     let key_id = key_insert.last_insert_rowid();
+    Self::insert_key_rotation(&mut *conn, key_id, item.revision, now).await?;
 
     if !item.tags.is_empty() {
       let mut query_builder: QueryBuilder<Sqlite> =
@@ -222,36 +934,95 @@ impl ProfileDB {
       });
 
       let query = query_builder.build();
-      query.execute(&mut *transaction).await?;
+      query.execute(&mut *conn).await?;
     }
 
+    Self::replace_key_urls(&mut *conn, key_id, &item.urls).await?;
+    Self::replace_key_fields(&mut *conn, key_id, &item.fields).await?;
+    Self::replace_key_secret(&mut *conn, key_id, item.kind, item.secret.as_deref()).await?;
+
     Self::sync_search_index(
-      &mut transaction,
+      &mut *conn,
       SearchIndexOp::Create(SearchIndex {
         id: key_id,
         notes: item.notes,
         username: item.username,
         domain: item.domain,
         tags: item.tags.join(' '),
+        urls: url_index_text(&item.urls),
+        fields: field_index_text(&item.fields),
       }),
     )
     .await?;
 
-    transaction.commit().await?;
-
     Ok(key_id)
   }
 
   pub async fn update_key(&self, key_id: i64, item: KeyData) -> Result<(), Error> {
-    item.validate()?;
+    self
+      .update_key_with(key_id, item, KeyWriteOptions::default())
+      .await
+  }
 
+  pub async fn update_key_with(
+    &self,
+    key_id: i64,
+    item: KeyData,
+    options: KeyWriteOptions,
+  ) -> Result<(), Error> {
+    item.validate_all()?;
+
+    let now: i64 = get_unix_timestamp()?;
     let mut conn = self.pool.acquire().await?;
     let mut transaction = conn.begin().await?;
-    query!(
+    let previous = query!(
+      r#"SELECT revision, kind as "kind: KeyKind" FROM keys
+      WHERE keys.id = ? AND keys.deleted_at IS NULL"#,
+      key_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    let Some(previous) = previous else {
+      return Err(Error::KeyNotFound { key_id });
+    };
+
+    if item.kind == KeyKind::Stored && previous.kind != KeyKind::Stored && item.secret.is_none() {
+      return Err(KeyData::missing_secret().into());
+    }
+
+    let secret_replaced = match (previous.kind, item.kind, item.secret.as_deref()) {
+      (KeyKind::Stored, KeyKind::Derived, _) => true,
+      (KeyKind::Stored, KeyKind::Stored, Some(secret)) => {
+        Self::read_key_secret(&mut transaction, key_id)
+          .await?
+          .as_deref()
+          != Some(secret)
+      }
+      _ => false,
+    };
+
+    if let Some(conflict_id) =
+      Self::find_conflicting_key(&mut transaction, Some(key_id), &item, options.uniqueness).await?
+    {
+      return Err(Error::DuplicateKey {
+        key_id: conflict_id,
+        check: options.uniqueness,
+      });
+    }
+
+    Self::insert_key_snapshot(&mut transaction, key_id, now).await?;
+
+    if secret_replaced {
+      Self::insert_key_secret_snapshot(&mut transaction, key_id, now).await?;
+    }
+
+    let (account_domain, account_username) = account_params(&item.domain, &item.username);
+    let result = query!(
       "UPDATE keys SET
-        (target_size, revision, charset, domain, username, notes, custom_icon, version) =
-        (?, ?, ?, ?, ?, ?, ?, ?)
-      WHERE keys.id = ?;",
+        (target_size, revision, charset, domain, username, notes, custom_icon, version, exclude_lookalikes, kind, updated_at, account_domain, account_username) =
+        (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+      WHERE keys.id = ? AND keys.deleted_at IS NULL;",
       item.target_size,
       item.revision,
       item.charset,
@@ -260,11 +1031,24 @@ impl ProfileDB {
       item.notes,
       item.custom_icon,
       item.version,
+      item.exclude_lookalikes,
+      item.kind,
+      now,
+      account_domain,
+      account_username,
       key_id
     )
     .execute(&mut *transaction)
     .await?;
 
+    if result.rows_affected() == 0 {
+      return Err(Error::KeyNotFound { key_id });
+    }
+
+    if previous.revision != item.revision {
+      Self::insert_key_rotation(&mut transaction, key_id, item.revision, now).await?;
+    }
+
     let existing_tags = query!("SELECT name FROM tags WHERE key_id = ?", key_id)
       .fetch_all(&mut *transaction)
       .await?;
@@ -299,6 +1083,10 @@ impl ProfileDB {
       query.execute(&mut *transaction).await?;
     }
 
+    Self::replace_key_urls(&mut transaction, key_id, &item.urls).await?;
+    Self::replace_key_fields(&mut transaction, key_id, &item.fields).await?;
+    Self::replace_key_secret(&mut transaction, key_id, item.kind, item.secret.as_deref()).await?;
+
     Self::sync_search_index(
       &mut transaction,
       SearchIndexOp::Update(SearchIndex {
@@ -307,153 +1095,142 @@ impl ProfileDB {
         username: item.username,
         domain: item.domain,
         tags: item.tags.join(' '),
+        urls: url_index_text(&item.urls),
+        fields: field_index_text(&item.fields),
       }),
     )
     .await?;
     transaction.commit().await?;
+    self.notify(ProfileEvent::KeyUpdated { key_id });
 
     Ok(())
   }
 
   pub async fn update_pin_status(&self, key_id: i64, pin_status: bool) -> Result<(), Error> {
     let mut conn = self.pool.acquire().await?;
-    query!(
-      "UPDATE keys SET pinned = ? WHERE keys.id = ?;",
+    let result = query!(
+      "UPDATE keys SET pinned = ? WHERE keys.id = ? AND keys.deleted_at IS NULL;",
       pin_status,
       key_id
     )
     .execute(&mut *conn)
     .await?;
 
+    if result.rows_affected() == 0 {
+      return Err(Error::KeyNotFound { key_id });
+    }
+
+    self.notify(ProfileEvent::KeyPinned {
+      key_id,
+      pinned: pin_status,
+    });
+
     Ok(())
   }
 
+  /// Rewrites the search index entries of the key from its current values.
+  pub(in crate::profile) async fn reindex_key(
+    conn: &mut SqliteConnection,
+    key_id: i64,
+  ) -> Result<(), Error> {
+    let row = query!(
+      r#"SELECT
+        keys.domain,
+        keys.username,
+        keys.notes,
+        ifnull((SELECT group_concat(tags.name, ' ') FROM tags WHERE tags.key_id = keys.id), '') as "tags!: String",
+        ifnull((SELECT group_concat(key_urls.url, ' ') FROM key_urls WHERE key_urls.key_id = keys.id), '') as "urls!: String",
+        ifnull((SELECT group_concat(key_fields.value, ' ') FROM key_fields WHERE key_fields.key_id = keys.id AND key_fields.field_type != 'hidden'), '') as "fields!: String"
+      FROM keys
+      WHERE keys.id = ?"#,
+      key_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Self::sync_search_index(
+      &mut *conn,
+      SearchIndexOp::Update(SearchIndex {
+        id: key_id,
+        notes: row.notes,
+        username: row.username,
+        domain: row.domain,
+        tags: row.tags,
+        urls: row.urls,
+        fields: row.fields,
+      }),
+    )
+    .await
+  }
+
   async fn sync_search_index(conn: &mut SqliteConnection, op: SearchIndexOp) -> Result<(), Error> {
     match op {
       SearchIndexOp::Delete(id) => {
         query!("DELETE FROM search_index WHERE ROWID = ?", id)
-          .execute(conn)
+          .execute(&mut *conn)
+          .await?;
+        query!("DELETE FROM search_trigram WHERE ROWID = ?", id)
+          .execute(&mut *conn)
           .await?;
       }
       SearchIndexOp::Update(update_values) => {
         query!(
-          "UPDATE search_index SET (domain, username, tags, notes) = (?,?,?,?) WHERE ROWID = ?",
+          "UPDATE search_index SET (domain, username, tags, notes, urls, fields) = (?,?,?,?,?,?) WHERE ROWID = ?",
           update_values.domain,
           update_values.username,
           update_values.tags,
           update_values.notes,
+          update_values.urls,
+          update_values.fields,
           update_values.id
         )
-        .execute(conn)
+        .execute(&mut *conn)
+        .await?;
+
+        let folded = update_values.folded();
+        query!(
+          "UPDATE search_trigram SET (domain, username, tags, notes, urls, fields) = (?,?,?,?,?,?) WHERE ROWID = ?",
+          folded.domain,
+          folded.username,
+          folded.tags,
+          folded.notes,
+          folded.urls,
+          folded.fields,
+          folded.id
+        )
+        .execute(&mut *conn)
         .await?;
       }
       SearchIndexOp::Create(create_values) => {
         query!(
-          "INSERT INTO search_index (ROWID, domain, username, tags, notes) VALUES (?,?,?,?,?);",
+          "INSERT INTO search_index (ROWID, domain, username, tags, notes, urls, fields) VALUES (?,?,?,?,?,?,?);",
           create_values.id,
           create_values.domain,
           create_values.username,
           create_values.tags,
           create_values.notes,
+          create_values.urls,
+          create_values.fields,
         )
-        .execute(conn)
+        .execute(&mut *conn)
         .await?;
-      }
-    }
-
-    Ok(())
-  }
-}
-
-impl SearchQuery {
-  pub fn new(query: &str) -> Self {
-    let mut username_tokens: Vec<Box<str>> = Vec::new();
-    let mut domain_tokens: Vec<Box<str>> = Vec::new();
-    let mut tag_tokens: Vec<Box<str>> = Vec::new();
-    let mut fts_tokens: Vec<Box<str>> = Vec::new();
-
-    for token in query.split_whitespace() {
-      if let Some(tag_token) = token.strip_prefix("tag:") {
-        tag_tokens.push(Box::from(tag_token));
-      } else if let Some(domain_token) = token.strip_prefix("domain:") {
-        domain_tokens.push(Box::from(domain_token));
-      } else if let Some(usr_token) = token.strip_prefix("username:") {
-        username_tokens.push(Box::from(usr_token));
-      } else {
-        fts_tokens.push(Box::from(token));
-      }
-    }
-
-    Self {
-      fts_tokens,
-      username_tokens,
-      domain_tokens,
-      tag_tokens,
-    }
-  }
-
-  pub fn to_fts_query(&self) -> Option<String> {
-    let mut queries: Vec<String> = Vec::new();
-
-    if !self.fts_tokens.is_empty() {
-      queries.push(format!(
-        "({{domain username tags notes}}: {})",
-        Self::join_tokens(&self.fts_tokens, " OR ")
-      ));
-    }
-
-    if !self.username_tokens.is_empty() {
-      queries.push(format!(
-        "({{username}}: {})",
-        Self::join_tokens(&self.username_tokens, " OR ")
-      ));
-    }
 
-    if !self.domain_tokens.is_empty() {
-      queries.push(format!(
-        "({{domain}}: {})",
-        Self::join_tokens(&self.domain_tokens, " OR ")
-      ));
-    }
-
-    if !self.tag_tokens.is_empty() {
-      queries.push(format!(
-        "({{tags}}: {})",
-        Self::join_tokens(&self.tag_tokens, " OR ")
-      ));
-    }
-
-    if queries.is_empty() {
-      None
-    } else {
-      Some(queries.join(" AND "))
-    }
-  }
-
-  fn join_tokens<T>(tokens: &[T], separator: &str) -> String
-  where
-    T: AsRef<str>,
-  {
-    let mut result = String::new();
-
-    for (index, token) in tokens.iter().enumerate() {
-      let normalized = format!("\"{}\"", token.as_ref().replace('"', "\"\""));
-      result.push_str(&normalized);
-
-      if index != tokens.len() - 1 {
-        result.push_str(separator);
+        let folded = create_values.folded();
+        query!(
+          "INSERT INTO search_trigram (ROWID, domain, username, tags, notes, urls, fields) VALUES (?,?,?,?,?,?,?);",
+          folded.id,
+          folded.domain,
+          folded.username,
+          folded.tags,
+          folded.notes,
+          folded.urls,
+          folded.fields,
+        )
+        .execute(&mut *conn)
+        .await?;
       }
     }
 
-    result
-  }
-}
-
-impl<T> From<T> for SearchQuery
-where
-  T: AsRef<str>,
-{
-  fn from(value: T) -> Self {
-    Self::new(value.as_ref())
+    Ok(())
   }
 }
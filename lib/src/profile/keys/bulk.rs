@@ -0,0 +1,195 @@
+use crate::charset::{validate_charset_with, CharsetOptions};
+use crate::errors::Error;
+use crate::profile::events::ProfileEvent;
+use crate::profile::utils::tag_list::TagList;
+use crate::profile::utils::timestamp::get_unix_timestamp;
+use crate::profile::ProfileDB;
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, FromRow, QueryBuilder, Sqlite};
+use validator::ValidationErrors;
+
+/// Operation applied to a set of keys at once.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum KeyBulkAction {
+  AddTags(TagList),
+  RemoveTags(TagList),
+  Pin,
+  Unpin,
+  /// Moves the keys to trash.
+  Delete,
+  /// Increments the revision of the keys and records the rotation.
+  BumpRevision,
+  SetCharset(String),
+}
+
+#[derive(FromRow)]
+struct BulkTarget {
+  id: i64,
+  exclude_lookalikes: bool,
+}
+
+impl ProfileDB {
+  /// Applies the action to the given keys in a single transaction. Unknown ids and keys in trash
+  /// are skipped. Returns the number of updated keys.
+  pub async fn bulk_update_keys(
+    &self,
+    key_ids: &[i64],
+    action: KeyBulkAction,
+  ) -> Result<u64, Error> {
+    if key_ids.is_empty() {
+      return Ok(0);
+    }
+
+    let now: i64 = get_unix_timestamp()?;
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+
+    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+      "SELECT keys.id, keys.exclude_lookalikes FROM keys WHERE keys.deleted_at IS NULL AND keys.id IN ",
+    );
+    push_id_list(&mut query_builder, key_ids.iter().copied());
+    let targets: Vec<BulkTarget> = query_builder
+      .build_query_as::<BulkTarget>()
+      .fetch_all(&mut *transaction)
+      .await?;
+
+    if targets.is_empty() {
+      return Ok(0);
+    }
+
+    let target_ids: Vec<i64> = targets.iter().map(|target| target.id).collect();
+    let event: fn(i64) -> ProfileEvent = match action {
+      KeyBulkAction::Pin => |key_id| ProfileEvent::KeyPinned {
+        key_id,
+        pinned: true,
+      },
+      KeyBulkAction::Unpin => |key_id| ProfileEvent::KeyPinned {
+        key_id,
+        pinned: false,
+      },
+      KeyBulkAction::Delete => |key_id| ProfileEvent::KeyDeleted { key_id },
+      _ => |key_id| ProfileEvent::KeyUpdated { key_id },
+    };
+
+    match action {
+      KeyBulkAction::AddTags(tags) => {
+        if tags.is_empty() {
+          return Ok(0);
+        }
+
+        let mut query_builder: QueryBuilder<Sqlite> =
+          QueryBuilder::new("INSERT OR IGNORE INTO tags (key_id, name) ");
+        let values = target_ids
+          .iter()
+          .flat_map(|key_id| tags.iter().map(move |tag| (*key_id, tag)));
+
+        query_builder.push_values(values, |mut b, (key_id, tag)| {
+          b.push_bind(key_id);
+          b.push_bind(tag.as_ref());
+        });
+        query_builder.build().execute(&mut *transaction).await?;
+
+        for key_id in target_ids.iter() {
+          Self::reindex_key(&mut transaction, *key_id).await?;
+        }
+      }
+      KeyBulkAction::RemoveTags(tags) => {
+        if tags.is_empty() {
+          return Ok(0);
+        }
+
+        let mut query_builder: QueryBuilder<Sqlite> =
+          QueryBuilder::new("DELETE FROM tags WHERE tags.name IN (");
+        let mut separated = query_builder.separated(", ");
+        for tag in tags.iter() {
+          separated.push_bind(tag.as_ref());
+        }
+        separated.push_unseparated(") AND tags.key_id IN ");
+        push_id_list(&mut query_builder, target_ids.iter().copied());
+        query_builder.build().execute(&mut *transaction).await?;
+
+        for key_id in target_ids.iter() {
+          Self::reindex_key(&mut transaction, *key_id).await?;
+        }
+      }
+      KeyBulkAction::Pin | KeyBulkAction::Unpin => {
+        let mut query_builder: QueryBuilder<Sqlite> =
+          QueryBuilder::new("UPDATE keys SET pinned = ");
+        query_builder.push_bind(matches!(action, KeyBulkAction::Pin));
+        query_builder.push(" WHERE keys.id IN ");
+        push_id_list(&mut query_builder, target_ids.iter().copied());
+        query_builder.build().execute(&mut *transaction).await?;
+      }
+      KeyBulkAction::Delete => {
+        let mut query_builder: QueryBuilder<Sqlite> =
+          QueryBuilder::new("UPDATE keys SET deleted_at = ");
+        query_builder.push_bind(now);
+        query_builder.push(" WHERE keys.id IN ");
+        push_id_list(&mut query_builder, target_ids.iter().copied());
+        query_builder.build().execute(&mut *transaction).await?;
+      }
+      KeyBulkAction::BumpRevision => {
+        for key_id in target_ids.iter() {
+          Self::insert_key_snapshot(&mut transaction, *key_id, now).await?;
+        }
+
+        let mut query_builder: QueryBuilder<Sqlite> =
+          QueryBuilder::new("UPDATE keys SET revision = revision + 1, updated_at = ");
+        query_builder.push_bind(now);
+        query_builder.push(" WHERE keys.id IN ");
+        push_id_list(&mut query_builder, target_ids.iter().copied());
+        query_builder.build().execute(&mut *transaction).await?;
+
+        let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+          "INSERT INTO key_rotations (key_id, revision, rotated_at) SELECT keys.id, keys.revision, ",
+        );
+        query_builder.push_bind(now);
+        query_builder.push(" FROM keys WHERE keys.id IN ");
+        push_id_list(&mut query_builder, target_ids.iter().copied());
+        query_builder.build().execute(&mut *transaction).await?;
+      }
+      KeyBulkAction::SetCharset(charset) => {
+        let options = CharsetOptions {
+          exclude_lookalikes: targets.iter().any(|target| target.exclude_lookalikes),
+        };
+
+        if let Err(err) = validate_charset_with(&charset, options) {
+          let mut errors = ValidationErrors::new();
+          errors.add("charset", err);
+          return Err(errors.into());
+        }
+
+        for key_id in target_ids.iter() {
+          Self::insert_key_snapshot(&mut transaction, *key_id, now).await?;
+        }
+
+        let mut query_builder: QueryBuilder<Sqlite> =
+          QueryBuilder::new("UPDATE keys SET charset = ");
+        query_builder.push_bind(&charset);
+        query_builder.push(", updated_at = ");
+        query_builder.push_bind(now);
+        query_builder.push(" WHERE keys.id IN ");
+        push_id_list(&mut query_builder, target_ids.iter().copied());
+        query_builder.build().execute(&mut *transaction).await?;
+      }
+    }
+
+    transaction.commit().await?;
+
+    for key_id in target_ids.iter() {
+      self.notify(event(*key_id));
+    }
+
+    Ok(target_ids.len() as u64)
+  }
+}
+
+fn push_id_list(query_builder: &mut QueryBuilder<Sqlite>, key_ids: impl Iterator<Item = i64>) {
+  query_builder.push("(");
+  let mut separated = query_builder.separated(", ");
+  for key_id in key_ids {
+    separated.push_bind(key_id);
+  }
+  separated.push_unseparated(")");
+}
@@ -0,0 +1,278 @@
+use super::{KeyData, KeyItem, KeyKind};
+use crate::errors::Error;
+use crate::profile::ProfileDB;
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, Connection, SqliteConnection};
+use std::collections::HashMap;
+
+/// Why the keys of a [DuplicateGroup] are reported together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+  /// Same domain, username, revision, charset, length, version and charset options. These keys
+  /// generate the same password.
+  SameDerivation,
+  /// Same domain and username after ignoring letter case and extra whitespace.
+  SameAccount,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+  pub reason: DuplicateReason,
+  /// Keys of the group, oldest first.
+  pub keys: Vec<KeyItem>,
+}
+
+/// Rejects the key when an existing key conflicts with it. Keys in trash are ignored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum UniquenessCheck {
+  #[default]
+  Off,
+  /// Rejects derived keys generating the same password with an existing key.
+  Derivation,
+  /// Rejects keys with the same normalized domain and username with an existing key.
+  Account,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyWriteOptions {
+  pub uniqueness: UniquenessCheck,
+}
+
+struct DuplicateCandidate {
+  id: i64,
+  target_size: i64,
+  revision: i64,
+  charset: String,
+  domain: String,
+  username: String,
+  version: String,
+  exclude_lookalikes: bool,
+  kind: KeyKind,
+}
+
+/// Every parameter used by the password generator.
+#[derive(Hash, PartialEq, Eq)]
+struct DerivationParams<'a> {
+  domain: &'a str,
+  username: &'a str,
+  revision: i64,
+  charset: &'a str,
+  target_size: i64,
+  version: &'a str,
+  exclude_lookalikes: bool,
+}
+
+macro_rules! derivation_params {
+  ($item:expr) => {
+    DerivationParams {
+      domain: &$item.domain,
+      username: &$item.username,
+      revision: $item.revision,
+      charset: &$item.charset,
+      target_size: $item.target_size,
+      version: &$item.version,
+      exclude_lookalikes: $item.exclude_lookalikes,
+    }
+  };
+}
+
+fn normalize(value: &str) -> String {
+  value
+    .split_whitespace()
+    .collect::<Vec<&str>>()
+    .join(" ")
+    .to_lowercase()
+}
+
+/// Normalized domain and username stored in the `account_domain` and `account_username` columns.
+pub(in crate::profile) fn account_params(domain: &str, username: &str) -> (String, String) {
+  (normalize(domain), normalize(username))
+}
+
+/// Groups the item indexes by the given value, groups with a single item are dropped.
+fn group_by<'a, T, K>(items: &'a [T], key_fn: impl Fn(&'a T) -> Option<K>) -> Vec<Vec<usize>>
+where
+  K: std::hash::Hash + Eq,
+{
+  let mut groups: Vec<Vec<usize>> = Vec::new();
+  let mut group_index: HashMap<K, usize> = HashMap::new();
+
+  for (idx, item) in items.iter().enumerate() {
+    let Some(key) = key_fn(item) else {
+      continue;
+    };
+
+    match group_index.get(&key) {
+      Some(group_idx) => groups[*group_idx].push(idx),
+      None => {
+        group_index.insert(key, groups.len());
+        groups.push(vec![idx]);
+      }
+    }
+  }
+
+  groups.retain(|group| group.len() > 1);
+  groups
+}
+
+impl ProfileDB {
+  /// Reports the keys generating the same password, followed by the keys with the same normalized
+  /// domain and username. Account groups already reported as the same derivation are skipped.
+  /// Keys in trash are ignored.
+  pub async fn find_duplicates(&self) -> Result<Vec<DuplicateGroup>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let candidates = Self::get_duplicate_candidates(&mut conn).await?;
+    drop(conn);
+
+    let derivation_groups = group_by(&candidates, |candidate| match candidate.kind {
+      KeyKind::Derived => Some(derivation_params!(candidate)),
+      KeyKind::Stored => None,
+    });
+    let account_groups = group_by(&candidates, |candidate| {
+      Some(account_params(&candidate.domain, &candidate.username))
+    });
+    let account_groups: Vec<Vec<usize>> = account_groups
+      .into_iter()
+      .filter(|group| !derivation_groups.contains(group))
+      .collect();
+
+    let groups = derivation_groups
+      .into_iter()
+      .map(|group| (DuplicateReason::SameDerivation, group))
+      .chain(
+        account_groups
+          .into_iter()
+          .map(|group| (DuplicateReason::SameAccount, group)),
+      );
+
+    let mut result = Vec::new();
+    for (reason, group) in groups {
+      let key_ids: Vec<i64> = group.iter().map(|idx| candidates[*idx].id).collect();
+      let keys = self.get_keys_by_ids(&key_ids).await?;
+      result.push(DuplicateGroup { reason, keys });
+    }
+
+    Ok(result)
+  }
+
+  /// Returns the id of an existing key conflicting with the item, excluding the key itself.
+  pub(super) async fn find_conflicting_key(
+    conn: &mut SqliteConnection,
+    key_id: Option<i64>,
+    item: &KeyData,
+    check: UniquenessCheck,
+  ) -> Result<Option<i64>, Error> {
+    let conflict_id = match check {
+      UniquenessCheck::Off => None,
+      UniquenessCheck::Derivation if item.kind == KeyKind::Stored => None,
+      UniquenessCheck::Derivation => {
+        // Domain and username columns are case insensitive, password parameters are compared
+        // exactly.
+        query!(
+          r#"SELECT id as "id!" FROM keys
+          WHERE keys.deleted_at IS NULL
+            AND keys.id IS NOT ?
+            AND keys.kind = 'derived'
+            AND keys.domain = ? COLLATE BINARY
+            AND keys.username = ? COLLATE BINARY
+            AND keys.revision = ?
+            AND keys.charset = ?
+            AND keys.target_size = ?
+            AND keys.version = ?
+            AND keys.exclude_lookalikes = ?
+          ORDER BY keys.id
+          LIMIT 1"#,
+          key_id,
+          item.domain,
+          item.username,
+          item.revision,
+          item.charset,
+          item.target_size,
+          item.version,
+          item.exclude_lookalikes
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(|row| row.id)
+      }
+      UniquenessCheck::Account => {
+        let (domain, username) = account_params(&item.domain, &item.username);
+
+        query!(
+          r#"SELECT id as "id!" FROM keys
+          WHERE keys.deleted_at IS NULL
+            AND keys.id IS NOT ?
+            AND keys.account_domain = ?
+            AND keys.account_username = ?
+          ORDER BY keys.id
+          LIMIT 1"#,
+          key_id,
+          domain,
+          username
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(|row| row.id)
+      }
+    };
+
+    Ok(conflict_id)
+  }
+
+  /// Writes the normalized account values of the keys created before the account columns.
+  pub(in crate::profile) async fn backfill_account_params(&self) -> Result<(), Error> {
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+    let rows =
+      query!(r#"SELECT id as "id!", domain, username FROM keys WHERE keys.account_domain IS NULL"#)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+    for row in rows {
+      let (domain, username) = account_params(&row.domain, &row.username);
+
+      query!(
+        "UPDATE keys SET account_domain = ?, account_username = ? WHERE keys.id = ?",
+        domain,
+        username,
+        row.id
+      )
+      .execute(&mut *transaction)
+      .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+  }
+
+  /// Password parameters of the keys outside the trash, oldest first.
+  async fn get_duplicate_candidates(
+    conn: &mut SqliteConnection,
+  ) -> Result<Vec<DuplicateCandidate>, Error> {
+    let candidates = query_as!(
+      DuplicateCandidate,
+      r#"SELECT
+        id as "id!",
+        target_size,
+        revision,
+        charset,
+        domain,
+        username,
+        version,
+        exclude_lookalikes as "exclude_lookalikes: bool",
+        kind as "kind: KeyKind"
+      FROM keys
+      WHERE keys.deleted_at IS NULL
+      ORDER BY keys.id"#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(candidates)
+  }
+}
@@ -0,0 +1,134 @@
+use crate::errors::Error;
+use crate::profile::search_query::parse_date;
+use crate::profile::utils::domain::parse_url;
+use crate::profile::ProfileDB;
+use serde::{Deserialize, Serialize};
+use sqlx::{query, QueryBuilder, Sqlite, SqliteConnection};
+use std::collections::HashSet;
+use validator::ValidationError;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyFieldType {
+  #[default]
+  Text,
+  Url,
+  Email,
+  Number,
+  /// `YYYY-MM-DD` formatted date.
+  Date,
+  /// Text excluded from the search index, such as security question answers.
+  Hidden,
+}
+
+impl KeyFieldType {
+  /// Value stored in the `key_fields.field_type` column.
+  fn as_str(&self) -> &'static str {
+    match self {
+      KeyFieldType::Text => "text",
+      KeyFieldType::Url => "url",
+      KeyFieldType::Email => "email",
+      KeyFieldType::Number => "number",
+      KeyFieldType::Date => "date",
+      KeyFieldType::Hidden => "hidden",
+    }
+  }
+
+  fn is_valid(&self, value: &str) -> bool {
+    match self {
+      KeyFieldType::Text | KeyFieldType::Hidden => true,
+      KeyFieldType::Url => parse_url(value).is_some(),
+      KeyFieldType::Email => match value.split_once('@') {
+        Some((local, domain)) => {
+          !local.is_empty()
+            && domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && !domain.contains('@')
+            && !value.contains(char::is_whitespace)
+        }
+        None => false,
+      },
+      KeyFieldType::Number => value.parse::<f64>().is_ok_and(f64::is_finite),
+      KeyFieldType::Date => parse_date(value).is_some(),
+    }
+  }
+}
+
+/// User defined value of a key, such as an account number or a recovery email.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyField {
+  pub name: String,
+  #[serde(default)]
+  pub field_type: KeyFieldType,
+  pub value: String,
+}
+
+impl ProfileDB {
+  /// Replaces all custom fields of the key.
+  pub(in crate::profile) async fn replace_key_fields(
+    conn: &mut SqliteConnection,
+    key_id: i64,
+    fields: &[KeyField],
+  ) -> Result<(), Error> {
+    query!("DELETE FROM key_fields WHERE key_fields.key_id = ?", key_id)
+      .execute(&mut *conn)
+      .await?;
+
+    if !fields.is_empty() {
+      let mut query_builder = create_key_field_query(fields.iter().map(|field| (key_id, field)));
+      query_builder.build().execute(&mut *conn).await?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Builds a single insert statement for the given key id and field pairs.
+pub(in crate::profile) fn create_key_field_query<'a>(
+  fields: impl Iterator<Item = (i64, &'a KeyField)>,
+) -> QueryBuilder<'a, Sqlite> {
+  let mut query_builder: QueryBuilder<Sqlite> =
+    QueryBuilder::new("INSERT INTO key_fields (key_id, name, field_type, value) ");
+
+  query_builder.push_values(fields, |mut b, (key_id, field)| {
+    b.push_bind(key_id);
+    b.push_bind(field.name.trim());
+    b.push_bind(field.field_type.as_str());
+    b.push_bind(field.value.trim());
+  });
+
+  query_builder
+}
+
+/// Space separated field values stored in the search index. Hidden fields are excluded.
+pub(in crate::profile) fn field_index_text(fields: &[KeyField]) -> String {
+  fields
+    .iter()
+    .filter(|field| field.field_type != KeyFieldType::Hidden)
+    .map(|field| field.value.trim())
+    .collect::<Vec<&str>>()
+    .join(" ")
+}
+
+pub(super) fn validate_key_fields(fields: &[KeyField]) -> Result<(), ValidationError> {
+  let mut seen: HashSet<String> = HashSet::new();
+
+  for field in fields {
+    let name = field.name.trim();
+
+    if name.is_empty() || !field.field_type.is_valid(field.value.trim()) {
+      let mut error = ValidationError::new("key_field");
+      error.add_param("name".into(), &name);
+      return Err(error);
+    }
+
+    if !seen.insert(name.to_lowercase()) {
+      let mut error = ValidationError::new("duplicate_key_field");
+      error.add_param("name".into(), &name);
+      return Err(error);
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,157 @@
+use crate::errors::Error;
+use crate::profile::search_query::{SearchField, SearchQuery, TermKind};
+use crate::profile::utils::text_fold::fold_text;
+use crate::profile::ProfileDB;
+use sqlx::{query_as, FromRow, Sqlite};
+use std::collections::BTreeSet;
+
+const TRIGRAM_LEN: usize = 3;
+const MAX_CANDIDATES: i64 = 500;
+
+#[derive(FromRow)]
+struct TrigramRow {
+  id: i64,
+  domain: String,
+  username: String,
+  notes: Option<String>,
+  tags: String,
+  urls: Option<String>,
+  fields: Option<String>,
+}
+
+struct FuzzyTerm {
+  field: Option<SearchField>,
+  text: Vec<char>,
+  kind: TermKind,
+}
+
+impl ProfileDB {
+  /// Typo tolerant search, used when the full text search has no results.
+  ///
+  /// Candidates sharing at least one trigram with the query terms are loaded from the trigram
+  /// index, then every term is matched against the words of the candidate by edit distance.
+  /// Returns the matching key ids ordered by their total distance.
+  pub(super) async fn fuzzy_search(&self, search_query: &SearchQuery) -> Result<Vec<i64>, Error> {
+    let terms: Vec<FuzzyTerm> = match search_query.fuzzy_terms() {
+      Some(terms) => terms
+        .into_iter()
+        .map(|term| FuzzyTerm {
+          field: term.field,
+          text: fold_text(&term.text).chars().collect(),
+          kind: term.kind,
+        })
+        .collect(),
+      None => return Ok(Vec::new()),
+    };
+
+    // Trigram index cannot match shorter terms.
+    if terms.iter().any(|term| term.text.len() < TRIGRAM_LEN) {
+      return Ok(Vec::new());
+    }
+
+    let trigrams: BTreeSet<String> = terms
+      .iter()
+      .flat_map(|term| term.text.windows(TRIGRAM_LEN))
+      .map(|window| {
+        let trigram: String = window.iter().collect();
+        format!("\"{}\"", trigram.replace('"', "\"\""))
+      })
+      .collect();
+    let match_query = Vec::from_iter(trigrams).join(" OR ");
+
+    let mut conn = self.pool.acquire().await?;
+    let candidates = query_as::<Sqlite, TrigramRow>(
+      "SELECT ROWID as id, domain, username, notes, tags, urls, fields
+       FROM search_trigram
+       WHERE search_trigram MATCH ?
+       ORDER BY rank
+       LIMIT ?",
+    )
+    .bind(match_query)
+    .bind(MAX_CANDIDATES)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut matches: Vec<(usize, i64)> = candidates
+      .iter()
+      .filter_map(|row| {
+        terms
+          .iter()
+          .map(|term| term_distance(term, row))
+          .try_fold(0, |total, distance| distance.map(|d| total + d))
+          .map(|total| (total, row.id))
+      })
+      .collect();
+
+    matches.sort();
+
+    Ok(matches.into_iter().map(|(_, id)| id).collect())
+  }
+}
+
+/// Returns the smallest edit distance between the term and the row fields, `None` if it exceeds
+/// the allowed distance for the term length.
+fn term_distance(term: &FuzzyTerm, row: &TrigramRow) -> Option<usize> {
+  let fields: Vec<&str> = match term.field {
+    Some(SearchField::Domain) => vec![&row.domain],
+    Some(SearchField::Username) => vec![&row.username],
+    Some(SearchField::Notes) => row.notes.as_deref().into_iter().collect(),
+    Some(SearchField::Tag) => vec![&row.tags],
+    None => [&row.domain, &row.username, &row.tags]
+      .into_iter()
+      .map(String::as_str)
+      .chain(row.notes.as_deref())
+      .chain(row.urls.as_deref())
+      .chain(row.fields.as_deref())
+      .collect(),
+  };
+
+  let term_text: String = term.text.iter().collect();
+  let max_distance = match term.text.len() {
+    0..=4 => 1,
+    5..=8 => 2,
+    _ => 3,
+  };
+
+  fields
+    .into_iter()
+    .flat_map(|field| {
+      if field.contains(&term_text) {
+        return vec![0];
+      }
+
+      field
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+          let word: Vec<char> = word.chars().collect();
+
+          match term.kind {
+            TermKind::Prefix => edit_distance(&term.text, &word[..word.len().min(term.text.len())]),
+            TermKind::Word | TermKind::Phrase => edit_distance(&term.text, &word),
+          }
+        })
+        .collect()
+    })
+    .min()
+    .filter(|distance| *distance <= max_distance)
+}
+
+/// Levenshtein distance of two strings.
+fn edit_distance(left: &[char], right: &[char]) -> usize {
+  let mut previous: Vec<usize> = (0..=right.len()).collect();
+  let mut current: Vec<usize> = vec![0; right.len() + 1];
+
+  for (i, left_char) in left.iter().enumerate() {
+    current[0] = i + 1;
+
+    for (j, right_char) in right.iter().enumerate() {
+      let substitution = previous[j] + usize::from(left_char != right_char);
+      current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+    }
+
+    std::mem::swap(&mut previous, &mut current);
+  }
+
+  previous[right.len()]
+}
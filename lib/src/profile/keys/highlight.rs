@@ -0,0 +1,171 @@
+use crate::errors::Error;
+use crate::profile::keys::{field_index_text, url_index_text, KeyItem};
+use crate::profile::search_query::SearchQuery;
+use crate::profile::ProfileDB;
+use serde::Serialize;
+use sqlx::{FromRow, QueryBuilder, Sqlite};
+use std::collections::HashMap;
+
+// Private use characters never appear in user input, so they can safely mark match boundaries.
+const MATCH_START: char = '\u{E000}';
+const MATCH_END: char = '\u{E001}';
+const SNIPPET_ELLIPSIS: &str = "…";
+const SNIPPET_TOKENS: i64 = 16;
+
+/// Part of a highlighted text. Concatenating all segments gives back the original text, except
+/// for snippets which may be trimmed with `…`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HighlightSegment {
+  pub text: String,
+  pub matched: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SearchHighlights {
+  pub domain: Vec<HighlightSegment>,
+  pub username: Vec<HighlightSegment>,
+  /// Short fragment of the notes around the best match.
+  pub notes: Option<Vec<HighlightSegment>>,
+  /// Tags joined with a single space.
+  pub tags: Vec<HighlightSegment>,
+  /// URLs joined with a single space.
+  pub urls: Vec<HighlightSegment>,
+  /// Values of the fields that are not hidden, joined with a single space.
+  pub fields: Vec<HighlightSegment>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+  #[serde(flatten)]
+  pub key: KeyItem,
+  /// Negated bm25 rank of the key, higher is more relevant. Results without a full text match,
+  /// like filter only queries or typo tolerant matches, have a score of `0.0`.
+  pub score: f64,
+  pub highlights: SearchHighlights,
+}
+
+#[derive(FromRow)]
+struct HighlightRow {
+  id: i64,
+  score: f64,
+  domain: String,
+  username: String,
+  notes: Option<String>,
+  tags: String,
+  urls: String,
+  fields: String,
+}
+
+impl ProfileDB {
+  /// Attaches the full text search highlights and scores to the given keys.
+  pub(super) async fn search_highlights(
+    &self,
+    search_query: &SearchQuery,
+    items: Vec<KeyItem>,
+  ) -> Result<Vec<SearchResult>, Error> {
+    let mut rows: HashMap<i64, HighlightRow> = HashMap::new();
+
+    if let (Some(fts_query), false) = (search_query.to_fts_query(), items.is_empty()) {
+      let mut query_builder: QueryBuilder<Sqlite> =
+        QueryBuilder::new("SELECT ROWID as id, -rank as score");
+      query_builder.push(format!(
+        ", highlight(search_index, 0, '{MATCH_START}', '{MATCH_END}') as domain, \
+           highlight(search_index, 1, '{MATCH_START}', '{MATCH_END}') as username, \
+           snippet(search_index, 2, '{MATCH_START}', '{MATCH_END}', '{SNIPPET_ELLIPSIS}', {SNIPPET_TOKENS}) as notes, \
+           highlight(search_index, 3, '{MATCH_START}', '{MATCH_END}') as tags, \
+           highlight(search_index, 4, '{MATCH_START}', '{MATCH_END}') as urls, \
+           highlight(search_index, 5, '{MATCH_START}', '{MATCH_END}') as fields"
+      ));
+      query_builder.push(" FROM search_index WHERE search_index MATCH ");
+      query_builder.push_bind(fts_query);
+      query_builder.push(" AND ROWID IN (");
+      let mut separated = query_builder.separated(", ");
+      for item in items.iter() {
+        separated.push_bind(item.id);
+      }
+      separated.push_unseparated(")");
+
+      let mut conn = self.pool.acquire().await?;
+      let result: Vec<HighlightRow> = query_builder
+        .build_query_as::<HighlightRow>()
+        .fetch_all(&mut *conn)
+        .await?;
+
+      rows.extend(result.into_iter().map(|row| (row.id, row)));
+    }
+
+    let results = items
+      .into_iter()
+      .map(|key| match rows.remove(&key.id) {
+        Some(row) => SearchResult {
+          score: row.score,
+          highlights: SearchHighlights {
+            domain: parse_segments(&row.domain),
+            username: parse_segments(&row.username),
+            notes: row.notes.as_deref().map(parse_segments),
+            tags: parse_segments(&row.tags),
+            urls: parse_segments(&row.urls),
+            fields: parse_segments(&row.fields),
+          },
+          key,
+        },
+        None => SearchResult {
+          score: 0.0,
+          highlights: SearchHighlights {
+            domain: plain_segments(&key.domain),
+            username: plain_segments(&key.username),
+            notes: key.notes.as_deref().map(plain_segments),
+            tags: plain_segments(&key.tags.join(' ')),
+            urls: plain_segments(&url_index_text(&key.urls)),
+            fields: plain_segments(&field_index_text(&key.fields)),
+          },
+          key,
+        },
+      })
+      .collect();
+
+    Ok(results)
+  }
+}
+
+fn plain_segments(text: &str) -> Vec<HighlightSegment> {
+  if text.is_empty() {
+    return Vec::new();
+  }
+
+  vec![HighlightSegment {
+    text: String::from(text),
+    matched: false,
+  }]
+}
+
+/// Splits the `highlight()` output into matched and unmatched segments.
+fn parse_segments(text: &str) -> Vec<HighlightSegment> {
+  let mut segments: Vec<HighlightSegment> = Vec::new();
+  let mut current = String::new();
+  let mut matched = false;
+
+  for symbol in text.chars() {
+    match symbol {
+      MATCH_START | MATCH_END => {
+        if !current.is_empty() {
+          segments.push(HighlightSegment {
+            text: std::mem::take(&mut current),
+            matched,
+          });
+        }
+        matched = symbol == MATCH_START;
+      }
+      _ => current.push(symbol),
+    }
+  }
+
+  if !current.is_empty() {
+    segments.push(HighlightSegment {
+      text: current,
+      matched,
+    });
+  }
+
+  segments
+}
@@ -0,0 +1,276 @@
+use crate::errors::Error;
+use crate::profile::events::ProfileEvent;
+use crate::profile::keys::KeyItem;
+use crate::profile::utils::timestamp::get_unix_timestamp;
+use crate::profile::ProfileDB;
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, Connection, FromRow, Sqlite, SqliteConnection};
+use std::collections::HashMap;
+use validator::{ValidationError, ValidationErrors};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Records the time a revision is set for a key.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct KeyRotationItem {
+  pub id: i64,
+  pub key_id: i64,
+  pub revision: i64,
+  pub rotated_at: i64,
+}
+
+/// Rotation interval applied to all keys with the tag.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct RotationPolicy {
+  pub tag: String,
+  pub rotation_days: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotationDueItem {
+  #[serde(flatten)]
+  pub key: KeyItem,
+  /// Effective rotation interval, either the key's own interval or the shortest interval of its
+  /// tags.
+  pub rotation_days: i64,
+  /// Unix timestamp of the last revision change. Keys without any rotation record use their
+  /// creation time.
+  pub last_rotated_at: i64,
+  pub due_at: i64,
+}
+
+#[derive(FromRow)]
+struct RotationDueRow {
+  key_id: i64,
+  rotation_days: i64,
+  last_rotated_at: i64,
+  due_at: i64,
+}
+
+impl ProfileDB {
+  /// Sets the rotation interval of the key. `None` falls back to the tag rotation policies.
+  pub async fn set_key_rotation(
+    &self,
+    key_id: i64,
+    rotation_days: Option<u32>,
+  ) -> Result<(), Error> {
+    validate_rotation_days(rotation_days)?;
+
+    let rotation_days = rotation_days.map(i64::from);
+    let mut conn = self.pool.acquire().await?;
+    let result = query!(
+      "UPDATE keys SET rotation_days = ? WHERE keys.id = ? AND keys.deleted_at IS NULL",
+      rotation_days,
+      key_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if result.rows_affected() == 0 {
+      return Err(Error::KeyNotFound { key_id });
+    }
+
+    self.notify(ProfileEvent::KeyUpdated { key_id });
+
+    Ok(())
+  }
+
+  pub async fn get_rotation_policies(&self) -> Result<Vec<RotationPolicy>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as!(
+      RotationPolicy,
+      "SELECT tag, rotation_days FROM tag_rotation_policies ORDER BY tag"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  /// Sets the rotation interval for all keys with the tag. `None` removes the policy.
+  pub async fn set_tag_rotation(&self, tag: &str, rotation_days: Option<u32>) -> Result<(), Error> {
+    if tag.is_empty() {
+      let mut error = ValidationError::new("length");
+      error.add_param("min".into(), &1);
+
+      let mut errors = ValidationErrors::new();
+      errors.add("tag", error);
+      return Err(errors.into());
+    }
+
+    validate_rotation_days(rotation_days)?;
+
+    let mut conn = self.pool.acquire().await?;
+
+    match rotation_days.map(i64::from) {
+      Some(rotation_days) => {
+        query!(
+          "INSERT INTO tag_rotation_policies (tag, rotation_days) VALUES (?, ?)
+          ON CONFLICT (tag) DO UPDATE SET rotation_days = excluded.rotation_days",
+          tag,
+          rotation_days
+        )
+        .execute(&mut *conn)
+        .await?;
+      }
+      None => {
+        query!("DELETE FROM tag_rotation_policies WHERE tag = ?", tag)
+          .execute(&mut *conn)
+          .await?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Returns the revision changes of the key, most recent first.
+  pub async fn get_key_rotations(&self, key_id: i64) -> Result<Vec<KeyRotationItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as!(
+      KeyRotationItem,
+      r#"SELECT id as "id!", key_id, revision, rotated_at
+      FROM key_rotations
+      WHERE key_id = ?
+      ORDER BY rotated_at DESC, id DESC"#,
+      key_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  /// Increments the revision of the key and records the rotation. Returns the new revision.
+  pub async fn rotate_key(&self, key_id: i64) -> Result<i64, Error> {
+    let now: i64 = get_unix_timestamp()?;
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+
+    Self::insert_key_snapshot(&mut transaction, key_id, now).await?;
+
+    let result = query!(
+      "UPDATE keys SET revision = revision + 1, updated_at = ?
+      WHERE keys.id = ? AND keys.deleted_at IS NULL
+      RETURNING revision",
+      now,
+      key_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    let Some(row) = result else {
+      return Err(Error::KeyNotFound { key_id });
+    };
+
+    Self::insert_key_rotation(&mut transaction, key_id, row.revision, now).await?;
+    transaction.commit().await?;
+    self.notify(ProfileEvent::KeyUpdated { key_id });
+
+    Ok(row.revision)
+  }
+
+  /// Returns the keys with a rotation interval that are overdue or become due within the given
+  /// number of days, earliest due date first.
+  pub async fn get_keys_due_for_rotation(
+    &self,
+    within_days: u32,
+  ) -> Result<Vec<RotationDueItem>, Error> {
+    let threshold = get_unix_timestamp()? + i64::from(within_days) * SECONDS_PER_DAY;
+    let mut conn = self.pool.acquire().await?;
+    let rows = query_as::<Sqlite, RotationDueRow>(
+      "WITH rotation AS (
+        SELECT
+          keys.id AS key_id,
+          ifnull(
+            keys.rotation_days,
+            (SELECT MIN(tag_rotation_policies.rotation_days)
+              FROM tags
+              INNER JOIN tag_rotation_policies ON tag_rotation_policies.tag = tags.name
+              WHERE tags.key_id = keys.id)
+          ) AS rotation_days,
+          ifnull(
+            (SELECT MAX(key_rotations.rotated_at) FROM key_rotations WHERE key_rotations.key_id = keys.id),
+            keys.created_at
+          ) AS last_rotated_at
+        FROM keys
+        WHERE keys.deleted_at IS NULL
+      )
+      SELECT key_id, rotation_days, last_rotated_at, last_rotated_at + rotation_days * ? AS due_at
+      FROM rotation
+      WHERE rotation_days IS NOT NULL AND last_rotated_at + rotation_days * ? <= ?
+      ORDER BY due_at ASC, key_id ASC",
+    )
+    .bind(SECONDS_PER_DAY)
+    .bind(SECONDS_PER_DAY)
+    .bind(threshold)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let key_ids: Vec<i64> = rows.iter().map(|row| row.key_id).collect();
+    let mut keys: HashMap<i64, KeyItem> = self
+      .get_keys_by_ids(&key_ids)
+      .await?
+      .into_iter()
+      .map(|key| (key.id, key))
+      .collect();
+
+    let result = rows
+      .into_iter()
+      .filter_map(|row| {
+        keys.remove(&row.key_id).map(|key| RotationDueItem {
+          key,
+          rotation_days: row.rotation_days,
+          last_rotated_at: row.last_rotated_at,
+          due_at: row.due_at,
+        })
+      })
+      .collect();
+
+    Ok(result)
+  }
+
+  pub(in crate::profile) async fn get_all_key_rotations(
+    &self,
+  ) -> Result<Vec<KeyRotationItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as!(
+      KeyRotationItem,
+      r#"SELECT id as "id!", key_id, revision, rotated_at FROM key_rotations"#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  pub(super) async fn insert_key_rotation(
+    conn: &mut SqliteConnection,
+    key_id: i64,
+    revision: i64,
+    rotated_at: i64,
+  ) -> Result<(), Error> {
+    query!(
+      "INSERT INTO key_rotations (key_id, revision, rotated_at) VALUES (?, ?, ?)",
+      key_id,
+      revision,
+      rotated_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+}
+
+fn validate_rotation_days(rotation_days: Option<u32>) -> Result<(), ValidationErrors> {
+  if rotation_days == Some(0) {
+    let mut error = ValidationError::new("range");
+    error.add_param("min".into(), &1);
+
+    let mut errors = ValidationErrors::new();
+    errors.add("rotation_days", error);
+    return Err(errors);
+  }
+
+  Ok(())
+}
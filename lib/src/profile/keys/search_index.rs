@@ -0,0 +1,157 @@
+use super::{field_index_text, url_index_text, KeyItem, SearchIndex};
+use crate::errors::Error;
+use crate::profile::backup::{create_index_query, create_trigram_index_query};
+use crate::profile::ProfileDB;
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, Acquire, FromRow, SqliteConnection};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Differences between the keys and the `search_index` and `search_trigram` tables. Keys with
+/// problems are excluded from search results until the index is rebuilt.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchIndexReport {
+  /// Keys without an index row.
+  pub missing: Vec<i64>,
+  /// Keys with outdated index values.
+  pub stale: Vec<i64>,
+  /// Index rows of keys that no longer exist.
+  pub orphaned: Vec<i64>,
+}
+
+impl SearchIndexReport {
+  pub fn is_valid(&self) -> bool {
+    self.missing.is_empty() && self.stale.is_empty() && self.orphaned.is_empty()
+  }
+}
+
+/// Index columns are nullable, rows created before the `urls` and `fields` columns have `NULL`
+/// values.
+#[derive(FromRow)]
+struct IndexRow {
+  id: i64,
+  domain: Option<String>,
+  username: Option<String>,
+  tags: Option<String>,
+  notes: Option<String>,
+  urls: Option<String>,
+  fields: Option<String>,
+}
+
+impl IndexRow {
+  fn matches(&self, expected: &SearchIndex) -> bool {
+    self.domain.as_deref().unwrap_or_default() == expected.domain
+      && self.username.as_deref().unwrap_or_default() == expected.username
+      && self.notes.as_deref().unwrap_or_default() == expected.notes.as_deref().unwrap_or_default()
+      && self.urls.as_deref().unwrap_or_default() == expected.urls
+      && self.fields.as_deref().unwrap_or_default() == expected.fields
+      && tag_set(self.tags.as_deref().unwrap_or_default()) == tag_set(&expected.tags)
+  }
+}
+
+/// Tag order depends on how the tag rows were written, only the tag set is compared.
+fn tag_set(tags: &str) -> BTreeSet<&str> {
+  tags.split_whitespace().collect()
+}
+
+impl From<&KeyItem> for SearchIndex {
+  fn from(key: &KeyItem) -> Self {
+    Self {
+      domain: key.domain.clone(),
+      fields: field_index_text(&key.fields),
+      id: key.id,
+      notes: key.notes.clone(),
+      tags: key.tags.join(' '),
+      urls: url_index_text(&key.urls),
+      username: key.username.clone(),
+    }
+  }
+}
+
+impl ProfileDB {
+  /// Compares the search index tables with the keys, including the keys in trash. Keys and index
+  /// rows are read in a single transaction, so concurrent writes are never reported as problems.
+  pub async fn verify_search_index(&self) -> Result<SearchIndexReport, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+    let keys = Self::fetch_keys_with(&mut transaction, false, true).await?;
+    let index_rows = fetch_index_rows(&mut transaction, "search_index").await?;
+    let trigram_rows = fetch_index_rows(&mut transaction, "search_trigram").await?;
+    transaction.rollback().await?;
+
+    let mut missing = BTreeSet::new();
+    let mut stale = BTreeSet::new();
+
+    for key in keys.iter() {
+      let expected = SearchIndex::from(key);
+      let folded = expected.folded();
+
+      for (rows, expected) in [(&index_rows, &expected), (&trigram_rows, &folded)] {
+        match rows.get(&key.id) {
+          None => {
+            missing.insert(key.id);
+          }
+          Some(row) if !row.matches(expected) => {
+            stale.insert(key.id);
+          }
+          Some(_) => {}
+        }
+      }
+    }
+
+    let key_ids: HashSet<i64> = keys.iter().map(|key| key.id).collect();
+    let orphaned: BTreeSet<i64> = index_rows
+      .keys()
+      .chain(trigram_rows.keys())
+      .filter(|id| !key_ids.contains(id))
+      .copied()
+      .collect();
+
+    Ok(SearchIndexReport {
+      stale: stale.difference(&missing).copied().collect(),
+      missing: missing.into_iter().collect(),
+      orphaned: orphaned.into_iter().collect(),
+    })
+  }
+
+  /// Regenerates both search index tables from the keys and their tags.
+  pub async fn rebuild_search_index(&self) -> Result<(), Error> {
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+
+    // Deleting first takes the write lock, keys cannot change until the new rows are committed.
+    query!(
+      "DELETE FROM search_index;
+       DELETE FROM search_trigram;"
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let keys = Self::fetch_keys_with(&mut transaction, false, true).await?;
+
+    if !keys.is_empty() {
+      let mut index_query = create_index_query(&keys);
+      index_query.build().execute(&mut *transaction).await?;
+
+      let mut trigram_query = create_trigram_index_query(&keys);
+      trigram_query.build().execute(&mut *transaction).await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+  }
+}
+
+async fn fetch_index_rows(
+  conn: &mut SqliteConnection,
+  table: &str,
+) -> Result<HashMap<i64, IndexRow>, Error> {
+  let rows: Vec<IndexRow> = query_as(&format!(
+    "SELECT ROWID as id, domain, username, tags, notes, urls, fields FROM {}",
+    table
+  ))
+  .fetch_all(&mut *conn)
+  .await?;
+
+  Ok(rows.into_iter().map(|row| (row.id, row)).collect())
+}
@@ -0,0 +1,116 @@
+use crate::errors::Error;
+use crate::profile::ProfileDB;
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, FromRow, QueryBuilder, Sqlite, SqliteConnection};
+use std::fmt::{Debug, Formatter};
+
+/// Defines how the password of a key is produced.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum KeyKind {
+  /// Password is generated from the master password and the key parameters.
+  #[default]
+  Derived,
+  /// Password is a stored secret, such as an issued API token or a recovery code. Password
+  /// parameters of the key are not used.
+  Stored,
+}
+
+/// Stored secret of a [KeyKind::Stored] key.
+#[derive(FromRow, Serialize, Deserialize)]
+pub struct KeySecretItem {
+  pub key_id: i64,
+  pub secret: String,
+}
+
+/// Value written in place of secrets in debug output.
+pub(in crate::profile) const REDACTED: &str = "<redacted>";
+
+impl Debug for KeySecretItem {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("KeySecretItem")
+      .field("key_id", &self.key_id)
+      .field("secret", &REDACTED)
+      .finish()
+  }
+}
+
+impl ProfileDB {
+  /// Returns the stored secret of the key, `None` for derived keys.
+  pub async fn get_key_secret(&self, key_id: i64) -> Result<Option<String>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    Self::read_key_secret(&mut conn, key_id).await
+  }
+
+  pub(in crate::profile) async fn read_key_secret(
+    conn: &mut SqliteConnection,
+    key_id: i64,
+  ) -> Result<Option<String>, Error> {
+    let result = query!(
+      "SELECT key_secrets.secret FROM key_secrets WHERE key_secrets.key_id = ?",
+      key_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(result.map(|row| row.secret))
+  }
+
+  pub(in crate::profile) async fn get_all_key_secrets(&self) -> Result<Vec<KeySecretItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as!(KeySecretItem, "SELECT key_id, secret FROM key_secrets")
+      .fetch_all(&mut *conn)
+      .await?;
+
+    Ok(result)
+  }
+
+  /// Sets the stored secret of the key. `None` keeps the existing secret of a stored key, derived
+  /// keys have their secret removed.
+  pub(in crate::profile) async fn replace_key_secret(
+    conn: &mut SqliteConnection,
+    key_id: i64,
+    kind: KeyKind,
+    secret: Option<&str>,
+  ) -> Result<(), Error> {
+    match (kind, secret) {
+      (KeyKind::Stored, Some(secret)) => {
+        query!(
+          "INSERT INTO key_secrets (key_id, secret) VALUES (?, ?)
+          ON CONFLICT (key_id) DO UPDATE SET secret = excluded.secret",
+          key_id,
+          secret
+        )
+        .execute(&mut *conn)
+        .await?;
+      }
+      (KeyKind::Stored, None) => {}
+      (KeyKind::Derived, _) => {
+        query!(
+          "DELETE FROM key_secrets WHERE key_secrets.key_id = ?",
+          key_id
+        )
+        .execute(&mut *conn)
+        .await?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Builds a single insert statement for the given secrets.
+pub(in crate::profile) fn create_key_secret_query(
+  secrets: &[KeySecretItem],
+) -> QueryBuilder<'_, Sqlite> {
+  let mut query_builder: QueryBuilder<Sqlite> =
+    QueryBuilder::new("INSERT INTO key_secrets (key_id, secret) ");
+
+  query_builder.push_values(secrets.iter(), |mut b, secret_item| {
+    b.push_bind(secret_item.key_id);
+    b.push_bind(&secret_item.secret);
+  });
+
+  query_builder
+}
@@ -0,0 +1,151 @@
+use crate::errors::Error;
+use crate::profile::events::ProfileEvent;
+use crate::profile::keys::{KeyItem, SearchIndexOp, KEY_ITEM_SELECT};
+use crate::profile::utils::timestamp::get_unix_timestamp;
+use crate::profile::ProfileDB;
+use sqlx::{query, query_as, Connection, Sqlite, SqliteConnection};
+
+impl ProfileDB {
+  /// Returns the keys in trash, most recently deleted first.
+  pub async fn get_trashed_keys(&self) -> Result<Vec<KeyItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as::<Sqlite, KeyItem>(&format!(
+      "{KEY_ITEM_SELECT}
+      FROM keys
+      WHERE keys.deleted_at IS NOT NULL
+      ORDER BY keys.deleted_at DESC, keys.id DESC"
+    ))
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  /// Moves the key out of trash.
+  pub async fn restore_key(&self, key_id: i64) -> Result<(), Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query!(
+      "UPDATE keys SET deleted_at = NULL WHERE keys.id = ? AND keys.deleted_at IS NOT NULL",
+      key_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if result.rows_affected() == 0 {
+      return Err(Error::KeyNotFound { key_id });
+    }
+
+    self.notify(ProfileEvent::KeyUpdated { key_id });
+
+    Ok(())
+  }
+
+  /// Permanently deletes the key with its tags, history and search index entries. Only keys in
+  /// trash can be purged.
+  pub async fn purge_key(&self, key_id: i64) -> Result<(), Error> {
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+    Self::purge_key_rows(&mut transaction, key_id).await?;
+    transaction.commit().await?;
+
+    self.notify(ProfileEvent::KeyDeleted { key_id });
+
+    Ok(())
+  }
+
+  /// Permanently deletes the keys that stayed in trash longer than the retention period. Returns
+  /// the purged keys, so their icons can be removed. Zero retention empties the trash.
+  pub async fn purge_trash(&self, retention_days: u32) -> Result<Vec<KeyItem>, Error> {
+    let threshold = get_unix_timestamp()? - i64::from(retention_days) * 24 * 60 * 60;
+    let expired: Vec<KeyItem> = self
+      .get_trashed_keys()
+      .await?
+      .into_iter()
+      .filter(|key| {
+        key
+          .deleted_at
+          .is_some_and(|deleted_at| deleted_at <= threshold)
+      })
+      .collect();
+
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+
+    for key in expired.iter() {
+      Self::purge_key_rows(&mut transaction, key.id).await?;
+    }
+
+    transaction.commit().await?;
+
+    for key in expired.iter() {
+      self.notify(ProfileEvent::KeyDeleted { key_id: key.id });
+    }
+
+    Ok(expired)
+  }
+
+  async fn purge_key_rows(conn: &mut SqliteConnection, key_id: i64) -> Result<(), Error> {
+    let trashed = query!(
+      "SELECT id FROM keys WHERE keys.id = ? AND keys.deleted_at IS NOT NULL",
+      key_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if trashed.is_none() {
+      return Err(Error::KeyNotFound { key_id });
+    }
+
+    query!("DELETE FROM key_urls WHERE key_urls.key_id = ?", key_id)
+      .execute(&mut *conn)
+      .await?;
+
+    query!("DELETE FROM key_fields WHERE key_fields.key_id = ?", key_id)
+      .execute(&mut *conn)
+      .await?;
+
+    query!(
+      "DELETE FROM key_secrets WHERE key_secrets.key_id = ?",
+      key_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!("DELETE FROM tags WHERE tags.key_id = ?", key_id)
+      .execute(&mut *conn)
+      .await?;
+
+    query!(
+      "DELETE FROM key_history_secrets WHERE key_history_secrets.history_id IN
+        (SELECT key_history.id FROM key_history WHERE key_history.key_id = ?)",
+      key_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+      "DELETE FROM key_history WHERE key_history.key_id = ?",
+      key_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+      "DELETE FROM key_rotations WHERE key_rotations.key_id = ?",
+      key_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+      "DELETE FROM keys WHERE keys.id = ? AND keys.deleted_at IS NOT NULL",
+      key_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Self::sync_search_index(&mut *conn, SearchIndexOp::Delete(key_id)).await?;
+
+    Ok(())
+  }
+}
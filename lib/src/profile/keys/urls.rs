@@ -0,0 +1,248 @@
+use super::KeyItem;
+use crate::errors::Error;
+use crate::profile::utils::domain::{parse_host, parse_url, registrable_domain};
+use crate::profile::ProfileDB;
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, FromRow, QueryBuilder, Sqlite, SqliteConnection};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use url::Url;
+use validator::ValidationError;
+
+/// Defines which addresses match a [KeyUrl].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlMatchRule {
+  /// Only the same host, `accounts.google.com` does not match `mail.google.com`.
+  ExactHost,
+  /// Any host with the same registrable domain, `google.com` matches `mail.google.com`.
+  #[default]
+  BaseDomain,
+  /// Addresses starting with the value, such as `https://example.com/admin` or an app id.
+  Prefix,
+}
+
+impl UrlMatchRule {
+  /// Value stored in the `key_urls.match_rule` column.
+  pub(in crate::profile) fn as_str(&self) -> &'static str {
+    match self {
+      UrlMatchRule::ExactHost => "exact_host",
+      UrlMatchRule::BaseDomain => "base_domain",
+      UrlMatchRule::Prefix => "prefix",
+    }
+  }
+
+  fn from_column(value: &str) -> Self {
+    match value {
+      "exact_host" => UrlMatchRule::ExactHost,
+      "prefix" => UrlMatchRule::Prefix,
+      _ => UrlMatchRule::BaseDomain,
+    }
+  }
+}
+
+/// Match quality of [ProfileDB::find_keys_for_url] results, from the best to the worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlMatchKind {
+  /// Same host, or a matching prefix rule.
+  ExactHost,
+  /// The URL host is a subdomain of the key host, e.g. `mail.google.com` for `google.com`.
+  Subdomain,
+  /// Only the registrable domains are the same, e.g. `mail.google.com` for `docs.google.com`.
+  BaseDomain,
+}
+
+/// Additional host, URL or app id of a key. Only the key domain is used for password generation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyUrl {
+  pub url: String,
+  #[serde(default)]
+  pub match_rule: UrlMatchRule,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UrlMatchItem {
+  #[serde(flatten)]
+  pub key: KeyItem,
+  pub match_kind: UrlMatchKind,
+}
+
+#[derive(FromRow)]
+struct UrlCandidate {
+  key_id: i64,
+  url: String,
+  match_rule: String,
+}
+
+impl ProfileDB {
+  /// Returns the keys matching the URL, best matches first. The key domain matches like a
+  /// [UrlMatchRule::BaseDomain] URL. Keys with the same match quality are ordered by their last
+  /// use. Values without a host return no keys.
+  pub async fn find_keys_for_url(&self, url: &str) -> Result<Vec<UrlMatchItem>, Error> {
+    let Some(target) = parse_url(url) else {
+      return Ok(Vec::new());
+    };
+    let Some(host) = target.host_str().map(str::to_lowercase) else {
+      return Ok(Vec::new());
+    };
+    let base_domain = registrable_domain(&host);
+    let unicode_base_domain = url::quirks::domain_to_unicode(&base_domain);
+
+    // Any match requires the same registrable domain, so the candidates must contain it. Parsed
+    // hosts are punycode while stored values keep the form they are typed in, both forms are
+    // searched. SQLite `lower()` only folds ASCII, values with non-ASCII uppercase letters are
+    // not found.
+    let mut conn = self.pool.acquire().await?;
+    let candidates = query_as::<Sqlite, UrlCandidate>(
+      "SELECT keys.id as key_id, keys.domain as url, 'base_domain' as match_rule
+      FROM keys
+      WHERE keys.deleted_at IS NULL
+        AND (instr(lower(keys.domain), ?) > 0 OR instr(lower(keys.domain), ?) > 0)
+      UNION ALL
+      SELECT key_urls.key_id, key_urls.url, key_urls.match_rule
+      FROM key_urls
+      INNER JOIN keys ON keys.id = key_urls.key_id
+      WHERE keys.deleted_at IS NULL
+        AND (key_urls.match_rule = 'prefix'
+          OR instr(lower(key_urls.url), ?) > 0
+          OR instr(lower(key_urls.url), ?) > 0)",
+    )
+    .bind(&base_domain)
+    .bind(&unicode_base_domain)
+    .bind(&base_domain)
+    .bind(&unicode_base_domain)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut matches: HashMap<i64, UrlMatchKind> = HashMap::new();
+
+    for candidate in candidates {
+      let rule = UrlMatchRule::from_column(&candidate.match_rule);
+
+      if let Some(kind) = match_url(&target, &host, &base_domain, &candidate.url, rule) {
+        matches
+          .entry(candidate.key_id)
+          .and_modify(|best| *best = (*best).min(kind))
+          .or_insert(kind);
+      }
+    }
+
+    let key_ids: Vec<i64> = matches.keys().copied().collect();
+    let mut items: Vec<UrlMatchItem> = self
+      .get_keys_by_ids(&key_ids)
+      .await?
+      .into_iter()
+      .filter_map(|key| {
+        matches.get(&key.id).map(|match_kind| UrlMatchItem {
+          match_kind: *match_kind,
+          key,
+        })
+      })
+      .collect();
+
+    items.sort_by_key(|item| (item.match_kind, Reverse(item.key.last_used_at), item.key.id));
+
+    Ok(items)
+  }
+
+  /// Replaces all URLs of the key.
+  pub(in crate::profile) async fn replace_key_urls(
+    conn: &mut SqliteConnection,
+    key_id: i64,
+    urls: &[KeyUrl],
+  ) -> Result<(), Error> {
+    query!("DELETE FROM key_urls WHERE key_urls.key_id = ?", key_id)
+      .execute(&mut *conn)
+      .await?;
+
+    if !urls.is_empty() {
+      let mut query_builder = create_key_url_query(urls.iter().map(|url| (key_id, url)));
+      query_builder.build().execute(&mut *conn).await?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Builds a single insert statement for the given key id and URL pairs.
+pub(in crate::profile) fn create_key_url_query<'a>(
+  urls: impl Iterator<Item = (i64, &'a KeyUrl)>,
+) -> QueryBuilder<'a, Sqlite> {
+  let mut query_builder: QueryBuilder<Sqlite> =
+    QueryBuilder::new("INSERT INTO key_urls (key_id, url, match_rule) ");
+
+  query_builder.push_values(urls, |mut b, (key_id, key_url)| {
+    b.push_bind(key_id);
+    b.push_bind(key_url.url.trim());
+    b.push_bind(key_url.match_rule.as_str());
+  });
+
+  query_builder
+}
+
+/// Space separated URLs stored in the search index.
+pub(in crate::profile) fn url_index_text(urls: &[KeyUrl]) -> String {
+  urls
+    .iter()
+    .map(|key_url| key_url.url.trim())
+    .collect::<Vec<&str>>()
+    .join(" ")
+}
+
+fn match_url(
+  target: &Url,
+  host: &str,
+  base_domain: &str,
+  candidate: &str,
+  rule: UrlMatchRule,
+) -> Option<UrlMatchKind> {
+  match rule {
+    UrlMatchRule::Prefix => {
+      let prefix = candidate.trim().to_lowercase();
+      let target = target.as_str().to_lowercase();
+      // Prefixes without a scheme match any scheme.
+      let target = if prefix.contains("://") {
+        target.as_str()
+      } else {
+        target
+          .split_once("://")
+          .map_or(target.as_str(), |(_, rest)| rest)
+      };
+
+      (!prefix.is_empty() && target.starts_with(&prefix)).then_some(UrlMatchKind::ExactHost)
+    }
+    UrlMatchRule::ExactHost => (parse_host(candidate)? == host).then_some(UrlMatchKind::ExactHost),
+    UrlMatchRule::BaseDomain => {
+      let candidate_host = parse_host(candidate)?;
+
+      if candidate_host == host {
+        Some(UrlMatchKind::ExactHost)
+      } else if host.ends_with(&format!(".{}", candidate_host)) {
+        Some(UrlMatchKind::Subdomain)
+      } else if registrable_domain(&candidate_host) == base_domain {
+        Some(UrlMatchKind::BaseDomain)
+      } else {
+        None
+      }
+    }
+  }
+}
+
+pub(super) fn validate_key_urls(urls: &[KeyUrl]) -> Result<(), ValidationError> {
+  let mut seen: HashSet<String> = HashSet::new();
+
+  for key_url in urls {
+    let url = key_url.url.trim();
+
+    if url.is_empty() || url.contains(char::is_whitespace) {
+      return Err(ValidationError::new("key_url"));
+    }
+
+    if !seen.insert(url.to_lowercase()) {
+      return Err(ValidationError::new("duplicate_key_url"));
+    }
+  }
+
+  Ok(())
+}
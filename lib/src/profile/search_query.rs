@@ -0,0 +1,644 @@
+//! Key search query language.
+//!
+//! ```text
+//! query   := or ( "OR" or )*
+//! or      := unary ( [ "AND" ] unary )*
+//! unary   := ( "NOT" | "-" ) unary | "(" query ")" | term
+//! term    := [ field ":" ] ( word [ "*" ] | "\"" phrase "\"" )
+//! ```
+//!
+//! Terms are joined with `AND` unless `OR` is used. Supported fields are `tag`, `domain`,
+//! `username` and `notes` for full text search, and `pinned:true|false`,
+//! `created:[<|<=|>|>=]YYYY-MM-DD` and `charset:<name or pattern>` filters.
+//!
+//! Parser never fails, malformed input is recovered as much as possible: unclosed quotes and
+//! parentheses end at the end of the query, stray `)` are ignored and filters with invalid values
+//! are searched as plain text.
+
+use sqlx::{QueryBuilder, Sqlite};
+
+pub(in crate::profile) const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchField {
+  Domain,
+  Username,
+  Notes,
+  Tag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermKind {
+  Word,
+  Phrase,
+  Prefix,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchTerm {
+  /// Searches all fields when empty.
+  pub field: Option<SearchField>,
+  pub text: String,
+  pub kind: TermKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+  Lt,
+  Le,
+  Eq,
+  Ge,
+  Gt,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchFilter {
+  Pinned(bool),
+  /// Compares the key creation day with the day starting at the given Unix timestamp (UTC).
+  Created(Comparison, i64),
+  /// Matches keys using the charset with the given name, or the given charset pattern.
+  Charset(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchExpr {
+  And(Vec<SearchExpr>),
+  Or(Vec<SearchExpr>),
+  Not(Box<SearchExpr>),
+  Term(SearchTerm),
+  Filter(SearchFilter),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+  /// Parsed query, `None` for blank queries.
+  pub expr: Option<SearchExpr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+  LParen,
+  RParen,
+  And,
+  Or,
+  Not,
+  Term(SearchExpr),
+}
+
+impl SearchQuery {
+  pub fn new(query: &str) -> Self {
+    let tokens = tokenize(query);
+    let mut parser = Parser {
+      tokens: &tokens,
+      position: 0,
+    };
+
+    Self {
+      expr: parser.parse(),
+    }
+  }
+
+  /// Returns the FTS5 `MATCH` expression of the query, used for ranking the results.
+  ///
+  /// Only top level conditions consisting of full text terms are included. Negated full text
+  /// conditions are included with the FTS5 `NOT` operator when there is at least one positive
+  /// condition to apply them to. Remaining conditions are applied by
+  /// [SearchQuery::push_sql_filter].
+  pub fn to_fts_query(&self) -> Option<String> {
+    let (positive, negative) = self.fts_parts();
+
+    if positive.is_empty() {
+      return None;
+    }
+
+    let mut fts_query = positive
+      .into_iter()
+      .filter_map(fts_expr)
+      .collect::<Vec<String>>()
+      .join(" AND ");
+
+    for expr in negative {
+      if let SearchExpr::Not(inner) = expr {
+        if let Some(negated) = fts_expr(inner) {
+          fts_query = format!("({}) NOT {}", fts_query, negated);
+        }
+      }
+    }
+
+    Some(fts_query)
+  }
+
+  /// Pushes the conditions not covered by [SearchQuery::to_fts_query] as `AND (...)` clauses.
+  pub(crate) fn push_sql_filter(&self, query_builder: &mut QueryBuilder<Sqlite>) {
+    let (positive, negative) = self.fts_parts();
+
+    for expr in self.conjuncts() {
+      let in_fts = positive
+        .iter()
+        .chain(negative.iter())
+        .any(|e| std::ptr::eq(*e, expr));
+
+      if !in_fts {
+        query_builder.push(" AND ");
+        push_sql_expr(query_builder, expr);
+      }
+    }
+  }
+
+  /// Returns the terms used by the typo tolerant search. Only queries consisting of terms joined
+  /// with `AND` are supported, `None` is returned for queries with operators or filters.
+  pub fn fuzzy_terms(&self) -> Option<Vec<&SearchTerm>> {
+    let conjuncts = self.conjuncts();
+
+    if conjuncts.is_empty() {
+      return None;
+    }
+
+    conjuncts
+      .into_iter()
+      .map(|expr| match expr {
+        SearchExpr::Term(term) => Some(term),
+        _ => None,
+      })
+      .collect()
+  }
+
+  fn conjuncts(&self) -> Vec<&SearchExpr> {
+    match &self.expr {
+      Some(SearchExpr::And(exprs)) => exprs.iter().collect(),
+      Some(expr) => vec![expr],
+      None => Vec::new(),
+    }
+  }
+
+  /// Splits the top level conditions that can be expressed with FTS5 into positive and negated
+  /// conditions. Negated conditions are only returned when there is a positive one.
+  fn fts_parts(&self) -> (Vec<&SearchExpr>, Vec<&SearchExpr>) {
+    let mut positive = Vec::new();
+    let mut negative = Vec::new();
+
+    for expr in self.conjuncts() {
+      match expr {
+        SearchExpr::Not(inner) if fts_expr(inner).is_some() => negative.push(expr),
+        expr if fts_expr(expr).is_some() => positive.push(expr),
+        _ => {}
+      }
+    }
+
+    if positive.is_empty() {
+      negative.clear();
+    }
+
+    (positive, negative)
+  }
+}
+
+impl<T> From<T> for SearchQuery
+where
+  T: AsRef<str>,
+{
+  fn from(value: T) -> Self {
+    Self::new(value.as_ref())
+  }
+}
+
+/// Converts the expression into an FTS5 expression, returns `None` when the expression contains
+/// filters or a negation that FTS5 cannot express.
+fn fts_expr(expr: &SearchExpr) -> Option<String> {
+  match expr {
+    SearchExpr::Term(term) => Some(fts_term(term)),
+    SearchExpr::Filter(_) | SearchExpr::Not(_) => None,
+    SearchExpr::Or(exprs) => {
+      let parts: Option<Vec<String>> = exprs.iter().map(fts_expr).collect();
+      Some(format!("({})", parts?.join(" OR ")))
+    }
+    SearchExpr::And(exprs) => {
+      let mut positive: Vec<String> = Vec::new();
+      let mut negative: Vec<String> = Vec::new();
+
+      for expr in exprs {
+        match expr {
+          SearchExpr::Not(inner) => negative.push(fts_expr(inner)?),
+          expr => positive.push(fts_expr(expr)?),
+        }
+      }
+
+      if positive.is_empty() {
+        return None;
+      }
+
+      let mut result = format!("({})", positive.join(" AND "));
+
+      for negated in negative {
+        result = format!("({} NOT {})", result, negated);
+      }
+
+      Some(result)
+    }
+  }
+}
+
+fn fts_term(term: &SearchTerm) -> String {
+  let mut result = String::new();
+
+  if let Some(field) = term.field {
+    let column = match field {
+      SearchField::Domain => "domain",
+      SearchField::Username => "username",
+      SearchField::Notes => "notes",
+      SearchField::Tag => "tags",
+    };
+
+    result.push_str(&format!("{{{}}}: ", column));
+  }
+
+  result.push_str(&format!("\"{}\"", term.text.replace('"', "\"\"")));
+
+  if term.kind == TermKind::Prefix {
+    result.push_str(" *");
+  }
+
+  result
+}
+
+fn push_sql_expr(query_builder: &mut QueryBuilder<Sqlite>, expr: &SearchExpr) {
+  if let Some(fts_query) = fts_expr(expr) {
+    query_builder.push("keys.id IN (SELECT ROWID FROM search_index WHERE search_index MATCH ");
+    query_builder.push_bind(fts_query);
+    query_builder.push(")");
+    return;
+  }
+
+  match expr {
+    SearchExpr::And(exprs) | SearchExpr::Or(exprs) => {
+      let separator = match expr {
+        SearchExpr::And(_) => " AND ",
+        _ => " OR ",
+      };
+
+      query_builder.push("(");
+      for (index, expr) in exprs.iter().enumerate() {
+        if index > 0 {
+          query_builder.push(separator);
+        }
+
+        push_sql_expr(query_builder, expr);
+      }
+      query_builder.push(")");
+    }
+    SearchExpr::Not(inner) => {
+      query_builder.push("NOT (");
+      push_sql_expr(query_builder, inner);
+      query_builder.push(")");
+    }
+    SearchExpr::Filter(SearchFilter::Pinned(pinned)) => {
+      query_builder.push("ifnull(keys.pinned, FALSE) = ");
+      query_builder.push_bind(*pinned);
+    }
+    SearchExpr::Filter(SearchFilter::Created(comparison, day_start)) => {
+      let day_end = day_start + SECONDS_PER_DAY;
+      let (condition, bound) = match comparison {
+        Comparison::Lt => ("keys.created_at < ", *day_start),
+        Comparison::Le => ("keys.created_at < ", day_end),
+        Comparison::Ge => ("keys.created_at >= ", *day_start),
+        Comparison::Gt => ("keys.created_at >= ", day_end),
+        Comparison::Eq => {
+          query_builder.push("(keys.created_at >= ");
+          query_builder.push_bind(*day_start);
+          query_builder.push(" AND keys.created_at < ");
+          query_builder.push_bind(day_end);
+          query_builder.push(")");
+          return;
+        }
+      };
+
+      query_builder.push(condition);
+      query_builder.push_bind(bound);
+    }
+    SearchExpr::Filter(SearchFilter::Charset(charset)) => {
+      query_builder.push("(keys.charset = ");
+      query_builder.push_bind(charset.clone());
+      query_builder.push(" OR keys.charset IN (SELECT charset FROM charsets WHERE name = ");
+      query_builder.push_bind(charset.clone());
+      query_builder.push("))");
+    }
+    SearchExpr::Term(_) => unreachable!("terms are always valid fts expressions"),
+  }
+}
+
+struct Parser<'a> {
+  tokens: &'a [Token],
+  position: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn parse(&mut self) -> Option<SearchExpr> {
+    let mut exprs = Vec::new();
+
+    while self.position < self.tokens.len() {
+      if let Some(expr) = self.parse_or() {
+        exprs.push(expr);
+      }
+
+      // Only a stray closing parenthesis stops the top level parsing, skip it and continue.
+      if let Some(Token::RParen) = self.peek() {
+        self.position += 1;
+      }
+    }
+
+    group(exprs, true)
+  }
+
+  fn peek(&self) -> Option<&'a Token> {
+    self.tokens.get(self.position)
+  }
+
+  fn next(&mut self) -> Option<&'a Token> {
+    let token = self.tokens.get(self.position);
+    self.position += 1;
+    token
+  }
+
+  fn parse_or(&mut self) -> Option<SearchExpr> {
+    let mut exprs = Vec::new();
+
+    if let Some(expr) = self.parse_and() {
+      exprs.push(expr);
+    }
+
+    while let Some(Token::Or) = self.peek() {
+      self.position += 1;
+
+      if let Some(expr) = self.parse_and() {
+        exprs.push(expr);
+      }
+    }
+
+    group(exprs, false)
+  }
+
+  fn parse_and(&mut self) -> Option<SearchExpr> {
+    let mut exprs = Vec::new();
+
+    loop {
+      match self.peek() {
+        None | Some(Token::RParen) | Some(Token::Or) => break,
+        Some(Token::And) => self.position += 1,
+        Some(_) => {
+          if let Some(expr) = self.parse_unary() {
+            exprs.push(expr);
+          }
+        }
+      }
+    }
+
+    group(exprs, true)
+  }
+
+  fn parse_unary(&mut self) -> Option<SearchExpr> {
+    match self.next()? {
+      Token::Not => match self.peek() {
+        None | Some(Token::RParen) | Some(Token::Or) | Some(Token::And) => None,
+        Some(_) => self.parse_unary().map(|e| SearchExpr::Not(Box::new(e))),
+      },
+      Token::LParen => {
+        let expr = self.parse_or();
+
+        if let Some(Token::RParen) = self.peek() {
+          self.position += 1;
+        }
+
+        expr
+      }
+      Token::Term(expr) => Some(expr.clone()),
+      Token::RParen | Token::And | Token::Or => None,
+    }
+  }
+}
+
+/// Groups the expressions with `AND` or `OR`, merging nested groups of the same kind.
+fn group(exprs: Vec<SearchExpr>, and: bool) -> Option<SearchExpr> {
+  let mut grouped = Vec::new();
+
+  for expr in exprs {
+    match expr {
+      SearchExpr::And(inner) if and => grouped.extend(inner),
+      SearchExpr::Or(inner) if !and => grouped.extend(inner),
+      expr => grouped.push(expr),
+    }
+  }
+
+  match grouped.len() {
+    0 => None,
+    1 => grouped.pop(),
+    _ if and => Some(SearchExpr::And(grouped)),
+    _ => Some(SearchExpr::Or(grouped)),
+  }
+}
+
+fn tokenize(query: &str) -> Vec<Token> {
+  let mut tokens = Vec::new();
+  let mut chars = query.chars().peekable();
+
+  while let Some(&char) = chars.peek() {
+    match char {
+      ch if ch.is_whitespace() => {
+        chars.next();
+      }
+      '(' => {
+        chars.next();
+        tokens.push(Token::LParen);
+      }
+      ')' => {
+        chars.next();
+        tokens.push(Token::RParen);
+      }
+      '"' => {
+        chars.next();
+        let phrase = read_phrase(&mut chars);
+
+        if let Some(term) = term(None, phrase, TermKind::Phrase) {
+          tokens.push(Token::Term(term));
+        }
+      }
+      '-' => {
+        chars.next();
+
+        match chars.peek() {
+          Some(next) if !next.is_whitespace() && *next != ')' => tokens.push(Token::Not),
+          _ => {}
+        }
+      }
+      _ => {
+        let mut word = String::new();
+
+        while let Some(&ch) = chars.peek() {
+          if ch.is_whitespace() || ch == '(' || ch == ')' {
+            break;
+          }
+
+          chars.next();
+          word.push(ch);
+
+          if ch == ':' && chars.peek() == Some(&'"') && is_field(&word[..word.len() - 1]) {
+            chars.next();
+            let phrase = read_phrase(&mut chars);
+            let field = &word[..word.len() - 1];
+
+            if let Some(term) = field_term(field, phrase, TermKind::Phrase) {
+              tokens.push(Token::Term(term));
+            }
+
+            word.clear();
+            break;
+          }
+        }
+
+        if word.is_empty() {
+          continue;
+        }
+
+        match word.as_str() {
+          "AND" => tokens.push(Token::And),
+          "OR" => tokens.push(Token::Or),
+          "NOT" => tokens.push(Token::Not),
+          _ => {
+            if let Some(term) = word_term(word) {
+              tokens.push(Token::Term(term));
+            }
+          }
+        }
+      }
+    }
+  }
+
+  tokens
+}
+
+fn read_phrase(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+  let mut phrase = String::new();
+
+  for ch in chars.by_ref() {
+    if ch == '"' {
+      break;
+    }
+
+    phrase.push(ch);
+  }
+
+  phrase
+}
+
+fn is_field(name: &str) -> bool {
+  matches!(
+    name.to_lowercase().as_str(),
+    "tag" | "domain" | "username" | "notes" | "pinned" | "created" | "charset"
+  )
+}
+
+fn word_term(word: String) -> Option<SearchExpr> {
+  let (text, kind) = match word.strip_suffix('*') {
+    Some(prefix) if !prefix.is_empty() => (prefix.to_owned(), TermKind::Prefix),
+    _ => (word, TermKind::Word),
+  };
+
+  if let Some((field, value)) = text.split_once(':') {
+    if is_field(field) && !value.is_empty() {
+      if let Some(expr) = field_term(field, value.to_owned(), kind) {
+        return Some(expr);
+      }
+    }
+  }
+
+  term(None, text, kind)
+}
+
+fn field_term(field: &str, value: String, kind: TermKind) -> Option<SearchExpr> {
+  let field = field.to_lowercase();
+  let search_field = match field.as_str() {
+    "tag" => SearchField::Tag,
+    "domain" => SearchField::Domain,
+    "username" => SearchField::Username,
+    "notes" => SearchField::Notes,
+    "pinned" if kind != TermKind::Prefix => {
+      return match value.to_lowercase().as_str() {
+        "true" | "yes" | "1" => Some(SearchExpr::Filter(SearchFilter::Pinned(true))),
+        "false" | "no" | "0" => Some(SearchExpr::Filter(SearchFilter::Pinned(false))),
+        _ => None,
+      };
+    }
+    "created" if kind != TermKind::Prefix => {
+      let (comparison, date) = parse_comparison(&value);
+      return parse_date(date)
+        .map(|day| SearchExpr::Filter(SearchFilter::Created(comparison, day)));
+    }
+    "charset" if kind != TermKind::Prefix && !value.is_empty() => {
+      return Some(SearchExpr::Filter(SearchFilter::Charset(value)));
+    }
+    _ => return None,
+  };
+
+  term(Some(search_field), value, kind)
+}
+
+fn term(field: Option<SearchField>, text: String, kind: TermKind) -> Option<SearchExpr> {
+  if text.trim().is_empty() {
+    None
+  } else {
+    Some(SearchExpr::Term(SearchTerm { field, text, kind }))
+  }
+}
+
+fn parse_comparison(value: &str) -> (Comparison, &str) {
+  if let Some(date) = value.strip_prefix(">=") {
+    (Comparison::Ge, date)
+  } else if let Some(date) = value.strip_prefix("<=") {
+    (Comparison::Le, date)
+  } else if let Some(date) = value.strip_prefix('>') {
+    (Comparison::Gt, date)
+  } else if let Some(date) = value.strip_prefix('<') {
+    (Comparison::Lt, date)
+  } else {
+    (Comparison::Eq, value.strip_prefix('=').unwrap_or(value))
+  }
+}
+
+/// Parses `YYYY-MM-DD` into the Unix timestamp of the day start in UTC. Years are limited to
+/// 1..=9999, so the timestamp and the day end computed from it cannot overflow.
+pub(in crate::profile) fn parse_date(date: &str) -> Option<i64> {
+  let mut parts = date.splitn(3, '-');
+  let year: i64 = parts.next()?.parse().ok()?;
+  let month: i64 = parts.next()?.parse().ok()?;
+  let day: i64 = parts.next()?.parse().ok()?;
+
+  if !(1..=9999).contains(&year)
+    || !(1..=12).contains(&month)
+    || day < 1
+    || day > days_in_month(year, month)
+  {
+    return None;
+  }
+
+  Some(days_from_civil(year, month, day) * SECONDS_PER_DAY)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+  match month {
+    2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+    2 => 28,
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  }
+}
+
+/// Days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = if year >= 0 { year } else { year - 399 } / 400;
+  let year_of_era = year - era * 400;
+  let month_index = if month > 2 { month - 3 } else { month + 9 };
+  let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+  let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+  era * 146_097 + day_of_era - 719_468
+}
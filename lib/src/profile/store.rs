@@ -0,0 +1,147 @@
+use crate::errors::Error;
+use crate::profile::backup::{BackupOptions, RestoreOptions};
+use crate::profile::charsets::{CharsetItem, CharsetUsage};
+use crate::profile::keys::{KeyData, KeyItem, SearchQuery};
+use crate::profile::ProfileDB;
+use std::future::Future;
+
+pub use memory::MemoryProfileStore;
+
+mod memory;
+
+/// Key, charset, search and backup operations of a profile storage backend.
+///
+/// [ProfileDB] stores the profile in an SQLCipher database. [MemoryProfileStore] keeps it in
+/// memory for ephemeral profiles and tests.
+pub trait ProfileStore {
+  fn get_key_by_id(
+    &self,
+    key_id: i64,
+  ) -> impl Future<Output = Result<Option<KeyItem>, Error>> + Send;
+
+  /// Returns all keys except the ones in trash.
+  fn get_keys(&self, pinned_only: bool)
+    -> impl Future<Output = Result<Vec<KeyItem>, Error>> + Send;
+
+  fn search_keys(
+    &self,
+    search_query: SearchQuery,
+  ) -> impl Future<Output = Result<Vec<KeyItem>, Error>> + Send;
+
+  fn insert_key(&self, item: KeyData) -> impl Future<Output = Result<i64, Error>> + Send;
+
+  fn update_key(
+    &self,
+    key_id: i64,
+    item: KeyData,
+  ) -> impl Future<Output = Result<(), Error>> + Send;
+
+  /// Moves the key to trash, returns `false` when the key is missing or already in trash.
+  fn delete_key(&self, key_id: i64) -> impl Future<Output = Result<bool, Error>> + Send;
+
+  fn update_pin_status(
+    &self,
+    key_id: i64,
+    pin_status: bool,
+  ) -> impl Future<Output = Result<(), Error>> + Send;
+
+  /// Returns the stored secret of the key, `None` for derived keys.
+  fn get_key_secret(
+    &self,
+    key_id: i64,
+  ) -> impl Future<Output = Result<Option<String>, Error>> + Send;
+
+  fn get_charsets(&self) -> impl Future<Output = Result<Vec<CharsetItem>, Error>> + Send;
+
+  /// Returns the keys using each charset, trashed keys are excluded.
+  fn get_charset_usage(&self) -> impl Future<Output = Result<Vec<CharsetUsage>, Error>> + Send;
+
+  fn insert_charset(&self, item: CharsetItem)
+    -> impl Future<Output = Result<String, Error>> + Send;
+
+  /// Updates the charset with the same name, keys keep their own charset text.
+  fn update_charset(&self, item: CharsetItem) -> impl Future<Output = Result<(), Error>> + Send;
+
+  fn rename_charset(
+    &self,
+    name: &str,
+    new_name: &str,
+  ) -> impl Future<Output = Result<(), Error>> + Send;
+
+  /// Deletes the charset, fails with [Error::CharsetInUse] for used charsets unless `force` is set.
+  fn delete_charset(
+    &self,
+    name: &str,
+    force: bool,
+  ) -> impl Future<Output = Result<u64, Error>> + Send;
+
+  fn backup(&self, options: BackupOptions) -> impl Future<Output = Result<(), Error>> + Send;
+
+  fn restore(&self, options: RestoreOptions) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+impl ProfileStore for ProfileDB {
+  async fn get_key_by_id(&self, key_id: i64) -> Result<Option<KeyItem>, Error> {
+    ProfileDB::get_key_by_id(self, key_id).await
+  }
+
+  async fn get_keys(&self, pinned_only: bool) -> Result<Vec<KeyItem>, Error> {
+    ProfileDB::get_keys(self, pinned_only).await
+  }
+
+  async fn search_keys(&self, search_query: SearchQuery) -> Result<Vec<KeyItem>, Error> {
+    ProfileDB::search_keys(self, search_query).await
+  }
+
+  async fn insert_key(&self, item: KeyData) -> Result<i64, Error> {
+    ProfileDB::insert_key(self, item).await
+  }
+
+  async fn update_key(&self, key_id: i64, item: KeyData) -> Result<(), Error> {
+    ProfileDB::update_key(self, key_id, item).await
+  }
+
+  async fn delete_key(&self, key_id: i64) -> Result<bool, Error> {
+    ProfileDB::delete_key(self, key_id).await
+  }
+
+  async fn update_pin_status(&self, key_id: i64, pin_status: bool) -> Result<(), Error> {
+    ProfileDB::update_pin_status(self, key_id, pin_status).await
+  }
+
+  async fn get_key_secret(&self, key_id: i64) -> Result<Option<String>, Error> {
+    ProfileDB::get_key_secret(self, key_id).await
+  }
+
+  async fn get_charsets(&self) -> Result<Vec<CharsetItem>, Error> {
+    ProfileDB::get_charsets(self).await
+  }
+
+  async fn get_charset_usage(&self) -> Result<Vec<CharsetUsage>, Error> {
+    ProfileDB::get_charset_usage(self).await
+  }
+
+  async fn insert_charset(&self, item: CharsetItem) -> Result<String, Error> {
+    ProfileDB::insert_charset(self, item).await
+  }
+
+  async fn update_charset(&self, item: CharsetItem) -> Result<(), Error> {
+    ProfileDB::update_charset(self, item).await
+  }
+
+  async fn rename_charset(&self, name: &str, new_name: &str) -> Result<(), Error> {
+    ProfileDB::rename_charset(self, name, new_name).await
+  }
+
+  async fn delete_charset(&self, name: &str, force: bool) -> Result<u64, Error> {
+    ProfileDB::delete_charset(self, name, force).await
+  }
+
+  async fn backup(&self, options: BackupOptions) -> Result<(), Error> {
+    ProfileDB::backup(self, options).await
+  }
+
+  async fn restore(&self, options: RestoreOptions) -> Result<(), Error> {
+    ProfileDB::restore(self, options).await
+  }
+}
@@ -0,0 +1,551 @@
+use super::ProfileStore;
+use crate::charset::Charset;
+use crate::errors::Error;
+use crate::profile::backup::reader::BackupReader;
+use crate::profile::backup::writer::BackupWriter;
+use crate::profile::backup::{
+  icon_paths, open_backup_reader, open_backup_writer, restore_icons, BackupOptions, BackupSecrets,
+  RestoreOptions,
+};
+use crate::profile::charsets::{charset_usage, CharsetItem, CharsetUsage};
+use crate::profile::keys::{
+  field_index_text, url_index_text, KeyData, KeyItem, KeyKind, KeySecretItem, SearchQuery,
+};
+use crate::profile::search_query::{
+  Comparison, SearchExpr, SearchField, SearchFilter, SearchTerm, TermKind, SECONDS_PER_DAY,
+};
+use crate::profile::utils::text_fold::fold_text;
+use crate::profile::utils::timestamp::get_unix_timestamp;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use validator::Validate;
+
+/// Profile store without a database file. Contents are lost when the store is dropped.
+///
+/// Only keys, stored secrets and charsets are kept, backups of the store have no key history,
+/// rotation, collection or template entries and these sections are skipped on restore. Search
+/// matches whole words after folding case and diacritics, returns the keys in insertion order and
+/// has no typo tolerant fallback.
+pub struct MemoryProfileStore {
+  state: RwLock<MemoryProfile>,
+}
+
+struct MemoryProfile {
+  keys: BTreeMap<i64, KeyItem>,
+  secrets: HashMap<i64, String>,
+  charsets: Vec<CharsetItem>,
+  next_key_id: i64,
+}
+
+/// Charsets seeded by the `01_initial` and `02_unambiguous_charsets` migrations. The store tests
+/// compare them with a new profile database.
+const DEFAULT_CHARSETS: [(&str, &str, Option<&str>); 6] = [
+  ("Alpha Numeric", "a..zA..Z0..9", None),
+  ("Numeric", "0..9", None),
+  ("Alpha", "a..zA..Z", None),
+  (
+    "Alpha Numeric (unambiguous)",
+    "a..km..zA..HJ..NP..RT..Z2..46..9",
+    Some("Alpha Numeric without 0/O, 1/l/I and 5/S."),
+  ),
+  (
+    "Numeric (unambiguous)",
+    "2..46..9",
+    Some("Numeric without 0, 1 and 5."),
+  ),
+  (
+    "Alpha (unambiguous)",
+    "a..km..zA..HJ..NP..RT..Z",
+    Some("Alpha without O, l/I and S."),
+  ),
+];
+
+impl MemoryProfileStore {
+  /// Creates an empty store with the default charsets of a new profile database.
+  pub fn new() -> Self {
+    let charsets = DEFAULT_CHARSETS
+      .into_iter()
+      .map(|(name, charset, description)| CharsetItem {
+        name: String::from(name),
+        charset: String::from(charset),
+        description: description.map(String::from),
+      })
+      .collect();
+
+    Self {
+      state: RwLock::new(MemoryProfile {
+        keys: BTreeMap::new(),
+        secrets: HashMap::new(),
+        charsets,
+        next_key_id: 1,
+      }),
+    }
+  }
+
+  fn read(&self) -> RwLockReadGuard<'_, MemoryProfile> {
+    self.state.read().unwrap_or_else(PoisonError::into_inner)
+  }
+
+  fn write(&self) -> RwLockWriteGuard<'_, MemoryProfile> {
+    self.state.write().unwrap_or_else(PoisonError::into_inner)
+  }
+}
+
+impl Default for MemoryProfileStore {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl MemoryProfile {
+  /// Same rules as [crate::profile::ProfileDB] secrets, `None` keeps the existing secret of a
+  /// stored key.
+  fn replace_secret(&mut self, key_id: i64, kind: KeyKind, secret: Option<String>) {
+    match (kind, secret) {
+      (KeyKind::Stored, Some(secret)) => {
+        self.secrets.insert(key_id, secret);
+      }
+      (KeyKind::Stored, None) => {}
+      (KeyKind::Derived, _) => {
+        self.secrets.remove(&key_id);
+      }
+    }
+  }
+}
+
+impl ProfileStore for MemoryProfileStore {
+  async fn get_key_by_id(&self, key_id: i64) -> Result<Option<KeyItem>, Error> {
+    Ok(self.read().keys.get(&key_id).cloned())
+  }
+
+  async fn get_keys(&self, pinned_only: bool) -> Result<Vec<KeyItem>, Error> {
+    let keys = self
+      .read()
+      .keys
+      .values()
+      .filter(|key| key.deleted_at.is_none() && (!pinned_only || key.pinned))
+      .cloned()
+      .collect();
+
+    Ok(keys)
+  }
+
+  async fn search_keys(&self, search_query: SearchQuery) -> Result<Vec<KeyItem>, Error> {
+    let state = self.read();
+    let keys = state
+      .keys
+      .values()
+      .filter(|key| key.deleted_at.is_none())
+      .filter(|key| match &search_query.expr {
+        Some(expr) => matches_expr(expr, &SearchDocument::new(key), key, &state.charsets),
+        None => true,
+      })
+      .cloned()
+      .collect();
+
+    Ok(keys)
+  }
+
+  async fn insert_key(&self, item: KeyData) -> Result<i64, Error> {
+    item.validate_all()?;
+
+    if item.kind == KeyKind::Stored && item.secret.is_none() {
+      return Err(KeyData::missing_secret().into());
+    }
+
+    let now = get_unix_timestamp()?;
+    let mut state = self.write();
+    let key_id = state.next_key_id;
+    state.next_key_id += 1;
+    state.keys.insert(
+      key_id,
+      KeyItem {
+        id: key_id,
+        pinned: false,
+        target_size: item.target_size,
+        revision: item.revision,
+        charset: item.charset,
+        domain: item.domain,
+        username: item.username,
+        notes: item.notes,
+        created_at: now,
+        custom_icon: item.custom_icon,
+        version: item.version,
+        exclude_lookalikes: item.exclude_lookalikes,
+        last_used_at: None,
+        use_count: 0,
+        updated_at: None,
+        deleted_at: None,
+        rotation_days: None,
+        collection_id: None,
+        kind: item.kind,
+        tags: item.tags,
+        urls: item.urls,
+        fields: item.fields,
+      },
+    );
+    state.replace_secret(key_id, item.kind, item.secret);
+
+    Ok(key_id)
+  }
+
+  async fn update_key(&self, key_id: i64, item: KeyData) -> Result<(), Error> {
+    item.validate_all()?;
+
+    let now = get_unix_timestamp()?;
+    let mut state = self.write();
+    let Some(key) = state.keys.get_mut(&key_id) else {
+      return Err(Error::KeyNotFound { key_id });
+    };
+
+    if item.kind == KeyKind::Stored && key.kind != KeyKind::Stored && item.secret.is_none() {
+      return Err(KeyData::missing_secret().into());
+    }
+
+    key.target_size = item.target_size;
+    key.revision = item.revision;
+    key.charset = item.charset;
+    key.domain = item.domain;
+    key.username = item.username;
+    key.notes = item.notes;
+    key.custom_icon = item.custom_icon;
+    key.version = item.version;
+    key.exclude_lookalikes = item.exclude_lookalikes;
+    key.kind = item.kind;
+    key.tags = item.tags;
+    key.urls = item.urls;
+    key.fields = item.fields;
+    key.updated_at = Some(now);
+    state.replace_secret(key_id, item.kind, item.secret);
+
+    Ok(())
+  }
+
+  async fn delete_key(&self, key_id: i64) -> Result<bool, Error> {
+    let now = get_unix_timestamp()?;
+
+    match self.write().keys.get_mut(&key_id) {
+      Some(key) if key.deleted_at.is_none() => {
+        key.deleted_at = Some(now);
+        Ok(true)
+      }
+      _ => Ok(false),
+    }
+  }
+
+  async fn update_pin_status(&self, key_id: i64, pin_status: bool) -> Result<(), Error> {
+    match self.write().keys.get_mut(&key_id) {
+      Some(key) => {
+        key.pinned = pin_status;
+        Ok(())
+      }
+      None => Err(Error::KeyNotFound { key_id }),
+    }
+  }
+
+  async fn get_key_secret(&self, key_id: i64) -> Result<Option<String>, Error> {
+    Ok(self.read().secrets.get(&key_id).cloned())
+  }
+
+  async fn get_charsets(&self) -> Result<Vec<CharsetItem>, Error> {
+    Ok(self.read().charsets.clone())
+  }
+
+  async fn get_charset_usage(&self) -> Result<Vec<CharsetUsage>, Error> {
+    let keys = self.get_keys(false).await?;
+
+    Ok(charset_usage(&self.read().charsets, &keys))
+  }
+
+  async fn insert_charset(&self, item: CharsetItem) -> Result<String, Error> {
+    item.validate()?;
+
+    let charset = Charset::new(&item.charset)?;
+    let mut state = self.write();
+
+    if state
+      .charsets
+      .iter()
+      .any(|e| e.name.eq_ignore_ascii_case(&item.name))
+    {
+      return Err(Error::DuplicateCharsetName(item.name));
+    }
+
+    if let Some(duplicate) = state
+      .charsets
+      .iter()
+      .find(|e| Charset::new(&e.charset).is_ok_and(|existing| existing == charset))
+    {
+      return Err(Error::DuplicateCharset(duplicate.name.clone()));
+    }
+
+    let name = item.name.clone();
+    state.charsets.push(item);
+
+    Ok(name)
+  }
+
+  async fn update_charset(&self, item: CharsetItem) -> Result<(), Error> {
+    item.validate()?;
+
+    let charset = Charset::new(&item.charset)?;
+    let mut state = self.write();
+
+    if let Some(duplicate) = state.charsets.iter().find(|e| {
+      !e.name.eq_ignore_ascii_case(&item.name)
+        && Charset::new(&e.charset).is_ok_and(|existing| existing == charset)
+    }) {
+      return Err(Error::DuplicateCharset(duplicate.name.clone()));
+    }
+
+    let existing = state
+      .charsets
+      .iter_mut()
+      .find(|e| e.name.eq_ignore_ascii_case(&item.name))
+      .ok_or_else(|| Error::CharsetNotFound(item.name.clone()))?;
+    existing.charset = item.charset;
+    existing.description = item.description;
+
+    Ok(())
+  }
+
+  async fn rename_charset(&self, name: &str, new_name: &str) -> Result<(), Error> {
+    let mut state = self.write();
+    let position = state
+      .charsets
+      .iter()
+      .position(|e| e.name.eq_ignore_ascii_case(name))
+      .ok_or_else(|| Error::CharsetNotFound(String::from(name)))?;
+    let renamed = CharsetItem {
+      name: String::from(new_name),
+      ..state.charsets[position].clone()
+    };
+    renamed.validate()?;
+
+    if !name.eq_ignore_ascii_case(new_name)
+      && state
+        .charsets
+        .iter()
+        .any(|e| e.name.eq_ignore_ascii_case(new_name))
+    {
+      return Err(Error::DuplicateCharsetName(renamed.name));
+    }
+
+    state.charsets[position] = renamed;
+
+    Ok(())
+  }
+
+  async fn delete_charset(&self, name: &str, force: bool) -> Result<u64, Error> {
+    if !force {
+      let keys = self.get_keys(false).await?;
+      let state = self.read();
+      let charsets: Vec<CharsetItem> = state
+        .charsets
+        .iter()
+        .filter(|e| e.name.eq_ignore_ascii_case(name))
+        .cloned()
+        .collect();
+
+      if let Some(usage) = charset_usage(&charsets, &keys).first() {
+        if !usage.key_ids.is_empty() {
+          return Err(Error::CharsetInUse {
+            name: String::from(name),
+            key_count: usage.key_ids.len(),
+          });
+        }
+      }
+    }
+
+    let mut state = self.write();
+    let count = state.charsets.len();
+    state
+      .charsets
+      .retain(|e| !e.name.eq_ignore_ascii_case(name));
+
+    Ok((count - state.charsets.len()) as u64)
+  }
+
+  async fn backup(&self, options: BackupOptions) -> Result<(), Error> {
+    let BackupOptions {
+      content_dir,
+      target,
+      sign_key,
+    } = options;
+
+    let (keys, secrets, charsets) = {
+      let state = self.read();
+      let keys: Vec<KeyItem> = state.keys.values().cloned().collect();
+      let secrets = BackupSecrets {
+        keys: state
+          .secrets
+          .iter()
+          .map(|(key_id, secret)| KeySecretItem {
+            key_id: *key_id,
+            secret: secret.clone(),
+          })
+          .collect(),
+        history: Vec::new(),
+      };
+
+      (keys, secrets, state.charsets.clone())
+    };
+    let icons = icon_paths(&keys, &content_dir);
+
+    let mut backup_writer = open_backup_writer(&target, &sign_key)?;
+    backup_writer.write_keys(&keys)?;
+    backup_writer.write_key_history(&[])?;
+    backup_writer.write_key_rotations(&[])?;
+    backup_writer.write_key_secrets(&secrets)?;
+    backup_writer.write_rotation_policies(&[])?;
+    backup_writer.write_charsets(&charsets)?;
+    backup_writer.write_collections(&[])?;
+    backup_writer.write_templates(&[])?;
+    backup_writer.write_icons(&icons)?;
+    backup_writer.finish()?;
+
+    Ok(())
+  }
+
+  async fn restore(&self, options: RestoreOptions) -> Result<(), Error> {
+    let RestoreOptions {
+      target,
+      content_dir,
+      sign_key,
+    } = options;
+
+    if !content_dir.is_dir() {
+      return Err(Error::backup("Content target isn't directory.").with_path(content_dir));
+    }
+
+    let mut backup_reader = open_backup_reader(&target)?;
+    let keys = backup_reader.keys()?;
+    let secrets = backup_reader.key_secrets(&sign_key)?;
+    let charsets = backup_reader.charsets()?;
+    let icons = backup_reader.icons()?;
+
+    {
+      let mut state = self.write();
+      state.next_key_id = keys.iter().map(|key| key.id + 1).max().unwrap_or(1);
+      state.keys = keys.into_iter().map(|key| (key.id, key)).collect();
+      state.secrets = secrets
+        .keys
+        .into_iter()
+        .map(|item| (item.key_id, item.secret))
+        .collect();
+      state.charsets = charsets;
+    }
+
+    restore_icons(&mut backup_reader, &icons, &content_dir)
+  }
+}
+
+/// Folded words of the searchable key fields, same columns as the search index.
+struct SearchDocument {
+  domain: Vec<String>,
+  username: Vec<String>,
+  notes: Vec<String>,
+  tags: Vec<String>,
+  urls: Vec<String>,
+  fields: Vec<String>,
+}
+
+impl SearchDocument {
+  fn new(key: &KeyItem) -> Self {
+    Self {
+      domain: words(&key.domain),
+      username: words(&key.username),
+      notes: key.notes.as_deref().map(words).unwrap_or_default(),
+      tags: words(&key.tags.join(' ')),
+      urls: words(&url_index_text(&key.urls)),
+      fields: words(&field_index_text(&key.fields)),
+    }
+  }
+
+  fn columns(&self, field: Option<SearchField>) -> Vec<&[String]> {
+    match field {
+      Some(SearchField::Domain) => vec![&self.domain],
+      Some(SearchField::Username) => vec![&self.username],
+      Some(SearchField::Notes) => vec![&self.notes],
+      Some(SearchField::Tag) => vec![&self.tags],
+      None => vec![
+        &self.domain,
+        &self.username,
+        &self.notes,
+        &self.tags,
+        &self.urls,
+        &self.fields,
+      ],
+    }
+  }
+}
+
+fn words(text: &str) -> Vec<String> {
+  fold_text(text)
+    .split(|ch: char| !ch.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(String::from)
+    .collect()
+}
+
+fn matches_expr(
+  expr: &SearchExpr,
+  document: &SearchDocument,
+  key: &KeyItem,
+  charsets: &[CharsetItem],
+) -> bool {
+  match expr {
+    SearchExpr::And(exprs) => exprs
+      .iter()
+      .all(|expr| matches_expr(expr, document, key, charsets)),
+    SearchExpr::Or(exprs) => exprs
+      .iter()
+      .any(|expr| matches_expr(expr, document, key, charsets)),
+    SearchExpr::Not(inner) => !matches_expr(inner, document, key, charsets),
+    SearchExpr::Term(term) => matches_term(term, document),
+    SearchExpr::Filter(SearchFilter::Pinned(pinned)) => key.pinned == *pinned,
+    SearchExpr::Filter(SearchFilter::Created(comparison, day_start)) => {
+      let day_end = day_start + SECONDS_PER_DAY;
+
+      match comparison {
+        Comparison::Lt => key.created_at < *day_start,
+        Comparison::Le => key.created_at < day_end,
+        Comparison::Ge => key.created_at >= *day_start,
+        Comparison::Gt => key.created_at >= day_end,
+        Comparison::Eq => key.created_at >= *day_start && key.created_at < day_end,
+      }
+    }
+    SearchExpr::Filter(SearchFilter::Charset(charset)) => {
+      key.charset == *charset
+        || charsets
+          .iter()
+          .any(|item| item.name.eq_ignore_ascii_case(charset) && item.charset == key.charset)
+    }
+  }
+}
+
+/// Matches the term words as consecutive words of a column, the last word is a prefix for prefix
+/// terms.
+fn matches_term(term: &SearchTerm, document: &SearchDocument) -> bool {
+  let term_words = words(&term.text);
+
+  if term_words.is_empty() {
+    return false;
+  }
+
+  document.columns(term.field).into_iter().any(|column| {
+    column.windows(term_words.len()).any(|window| {
+      window
+        .iter()
+        .zip(term_words.iter())
+        .enumerate()
+        .all(|(idx, (word, term_word))| {
+          if term.kind == TermKind::Prefix && idx == term_words.len() - 1 {
+            word.starts_with(term_word.as_str())
+          } else {
+            word == term_word
+          }
+        })
+    })
+  })
+}
@@ -0,0 +1,164 @@
+use crate::errors::Error;
+use crate::profile::events::ProfileEvent;
+use crate::profile::ProfileDB;
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, Connection, FromRow, QueryBuilder, Sqlite, SqliteConnection};
+use validator::{ValidationError, ValidationErrors};
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct TagItem {
+  pub name: String,
+  /// Number of keys using the tag, excluding the keys in trash.
+  pub key_count: i64,
+}
+
+impl ProfileDB {
+  /// Returns all tags in use with their key counts, ordered by name.
+  pub async fn list_tags(&self) -> Result<Vec<TagItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as!(
+      TagItem,
+      r#"SELECT tags.name as "name!", COUNT(tags.key_id) as "key_count!: i64"
+      FROM tags
+      INNER JOIN keys ON keys.id = tags.key_id
+      WHERE keys.deleted_at IS NULL
+      GROUP BY tags.name
+      ORDER BY tags.name"#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  /// Renames the tag on every key. If a key already has the new name, the tags are merged. Returns
+  /// the number of updated keys.
+  pub async fn rename_tag(&self, name: &str, new_name: &str) -> Result<u64, Error> {
+    self.merge_tags(&[name], new_name).await
+  }
+
+  /// Replaces the source tags with the target tag on every key, including the keys in trash.
+  /// Rotation policies of the source tags move to the target tag unless it already has one.
+  /// Returns the number of updated keys.
+  pub async fn merge_tags(&self, sources: &[&str], target: &str) -> Result<u64, Error> {
+    let target = target.trim();
+
+    let mut errors = ValidationErrors::new();
+
+    if target.is_empty() {
+      errors.add("target", required_length_error());
+    }
+
+    if sources.is_empty() {
+      errors.add("sources", required_length_error());
+    }
+
+    if !errors.is_empty() {
+      return Err(errors.into());
+    }
+
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+    let key_ids = Self::get_tagged_key_ids(&mut transaction, sources).await?;
+
+    for source in sources {
+      query!(
+        "UPDATE OR IGNORE tags SET name = ? WHERE name = ?",
+        target,
+        source
+      )
+      .execute(&mut *transaction)
+      .await?;
+
+      // Keys having both tags keep the source tag after the update.
+      query!(
+        "DELETE FROM tags WHERE name = ? AND name != ?",
+        source,
+        target
+      )
+      .execute(&mut *transaction)
+      .await?;
+
+      query!(
+        "UPDATE OR IGNORE tag_rotation_policies SET tag = ? WHERE tag = ? COLLATE NOCASE",
+        target,
+        source
+      )
+      .execute(&mut *transaction)
+      .await?;
+
+      query!(
+        "DELETE FROM tag_rotation_policies WHERE tag = ? COLLATE NOCASE AND tag != ?",
+        source,
+        target
+      )
+      .execute(&mut *transaction)
+      .await?;
+    }
+
+    for key_id in key_ids.iter() {
+      Self::reindex_key(&mut transaction, *key_id).await?;
+    }
+
+    transaction.commit().await?;
+
+    for key_id in key_ids.iter() {
+      self.notify(ProfileEvent::KeyUpdated { key_id: *key_id });
+    }
+
+    Ok(key_ids.len() as u64)
+  }
+
+  /// Removes the tag and its rotation policy from every key. Returns the number of updated keys.
+  pub async fn delete_tag(&self, name: &str) -> Result<u64, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+    let key_ids = Self::get_tagged_key_ids(&mut transaction, &[name]).await?;
+
+    query!("DELETE FROM tags WHERE name = ?", name)
+      .execute(&mut *transaction)
+      .await?;
+
+    query!(
+      "DELETE FROM tag_rotation_policies WHERE tag = ? COLLATE NOCASE",
+      name
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    for key_id in key_ids.iter() {
+      Self::reindex_key(&mut transaction, *key_id).await?;
+    }
+
+    transaction.commit().await?;
+
+    for key_id in key_ids.iter() {
+      self.notify(ProfileEvent::KeyUpdated { key_id: *key_id });
+    }
+
+    Ok(key_ids.len() as u64)
+  }
+
+  async fn get_tagged_key_ids(
+    conn: &mut SqliteConnection,
+    names: &[&str],
+  ) -> Result<Vec<i64>, Error> {
+    let mut query_builder: QueryBuilder<Sqlite> =
+      QueryBuilder::new("SELECT DISTINCT key_id FROM tags WHERE name IN (");
+    let mut separated = query_builder.separated(", ");
+    for name in names {
+      separated.push_bind(*name);
+    }
+    separated.push_unseparated(") ORDER BY key_id");
+
+    let key_ids: Vec<(i64,)> = query_builder.build_query_as().fetch_all(&mut *conn).await?;
+
+    Ok(key_ids.into_iter().map(|(key_id,)| key_id).collect())
+  }
+}
+
+fn required_length_error() -> ValidationError {
+  let mut error = ValidationError::new("length");
+  error.add_param("min".into(), &1);
+  error
+}
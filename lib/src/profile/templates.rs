@@ -0,0 +1,262 @@
+use crate::charset::validate_charset;
+use crate::errors::Error;
+use crate::profile::events::ProfileEvent;
+use crate::profile::keys::{KeyData, KeyField, KeyKind, KeyUrl, KeyWriteOptions, UniquenessCheck};
+use crate::profile::utils::tag_list::TagList;
+use crate::profile::utils::timestamp::get_unix_timestamp;
+use crate::profile::ProfileDB;
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, Connection, FromRow, Sqlite, SqliteConnection};
+use validator::Validate;
+
+/// Named preset for the password parameters of new keys.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct KeyTemplateItem {
+  pub id: i64,
+  pub name: String,
+  pub charset: String,
+  pub target_size: i64,
+  pub version: String,
+  #[serde(default)]
+  pub exclude_lookalikes: bool,
+  /// Uniqueness check applied to the keys created from the template.
+  #[serde(default)]
+  pub uniqueness: UniquenessCheck,
+  /// Tags added to the keys created from the template.
+  #[serde(default)]
+  #[sqlx(json)]
+  pub tags: TagList,
+  /// Rotation interval of the keys created from the template.
+  #[serde(default)]
+  pub rotation_days: Option<i64>,
+  pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct KeyTemplateData {
+  #[validate(length(min = 1))]
+  pub name: String,
+  #[validate(length(min = 1), custom = "validate_charset")]
+  pub charset: String,
+  #[validate(range(min = 1, max = 64))]
+  pub target_size: i64,
+  #[validate(length(min = 1))]
+  pub version: String,
+  #[serde(default)]
+  pub exclude_lookalikes: bool,
+  #[serde(default)]
+  pub uniqueness: UniquenessCheck,
+  #[serde(default)]
+  pub tags: TagList,
+  #[serde(default)]
+  #[validate(range(min = 1))]
+  pub rotation_days: Option<i64>,
+}
+
+/// Account details of a key created from a template. Password parameters come from the template.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateKeyData {
+  pub domain: String,
+  pub username: String,
+  pub notes: Option<String>,
+  pub custom_icon: Option<String>,
+  /// Starts at 1 like [KeyData::default], so an omitted revision generates the same password as a
+  /// key created by hand.
+  #[serde(default = "default_revision")]
+  pub revision: i64,
+  /// Tags added along with the template tags.
+  #[serde(default)]
+  pub tags: TagList,
+  #[serde(default)]
+  pub urls: Vec<KeyUrl>,
+  #[serde(default)]
+  pub fields: Vec<KeyField>,
+}
+
+fn default_revision() -> i64 {
+  1
+}
+
+impl ProfileDB {
+  /// Returns all templates ordered by name.
+  pub async fn get_templates(&self) -> Result<Vec<KeyTemplateItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query_as::<Sqlite, KeyTemplateItem>(
+      "SELECT id, name, charset, target_size, version, exclude_lookalikes, uniqueness, tags, rotation_days, created_at
+      FROM key_templates
+      ORDER BY name",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  pub async fn get_template(&self, template_id: i64) -> Result<Option<KeyTemplateItem>, Error> {
+    let mut conn = self.pool.acquire().await?;
+    Self::fetch_template(&mut conn, template_id).await
+  }
+
+  pub async fn insert_template(&self, item: KeyTemplateData) -> Result<i64, Error> {
+    item.validate()?;
+
+    let name = item.name.trim();
+    let tags = serde_json::to_string(&item.tags)?;
+    let now = get_unix_timestamp()?;
+    let mut conn = self.pool.acquire().await?;
+    Self::ensure_unique_template_name(&mut conn, name, None).await?;
+
+    let result = query!(
+      "INSERT INTO key_templates
+        (name, charset, target_size, version, exclude_lookalikes, uniqueness, tags, rotation_days, created_at) VALUES
+        (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+      name,
+      item.charset,
+      item.target_size,
+      item.version,
+      item.exclude_lookalikes,
+      item.uniqueness,
+      tags,
+      item.rotation_days,
+      now
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+  }
+
+  /// Updates the template. Keys created from the template keep their parameters.
+  pub async fn update_template(
+    &self,
+    template_id: i64,
+    item: KeyTemplateData,
+  ) -> Result<(), Error> {
+    item.validate()?;
+
+    let name = item.name.trim();
+    let tags = serde_json::to_string(&item.tags)?;
+    let mut conn = self.pool.acquire().await?;
+    Self::ensure_unique_template_name(&mut conn, name, Some(template_id)).await?;
+
+    let result = query!(
+      "UPDATE key_templates SET
+        (name, charset, target_size, version, exclude_lookalikes, uniqueness, tags, rotation_days) =
+        (?, ?, ?, ?, ?, ?, ?, ?)
+      WHERE id = ?",
+      name,
+      item.charset,
+      item.target_size,
+      item.version,
+      item.exclude_lookalikes,
+      item.uniqueness,
+      tags,
+      item.rotation_days,
+      template_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if result.rows_affected() == 0 {
+      return Err(Error::TemplateNotFound { template_id });
+    }
+
+    Ok(())
+  }
+
+  /// Deletes the template. Keys created from the template are not changed.
+  pub async fn delete_template(&self, template_id: i64) -> Result<bool, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let result = query!("DELETE FROM key_templates WHERE id = ?", template_id)
+      .execute(&mut *conn)
+      .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  /// Creates a key with the password parameters, tags, uniqueness check and rotation interval of
+  /// the template.
+  pub async fn insert_key_from_template(
+    &self,
+    template_id: i64,
+    item: TemplateKeyData,
+  ) -> Result<i64, Error> {
+    let mut conn = self.pool.acquire().await?;
+    let mut transaction = conn.begin().await?;
+    let template = Self::fetch_template(&mut transaction, template_id)
+      .await?
+      .ok_or(Error::TemplateNotFound { template_id })?;
+
+    let key_data = KeyData {
+      target_size: template.target_size,
+      revision: item.revision,
+      charset: template.charset,
+      domain: item.domain,
+      username: item.username,
+      notes: item.notes,
+      custom_icon: item.custom_icon,
+      version: template.version,
+      tags: template.tags.union(&item.tags),
+      exclude_lookalikes: template.exclude_lookalikes,
+      urls: item.urls,
+      fields: item.fields,
+      kind: KeyKind::Derived,
+      secret: None,
+    };
+    let options = KeyWriteOptions {
+      uniqueness: template.uniqueness,
+    };
+
+    let key_id = Self::insert_key_rows(&mut transaction, key_data, options).await?;
+
+    if let Some(rotation_days) = template.rotation_days {
+      query!(
+        "UPDATE keys SET rotation_days = ? WHERE keys.id = ?",
+        rotation_days,
+        key_id
+      )
+      .execute(&mut *transaction)
+      .await?;
+    }
+
+    transaction.commit().await?;
+    self.notify(ProfileEvent::KeyCreated { key_id });
+
+    Ok(key_id)
+  }
+
+  async fn fetch_template(
+    conn: &mut SqliteConnection,
+    template_id: i64,
+  ) -> Result<Option<KeyTemplateItem>, Error> {
+    let result = query_as::<Sqlite, KeyTemplateItem>(
+      "SELECT id, name, charset, target_size, version, exclude_lookalikes, uniqueness, tags, rotation_days, created_at
+      FROM key_templates
+      WHERE id = ?",
+    )
+    .bind(template_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+
+  async fn ensure_unique_template_name(
+    conn: &mut SqliteConnection,
+    name: &str,
+    exclude_id: Option<i64>,
+  ) -> Result<(), Error> {
+    let duplicate = query!(
+      "SELECT id FROM key_templates WHERE name = ? AND id != ifnull(?, 0)",
+      name,
+      exclude_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    match duplicate {
+      Some(_) => Err(Error::DuplicateTemplate(String::from(name))),
+      None => Ok(()),
+    }
+  }
+}
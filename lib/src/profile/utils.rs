@@ -1,2 +1,4 @@
+pub(super) mod domain;
 pub mod tag_list;
+pub mod text_fold;
 pub(super) mod timestamp;
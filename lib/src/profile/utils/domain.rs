@@ -0,0 +1,54 @@
+use publicsuffix::{List, Psl};
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use url::Url;
+
+/// Copy of https://publicsuffix.org/list/public_suffix_list.dat including the private domains, so
+/// hosts such as `user.github.io` are not reduced to `github.io`. Update it by replacing the file
+/// with the latest list.
+const PUBLIC_SUFFIX_LIST: &str = include_str!("public_suffix_list.dat");
+
+static SUFFIX_LIST: OnceLock<List> = OnceLock::new();
+
+/// Parses a URL, host name or app id. Values without a scheme such as `mail.google.com/inbox` are
+/// parsed as `https` URLs. Returns `None` if the value has no host.
+pub fn parse_url(value: &str) -> Option<Url> {
+  let value = value.trim();
+
+  Url::parse(value)
+    .ok()
+    .filter(|url| url.has_host())
+    .or_else(|| Url::parse(&format!("https://{}", value)).ok())
+    .filter(|url| url.has_host())
+}
+
+/// Lowercase host of the URL, host name or app id.
+pub fn parse_host(value: &str) -> Option<String> {
+  parse_url(value)
+    .and_then(|url| url.host_str().map(str::to_lowercase))
+    .filter(|host| !host.is_empty())
+}
+
+/// Reduces the host to its registrable domain with the public suffix list, e.g.
+/// `accounts.google.co.uk` becomes `google.co.uk`. Hosts without a registrable domain such as
+/// `localhost`, IP addresses and bare suffixes are returned as is.
+pub fn registrable_domain(host: &str) -> String {
+  let host = host.trim_end_matches('.');
+
+  if host.starts_with('[') || host.parse::<IpAddr>().is_ok() {
+    return host.to_lowercase();
+  }
+
+  // An empty list would silently reduce every host to its last label, the bundled list is parsed
+  // by the URL tests.
+  let list = SUFFIX_LIST.get_or_init(|| {
+    PUBLIC_SUFFIX_LIST
+      .parse()
+      .expect("bundled public suffix list must be valid")
+  });
+
+  match list.domain(host.as_bytes()) {
+    Some(domain) => String::from_utf8_lossy(domain.as_bytes()).to_lowercase(),
+    None => host.to_lowercase(),
+  }
+}
@@ -6,6 +6,7 @@ use serde::{
 use std::collections::{hash_set::Iter, HashSet};
 use std::fmt::{Debug, Formatter};
 
+#[derive(Clone)]
 pub struct TagList {
   internal: HashSet<Box<str>>,
 }
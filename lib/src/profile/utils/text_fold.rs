@@ -0,0 +1,40 @@
+/// Lowercases the text and removes diacritics, e.g. `Şişli Müzesi` becomes `sisli muzesi`.
+///
+/// Folding covers Latin letters used in European languages including Turkish dotted and dotless
+/// `i`. Other scripts are only lowercased.
+pub fn fold_text(text: &str) -> String {
+  let mut folded = String::with_capacity(text.len());
+
+  for symbol in text.chars().flat_map(char::to_lowercase) {
+    match symbol {
+      // Combining diacritical marks, e.g. the dot of lowercase 'İ'.
+      '\u{0300}'..='\u{036F}' => {}
+      'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => folded.push('a'),
+      'æ' => folded.push_str("ae"),
+      'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => folded.push('c'),
+      'ď' | 'đ' | 'ð' => folded.push('d'),
+      'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => folded.push('e'),
+      'ĝ' | 'ğ' | 'ġ' | 'ģ' => folded.push('g'),
+      'ĥ' | 'ħ' => folded.push('h'),
+      'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => folded.push('i'),
+      'ĵ' => folded.push('j'),
+      'ķ' => folded.push('k'),
+      'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => folded.push('l'),
+      'ñ' | 'ń' | 'ņ' | 'ň' => folded.push('n'),
+      'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => folded.push('o'),
+      'œ' => folded.push_str("oe"),
+      'ŕ' | 'ŗ' | 'ř' => folded.push('r'),
+      'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => folded.push('s'),
+      'ß' => folded.push_str("ss"),
+      'ţ' | 'ť' | 'ŧ' | 'ț' => folded.push('t'),
+      'þ' => folded.push_str("th"),
+      'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => folded.push('u'),
+      'ŵ' => folded.push('w'),
+      'ý' | 'ÿ' | 'ŷ' => folded.push('y'),
+      'ź' | 'ż' | 'ž' => folded.push('z'),
+      symbol => folded.push(symbol),
+    }
+  }
+
+  folded
+}
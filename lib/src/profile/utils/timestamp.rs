@@ -14,12 +14,12 @@ pub(crate) fn get_unix_timestamp() -> Result<i64, Error> {
 
 impl From<SystemTimeError> for Error {
   fn from(value: SystemTimeError) -> Self {
-    Error::InvalidTime(value.to_string())
+    Error::InvalidTime(Box::new(value))
   }
 }
 
 impl From<TryFromIntError> for Error {
   fn from(value: TryFromIntError) -> Self {
-    Error::InvalidTime(value.to_string())
+    Error::InvalidTime(Box::new(value))
   }
 }
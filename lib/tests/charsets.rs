@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod test {
-  use keywich_lib::charset::Charset;
+  use keywich_lib::charset::analysis::{analyze_charset, CharsetClasses, CharsetRange};
+  use keywich_lib::charset::parser::{ParseError, ParseErrorReason};
+  use keywich_lib::charset::{Charset, CharsetOptions};
+  use keywich_lib::errors::Error;
 
   #[test]
   fn valid_alpha_numeric() {
@@ -37,4 +40,230 @@ mod test {
    let charset = Charset::new("a..da..dabcd").unwrap();
     assert_eq!("abcd", &charset.to_string());
   }
+
+  #[test]
+  fn exclude_lookalikes() {
+    let options = CharsetOptions {
+      exclude_lookalikes: true,
+    };
+    let charset = Charset::with_options("a..zA..Z0..9", options).unwrap();
+    let symbols = charset.to_string();
+
+    assert_eq!(62 - 7, symbols.len());
+    assert!(!symbols.contains(['0', 'O', '1', 'l', 'I', '5', 'S']));
+  }
+
+  #[test]
+  fn unambiguous_preset_has_no_lookalikes() {
+    let preset = Charset::new("a..km..zA..HJ..NP..RT..Z2..46..9").unwrap();
+    let filtered = Charset::with_options(
+      "a..zA..Z0..9",
+      CharsetOptions {
+        exclude_lookalikes: true,
+      },
+    )
+    .unwrap();
+
+    assert_eq!(filtered.to_string(), preset.to_string());
+  }
+
+  #[test]
+  fn exclude_lookalikes_empty_result() {
+    let options = CharsetOptions {
+      exclude_lookalikes: true,
+    };
+    let result = Charset::with_options("0..1OlIS5", options);
+
+    assert!(matches!(result, Err(Error::EmptyCharset)));
+  }
+
+  #[test]
+  fn mixed_class_range_diagnostic() {
+    let result = Charset::new("a..zA..9");
+
+    if let Err(Error::ParserInvalidRange(err)) = result {
+      assert_eq!(
+        ParseError {
+          reason: ParseErrorReason::MixedClassRange,
+          token: "A..9".into(),
+          start: 4,
+          end: 8,
+          char_start: 4,
+          char_end: 8,
+          suggestion: Some("A..Z0..9".into()),
+        },
+        err
+      );
+    } else {
+      panic!("Mixed class range is accepted.");
+    }
+  }
+
+  #[test]
+  fn reversed_range_warning() {
+    // Reversed ranges add no symbols, keys created with them must keep their passwords.
+    let charset = Charset::new("ÄäÖz..a").unwrap();
+    assert_eq!("ÄÖä", &charset.to_string());
+
+    let analysis = analyze_charset("ÄäÖz..a").unwrap();
+    let warning = &analysis.warnings[0];
+
+    assert_eq!(1, analysis.warnings.len());
+    assert_eq!(ParseErrorReason::ReversedRange, warning.reason);
+    assert_eq!("z..a", &warning.token);
+    assert_eq!((6, 10), (warning.start, warning.end));
+    assert_eq!((3, 7), (warning.char_start, warning.char_end));
+    assert_eq!(Some("a..z"), warning.suggestion.as_deref());
+    assert!(analyze_charset("a..z").unwrap().warnings.is_empty());
+  }
+
+  #[test]
+  fn range_suggestions() {
+    let suggestion = |charset: &str| match Charset::new(charset) {
+      Err(Error::ParserInvalidRange(err)) => err.suggestion,
+      _ => panic!("Invalid range is accepted."),
+    };
+
+    assert_eq!(Some("a..zA..Z".into()), suggestion("a..Z"));
+    assert_eq!(Some("9a..c".into()), suggestion("9..c"));
+    assert_eq!(Some("!\"#$%".into()), suggestion("%..!"));
+    assert_eq!(Some("0.".into()), suggestion("a..z0.."));
+    assert_eq!(None, suggestion("ä..ö"));
+
+    for charset in ["a..Z", "9..c", "%..!", "a..z0.."] {
+      assert!(Charset::new(&suggestion(charset).unwrap()).is_ok());
+    }
+  }
+
+  #[test]
+  fn trailing_range_diagnostic() {
+    let result = Charset::new("a..z0..");
+
+    if let Err(Error::ParserInvalidRange(err)) = result {
+      assert_eq!(ParseErrorReason::TrailingRange, err.reason);
+      assert_eq!("0..", &err.token);
+      assert_eq!((4, 7), (err.start, err.end));
+    } else {
+      panic!("Trailing range is accepted.");
+    }
+  }
+
+  #[test]
+  fn unsupported_range_diagnostic() {
+    let result = Charset::new("!..~");
+
+    if let Err(Error::ParserInvalidRange(err)) = result {
+      assert_eq!(ParseErrorReason::UnsupportedRange, err.reason);
+    } else {
+      panic!("Symbol range is accepted.");
+    }
+  }
+
+  #[test]
+  fn canonical_form() {
+    let charset = Charset::new("zyxa..cA..B0..9_-!").unwrap();
+    assert_eq!("!-0..9AB_a..cx..z", &charset.canonical());
+  }
+
+  #[test]
+  fn canonical_form_reparse() {
+    let charset = Charset::new("-./,a..zA..Z0..9").unwrap();
+    let canonical = Charset::new(&charset.canonical()).unwrap();
+
+    assert_eq!(".,-/0..9A..Za..z", &charset.canonical());
+    assert_eq!(charset, canonical);
+  }
+
+  #[test]
+  fn canonical_form_reparse_around_dot() {
+    let neighbours = ['+', ',', '-', '.', '/', '0', '1', '2', '9', 'a', 'b', 'c', 'z'];
+
+    for mask in 1u32..(1 << neighbours.len()) {
+      let symbols: String = neighbours
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| mask & (1 << idx) != 0)
+        .map(|(_, symbol)| *symbol)
+        .collect();
+      let charset = Charset::new(&symbols).unwrap();
+      let canonical = charset.canonical();
+
+      assert_eq!(
+        charset,
+        Charset::new(&canonical).unwrap(),
+        "{} is written as {}",
+        symbols,
+        canonical
+      );
+    }
+  }
+
+  #[test]
+  fn equality_across_spellings() {
+    use std::collections::HashSet;
+
+    let charsets = HashSet::from([
+      Charset::new("a..d").unwrap(),
+      Charset::new("dcba").unwrap(),
+      Charset::new("a..bc..d").unwrap(),
+    ]);
+
+    assert_eq!(1, charsets.len());
+    assert_ne!(Charset::new("a..d").unwrap(), Charset::new("a..e").unwrap());
+  }
+
+  #[test]
+  fn analyze() {
+    let analysis = Charset::new("a..zA..Z0..9_-").unwrap().analyze();
+
+    assert_eq!(64, analysis.size);
+    assert_eq!(6.0, analysis.entropy_per_symbol);
+    assert_eq!(
+      CharsetClasses {
+        lowercase: 26,
+        uppercase: 26,
+        numeric: 10,
+        symbol: 2,
+      },
+      analysis.classes
+    );
+    assert_eq!(
+      vec![
+        CharsetRange {
+          start: '-',
+          end: '-'
+        },
+        CharsetRange {
+          start: '0',
+          end: '9'
+        },
+        CharsetRange {
+          start: 'A',
+          end: 'Z'
+        },
+        CharsetRange {
+          start: '_',
+          end: '_'
+        },
+        CharsetRange {
+          start: 'a',
+          end: 'z'
+        },
+      ],
+      analysis.ranges
+    );
+    assert_eq!("-0..9A..Z_a..z", &analysis.canonical);
+  }
+
+  #[cfg(feature = "json")]
+  #[test]
+  fn serde_round_trip() {
+    let charset = Charset::new("dcba0..9").unwrap();
+    let json_text = serde_json::to_string(&charset).unwrap();
+    let parsed: Charset = serde_json::from_str(&json_text).unwrap();
+
+    assert_eq!("\"0..9a..d\"", &json_text);
+    assert_eq!(charset, parsed);
+    assert!(serde_json::from_str::<Charset>("\"z..a\"").is_err());
+  }
 }
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod test {
+  use keywich_lib::charset::Charset;
+  use keywich_lib::errors::Error;
+  use std::error::Error as StdError;
+
+  #[test]
+  fn stable_codes() {
+    assert_eq!("empty_charset", Error::EmptyCharset.code());
+    assert_eq!("key_not_found", Error::KeyNotFound { key_id: 1 }.code());
+    assert_eq!(
+      "invalid_charset_range",
+      Charset::new("a..").unwrap_err().code()
+    );
+  }
+
+  #[test]
+  fn source_chain() {
+    let io_err = std::io::Error::other("disk full");
+    let err = Error::InvalidJsonError(Box::new(io_err));
+
+    assert_eq!("disk full", err.source().unwrap().to_string());
+    assert!(Error::EmptyCharset.source().is_none());
+  }
+
+  #[cfg(feature = "json")]
+  #[test]
+  fn serialize_with_context() {
+    let value = serde_json::to_value(Error::KeyNotFound { key_id: 42 }).unwrap();
+
+    assert_eq!("key_not_found", value["code"]);
+    assert_eq!(42, value["key_id"]);
+    assert_eq!("Key 42 does not exist.", value["message"]);
+  }
+
+  #[cfg(feature = "json")]
+  #[test]
+  fn serialize_source_details() {
+    let io_err = std::io::Error::other("disk full");
+    let value = serde_json::to_value(Error::InvalidTime(Box::new(io_err))).unwrap();
+
+    assert_eq!("invalid_time", value["code"]);
+    assert_eq!("disk full", value["details"]);
+  }
+}
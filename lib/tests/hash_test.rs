@@ -1,8 +1,9 @@
 #[cfg(test)]
 mod test {
+  use keywich_lib::charset::CharsetOptions;
   use keywich_lib::errors::Error;
   use keywich_lib::hash::HashAlgorithm;
-  use keywich_lib::{generate_password, PasswordConfig};
+  use keywich_lib::{generate_password, generate_password_with, PasswordConfig};
 
   #[test]
   fn kwscrypt_basic_password_test() {
@@ -155,4 +156,28 @@ mod test {
 
     assert_eq!("$kw_scrypt$v=v1$rkyosiykoybz", &phc_text);
   }
+
+  #[test]
+  fn kwscrypt_exclude_lookalikes_test() {
+    let config = PasswordConfig {
+      charset: "a..zA..Z0..9",
+      target_len: 64,
+      password: "test",
+      username: "john",
+      domain: "acme",
+      revision: 1,
+    };
+
+    let pass = generate_password_with(
+      config,
+      HashAlgorithm::KwScryptV1,
+      CharsetOptions {
+        exclude_lookalikes: true,
+      },
+    )
+    .unwrap();
+
+    assert_eq!(64, pass.pass.len());
+    assert!(!pass.pass.contains(['0', 'O', '1', 'l', 'I', '5', 'S']));
+  }
 }
@@ -0,0 +1,158 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::errors::Error;
+  use keywich_lib::profile::keys::{KeyBulkAction, KeyData, SearchQuery};
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+
+  fn key_data(domain: &str, tags: &[&str]) -> KeyData {
+    KeyData {
+      domain: domain.into(),
+      username: "username".into(),
+      charset: "a..z0..9".into(),
+      tags: TagList::from(tags),
+      ..Default::default()
+    }
+  }
+
+  async fn insert_keys(profile_db: &ProfileDB) -> Vec<i64> {
+    let mut key_ids = Vec::new();
+
+    for domain in ["github.com", "gitlab.com", "example.org"] {
+      key_ids.push(
+        profile_db
+          .insert_key(key_data(domain, &["old"]))
+          .await
+          .unwrap(),
+      );
+    }
+
+    key_ids
+  }
+
+  #[tokio::test]
+  async fn bulk_tags() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_ids = insert_keys(&profile_db).await;
+    let targets = [key_ids[0], key_ids[1], 999];
+
+    let updated = profile_db
+      .bulk_update_keys(
+        &targets,
+        KeyBulkAction::AddTags(TagList::from(["work", "old"])),
+      )
+      .await
+      .unwrap();
+    assert_eq!(2, updated);
+
+    let updated = profile_db
+      .bulk_update_keys(&targets, KeyBulkAction::RemoveTags(TagList::from(["old"])))
+      .await
+      .unwrap();
+    assert_eq!(2, updated);
+
+    let key = profile_db.get_key_by_id(key_ids[0]).await.unwrap().unwrap();
+    assert_eq!("work", key.tags.join(' '));
+    let key = profile_db.get_key_by_id(key_ids[2]).await.unwrap().unwrap();
+    assert_eq!("old", key.tags.join(' '));
+
+    let result = profile_db
+      .search_keys(SearchQuery::new("tag:work"))
+      .await
+      .unwrap();
+    assert_eq!(2, result.len());
+    let result = profile_db
+      .search_keys(SearchQuery::new("tag:old"))
+      .await
+      .unwrap();
+    assert_eq!(
+      vec![key_ids[2]],
+      result.iter().map(|e| e.id).collect::<Vec<i64>>()
+    );
+  }
+
+  #[tokio::test]
+  async fn bulk_pin_and_delete() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_ids = insert_keys(&profile_db).await;
+
+    profile_db
+      .bulk_update_keys(&key_ids, KeyBulkAction::Pin)
+      .await
+      .unwrap();
+    assert_eq!(3, profile_db.get_keys(true).await.unwrap().len());
+
+    profile_db
+      .bulk_update_keys(&key_ids[..2], KeyBulkAction::Unpin)
+      .await
+      .unwrap();
+    assert_eq!(1, profile_db.get_keys(true).await.unwrap().len());
+
+    let deleted = profile_db
+      .bulk_update_keys(&key_ids[1..], KeyBulkAction::Delete)
+      .await
+      .unwrap();
+    assert_eq!(2, deleted);
+    assert_eq!(2, profile_db.get_trashed_keys().await.unwrap().len());
+
+    let updated = profile_db
+      .bulk_update_keys(&key_ids[1..], KeyBulkAction::Pin)
+      .await
+      .unwrap();
+    assert_eq!(0, updated);
+    assert_eq!(
+      0,
+      profile_db
+        .bulk_update_keys(&[], KeyBulkAction::Pin)
+        .await
+        .unwrap()
+    );
+  }
+
+  #[tokio::test]
+  async fn bulk_revision_and_charset() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_ids = insert_keys(&profile_db).await;
+
+    profile_db
+      .bulk_update_keys(&key_ids[..2], KeyBulkAction::BumpRevision)
+      .await
+      .unwrap();
+    profile_db
+      .bulk_update_keys(&key_ids[..1], KeyBulkAction::SetCharset("a..z".into()))
+      .await
+      .unwrap();
+
+    let key = profile_db.get_key_by_id(key_ids[0]).await.unwrap().unwrap();
+    assert_eq!(2, key.revision);
+    assert_eq!("a..z", key.charset);
+    assert_eq!(
+      2,
+      profile_db.get_key_history(key_ids[0]).await.unwrap().len()
+    );
+    assert_eq!(
+      2,
+      profile_db
+        .get_key_rotations(key_ids[1])
+        .await
+        .unwrap()
+        .len()
+    );
+
+    let key = profile_db.get_key_by_id(key_ids[2]).await.unwrap().unwrap();
+    assert_eq!(1, key.revision);
+    assert_eq!("a..z0..9", key.charset);
+
+    assert!(matches!(
+      profile_db
+        .bulk_update_keys(&key_ids, KeyBulkAction::SetCharset("z..a".into()))
+        .await,
+      Err(Error::ValidationError(_))
+    ));
+    assert!(profile_db
+      .get_key_history(key_ids[2])
+      .await
+      .unwrap()
+      .is_empty());
+  }
+}
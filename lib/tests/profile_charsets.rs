@@ -1,12 +1,15 @@
 #[cfg(all(test, feature = "profile"))]
 mod tests {
-  use keywich_lib::profile::charsets::CharsetItem;
+  use keywich_lib::errors::Error;
+  use keywich_lib::profile::charsets::{CharsetItem, CharsetUsage};
+  use keywich_lib::profile::keys::KeyData;
+  use keywich_lib::profile::utils::tag_list::TagList;
   use keywich_lib::profile::ProfileDB;
 
   macro_rules! generate_charset {
     () => {
       CharsetItem {
-        charset: "a..zA..Z".into(),
+        charset: "a..zA..Z_-".into(),
         description: Some("Description".into()),
         name: "__test".into(),
       }
@@ -18,8 +21,8 @@ mod tests {
     let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
     let charset_items = profile_db.get_charsets().await.unwrap();
 
-    // By default 3 charset should be available.
-    assert_eq!(3, charset_items.len())
+    // By default 3 charset and their 3 unambiguous presets should be available.
+    assert_eq!(6, charset_items.len())
   }
 
   #[tokio::test]
@@ -40,6 +43,45 @@ mod tests {
     }
   }
 
+  #[tokio::test]
+  async fn invalid_charset_params() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let charset = CharsetItem {
+      charset: "a..zA..9".into(),
+      description: None,
+      name: "__test".into(),
+    };
+
+    let result = profile_db.insert_charset(charset).await;
+
+    if let Err(keywich_lib::errors::Error::ValidationError(details)) = result {
+      let charset_error = &details.field_errors()["charset"][0];
+
+      assert_eq!("mixed_class_range", charset_error.params["reason"]);
+      assert_eq!("A..9", charset_error.params["token"]);
+      assert_eq!(4, charset_error.params["char_start"]);
+    } else {
+      panic!("Invalid charset is accepted.");
+    }
+  }
+
+  #[tokio::test]
+  async fn duplicate_charset() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let charset = CharsetItem {
+      charset: "0..9A..Zabcd..z".into(),
+      description: None,
+      name: "__test".into(),
+    };
+
+    let result = profile_db.insert_charset(charset).await;
+
+    assert!(matches!(
+      result,
+      Err(keywich_lib::errors::Error::DuplicateCharset(name)) if name == "Alpha Numeric"
+    ));
+  }
+
   #[tokio::test]
   async fn delete_charset() {
     let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
@@ -51,8 +93,132 @@ mod tests {
 
     assert_eq!("__test", &inserted);
 
-    let deleted_row = profile_db.delete_charset("__test").await.unwrap();
+    let deleted_row = profile_db.delete_charset("__test", false).await.unwrap();
 
     assert_eq!(1, deleted_row);
   }
+
+  #[tokio::test]
+  async fn update_charset() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    profile_db
+      .insert_charset(generate_charset!())
+      .await
+      .unwrap();
+
+    profile_db
+      .update_charset(CharsetItem {
+        charset: "a..z_".into(),
+        description: None,
+        name: "__TEST".into(),
+      })
+      .await
+      .unwrap();
+
+    let charset = profile_db.get_charset("__test").await.unwrap().unwrap();
+    assert_eq!("a..z_", charset.charset);
+    assert_eq!(None, charset.description);
+
+    assert!(matches!(
+      profile_db
+        .update_charset(CharsetItem {
+          charset: "0..9".into(),
+          ..generate_charset!()
+        })
+        .await,
+      Err(Error::DuplicateCharset(name)) if name == "Numeric"
+    ));
+    assert!(matches!(
+      profile_db
+        .update_charset(CharsetItem {
+          charset: "a..z-".into(),
+          description: None,
+          name: "__missing".into(),
+        })
+        .await,
+      Err(Error::CharsetNotFound(name)) if name == "__missing"
+    ));
+  }
+
+  #[tokio::test]
+  async fn rename_charset() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    profile_db
+      .insert_charset(generate_charset!())
+      .await
+      .unwrap();
+
+    profile_db
+      .rename_charset("__test", "__renamed")
+      .await
+      .unwrap();
+
+    assert!(profile_db.get_charset("__test").await.unwrap().is_none());
+    assert_eq!(
+      "a..zA..Z_-",
+      profile_db
+        .get_charset("__renamed")
+        .await
+        .unwrap()
+        .unwrap()
+        .charset
+    );
+
+    assert!(matches!(
+      profile_db.rename_charset("__renamed", "numeric").await,
+      Err(Error::DuplicateCharsetName(name)) if name == "numeric"
+    ));
+    assert!(matches!(
+      profile_db.rename_charset("__renamed", "").await,
+      Err(Error::ValidationError(_))
+    ));
+    assert!(matches!(
+      profile_db.rename_charset("__test", "__other").await,
+      Err(Error::CharsetNotFound(name)) if name == "__test"
+    ));
+  }
+
+  #[tokio::test]
+  async fn charset_usage() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_data = |charset: &str| KeyData {
+      domain: "example.com".into(),
+      username: "username".into(),
+      charset: charset.into(),
+      target_size: 12,
+      tags: TagList::from(["tag"]),
+      ..Default::default()
+    };
+
+    let first_key = profile_db.insert_key(key_data("0..9")).await.unwrap();
+    let second_key = profile_db.insert_key(key_data("9876543210")).await.unwrap();
+    let trashed_key = profile_db.insert_key(key_data("0..9")).await.unwrap();
+    profile_db.delete_key(trashed_key).await.unwrap();
+
+    let usage = profile_db.get_charset_usage().await.unwrap();
+    assert!(usage.contains(&CharsetUsage {
+      name: "Numeric".into(),
+      key_ids: vec![first_key, second_key],
+    }));
+    assert!(usage.contains(&CharsetUsage {
+      name: "Alpha".into(),
+      key_ids: Vec::new(),
+    }));
+
+    assert!(matches!(
+      profile_db.delete_charset("Numeric", false).await,
+      Err(Error::CharsetInUse { name, key_count: 2 }) if name == "Numeric"
+    ));
+    assert_eq!(1, profile_db.delete_charset("Numeric", true).await.unwrap());
+    assert_eq!(1, profile_db.delete_charset("Alpha", false).await.unwrap());
+    assert_eq!(
+      "0..9",
+      profile_db
+        .get_key_by_id(first_key)
+        .await
+        .unwrap()
+        .unwrap()
+        .charset
+    );
+  }
 }
@@ -0,0 +1,239 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::errors::Error;
+  use keywich_lib::profile::backup::{BackupOptions, BackupTarget, RestoreOptions};
+  use keywich_lib::profile::keys::{KeyData, KeyListOptions, SearchQuery};
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+
+  fn key_data(domain: &str) -> KeyData {
+    KeyData {
+      domain: domain.into(),
+      username: "username".into(),
+      tags: TagList::from(["tag1"]),
+      ..Default::default()
+    }
+  }
+
+  async fn page_ids(profile_db: &ProfileDB, collection_id: i64) -> Vec<i64> {
+    let mut ids: Vec<i64> = profile_db
+      .get_keys_page(KeyListOptions {
+        collection_id: Some(collection_id),
+        ..KeyListOptions::default()
+      })
+      .await
+      .unwrap()
+      .items
+      .iter()
+      .map(|e| e.id)
+      .collect();
+    ids.sort();
+    ids
+  }
+
+  #[tokio::test]
+  async fn collection_paths() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let prod = profile_db
+      .create_collection_path("clients/acme/prod")
+      .await
+      .unwrap();
+    let dev = profile_db
+      .create_collection_path("clients/acme/dev")
+      .await
+      .unwrap();
+    assert_eq!(
+      prod,
+      profile_db
+        .create_collection_path("/Clients/ACME/prod/")
+        .await
+        .unwrap()
+    );
+
+    let paths: Vec<String> = profile_db
+      .get_collections()
+      .await
+      .unwrap()
+      .into_iter()
+      .map(|e| e.path)
+      .collect();
+    assert_eq!(
+      vec![
+        "clients",
+        "clients/acme",
+        "clients/acme/dev",
+        "clients/acme/prod"
+      ],
+      paths
+    );
+
+    let dev_item = profile_db.get_collection(dev).await.unwrap().unwrap();
+    assert!(matches!(
+      profile_db
+        .create_collection("prod", dev_item.parent_id)
+        .await,
+      Err(Error::DuplicateCollection(_))
+    ));
+    assert!(matches!(
+      profile_db.create_collection("a/b", None).await,
+      Err(Error::ValidationError(_))
+    ));
+    assert!(matches!(
+      profile_db.create_collection("child", Some(999)).await,
+      Err(Error::CollectionNotFound { .. })
+    ));
+  }
+
+  #[tokio::test]
+  async fn subtree_listing_and_search() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let clients = profile_db.create_collection("clients", None).await.unwrap();
+    let prod = profile_db
+      .create_collection_path("clients/acme/prod")
+      .await
+      .unwrap();
+    let personal = profile_db
+      .create_collection("personal", None)
+      .await
+      .unwrap();
+
+    let prod_key = profile_db.insert_key(key_data("github.com")).await.unwrap();
+    let client_key = profile_db.insert_key(key_data("gitlab.com")).await.unwrap();
+    let personal_key = profile_db
+      .insert_key(key_data("example.org"))
+      .await
+      .unwrap();
+    profile_db
+      .move_keys_to_collection(&[prod_key], Some(prod))
+      .await
+      .unwrap();
+    profile_db
+      .move_keys_to_collection(&[client_key], Some(clients))
+      .await
+      .unwrap();
+    profile_db
+      .move_keys_to_collection(&[personal_key], Some(personal))
+      .await
+      .unwrap();
+
+    assert_eq!(
+      vec![prod_key, client_key],
+      page_ids(&profile_db, clients).await
+    );
+    assert_eq!(vec![prod_key], page_ids(&profile_db, prod).await);
+
+    let options = KeyListOptions {
+      collection_id: Some(clients),
+      ..KeyListOptions::default()
+    };
+    let page = profile_db
+      .search_keys_page(SearchQuery::new("github"), options.clone())
+      .await
+      .unwrap();
+    assert_eq!(1, page.total);
+    let page = profile_db
+      .search_keys_page(SearchQuery::new("example"), options.clone())
+      .await
+      .unwrap();
+    assert_eq!(0, page.total);
+    let page = profile_db
+      .search_keys_page(SearchQuery::new("exmaple"), options)
+      .await
+      .unwrap();
+    assert!(page.items.is_empty());
+  }
+
+  #[tokio::test]
+  async fn move_and_delete_collections() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let clients = profile_db.create_collection("clients", None).await.unwrap();
+    let acme = profile_db
+      .create_collection("acme", Some(clients))
+      .await
+      .unwrap();
+    let prod = profile_db
+      .create_collection("prod", Some(acme))
+      .await
+      .unwrap();
+    let archive = profile_db.create_collection("archive", None).await.unwrap();
+    let key_id = profile_db.insert_key(key_data("github.com")).await.unwrap();
+    profile_db
+      .move_keys_to_collection(&[key_id], Some(prod))
+      .await
+      .unwrap();
+
+    assert!(matches!(
+      profile_db.move_collection(clients, Some(prod)).await,
+      Err(Error::InvalidCollectionParent { .. })
+    ));
+    assert!(matches!(
+      profile_db.move_collection(clients, Some(clients)).await,
+      Err(Error::InvalidCollectionParent { .. })
+    ));
+
+    profile_db
+      .move_collection(acme, Some(archive))
+      .await
+      .unwrap();
+    let prod_item = profile_db.get_collection(prod).await.unwrap().unwrap();
+    assert_eq!("archive/acme/prod", prod_item.path);
+    assert_eq!(vec![key_id], page_ids(&profile_db, archive).await);
+    assert!(page_ids(&profile_db, clients).await.is_empty());
+
+    profile_db
+      .rename_collection(acme, "acme-corp")
+      .await
+      .unwrap();
+    let prod_item = profile_db.get_collection(prod).await.unwrap().unwrap();
+    assert_eq!("archive/acme-corp/prod", prod_item.path);
+
+    profile_db.delete_collection(acme).await.unwrap();
+    assert!(profile_db.get_collection(prod).await.unwrap().is_none());
+    let key = profile_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!(Some(archive), key.collection_id);
+  }
+
+  #[tokio::test]
+  async fn backup_keeps_collections() {
+    let content_dir = std::env::temp_dir().join("keywich_collection_backup");
+    std::fs::create_dir_all(&content_dir).unwrap();
+    let backup_path = content_dir.join("backup.kwb");
+    let _ = std::fs::remove_file(&backup_path);
+
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let prod = profile_db
+      .create_collection_path("clients/acme/prod")
+      .await
+      .unwrap();
+    let key_id = profile_db.insert_key(key_data("github.com")).await.unwrap();
+    profile_db
+      .move_keys_to_collection(&[key_id], Some(prod))
+      .await
+      .unwrap();
+    profile_db
+      .backup(BackupOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let restored_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    restored_db
+      .restore(RestoreOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let prod_item = restored_db.get_collection(prod).await.unwrap().unwrap();
+    assert_eq!("clients/acme/prod", prod_item.path);
+    let key = restored_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!(Some(prod), key.collection_id);
+
+    let _ = std::fs::remove_dir_all(&content_dir);
+  }
+}
@@ -0,0 +1,232 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::errors::Error;
+  use keywich_lib::profile::keys::{
+    DuplicateReason, KeyData, KeyKind, KeyWriteOptions, UniquenessCheck,
+  };
+  use keywich_lib::profile::ProfileDB;
+
+  fn key_data(domain: &str, username: &str, revision: i64) -> KeyData {
+    KeyData {
+      domain: domain.into(),
+      username: username.into(),
+      revision,
+      ..Default::default()
+    }
+  }
+
+  fn unique(uniqueness: UniquenessCheck) -> KeyWriteOptions {
+    KeyWriteOptions { uniqueness }
+  }
+
+  #[tokio::test]
+  async fn find_duplicates() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let first = profile_db
+      .insert_key(key_data("example.com", "alice", 1))
+      .await
+      .unwrap();
+    let second = profile_db
+      .insert_key(key_data("example.com", "alice", 1))
+      .await
+      .unwrap();
+    let rotated = profile_db
+      .insert_key(key_data("Example.com ", "ALICE", 2))
+      .await
+      .unwrap();
+    profile_db
+      .insert_key(key_data("example.com", "bob", 1))
+      .await
+      .unwrap();
+    let github = profile_db
+      .insert_key(key_data("github.com", "alice", 1))
+      .await
+      .unwrap();
+    let github_copy = profile_db
+      .insert_key(key_data("github.com", "alice", 1))
+      .await
+      .unwrap();
+    let trashed = profile_db
+      .insert_key(key_data("github.com", "alice", 1))
+      .await
+      .unwrap();
+    profile_db.delete_key(trashed).await.unwrap();
+
+    let groups: Vec<(DuplicateReason, Vec<i64>)> = profile_db
+      .find_duplicates()
+      .await
+      .unwrap()
+      .iter()
+      .map(|group| (group.reason, group.keys.iter().map(|e| e.id).collect()))
+      .collect();
+
+    assert_eq!(
+      vec![
+        (DuplicateReason::SameDerivation, vec![first, second]),
+        (DuplicateReason::SameDerivation, vec![github, github_copy]),
+        (DuplicateReason::SameAccount, vec![first, second, rotated]),
+      ],
+      groups
+    );
+  }
+
+  #[tokio::test]
+  async fn stored_keys_are_not_derivation_duplicates() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let stored_data = || KeyData {
+      kind: KeyKind::Stored,
+      secret: Some("secret".into()),
+      ..key_data("router", "admin", 1)
+    };
+    let first = profile_db.insert_key(stored_data()).await.unwrap();
+    let second = profile_db.insert_key(stored_data()).await.unwrap();
+
+    let groups = profile_db.find_duplicates().await.unwrap();
+    assert_eq!(1, groups.len());
+    assert_eq!(DuplicateReason::SameAccount, groups[0].reason);
+    assert_eq!(
+      vec![first, second],
+      groups[0].keys.iter().map(|e| e.id).collect::<Vec<i64>>()
+    );
+
+    profile_db
+      .insert_key_with(stored_data(), unique(UniquenessCheck::Derivation))
+      .await
+      .unwrap();
+  }
+
+  #[tokio::test]
+  async fn uniqueness_check() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db
+      .insert_key(key_data("example.com", "alice", 1))
+      .await
+      .unwrap();
+
+    assert!(matches!(
+      profile_db
+        .insert_key_with(
+          key_data("example.com", "alice", 1),
+          unique(UniquenessCheck::Derivation)
+        )
+        .await,
+      Err(Error::DuplicateKey { key_id: conflict, check: UniquenessCheck::Derivation }) if conflict == key_id
+    ));
+    let rotated = profile_db
+      .insert_key_with(
+        key_data("example.com", "alice", 2),
+        unique(UniquenessCheck::Derivation),
+      )
+      .await
+      .unwrap();
+    assert!(matches!(
+      profile_db
+        .insert_key_with(
+          key_data(" EXAMPLE.com", "Alice", 3),
+          unique(UniquenessCheck::Account)
+        )
+        .await,
+      Err(Error::DuplicateKey { key_id: conflict, check: UniquenessCheck::Account }) if conflict == key_id
+    ));
+
+    profile_db
+      .update_key_with(
+        key_id,
+        key_data("example.com", "alice", 1),
+        unique(UniquenessCheck::Derivation),
+      )
+      .await
+      .unwrap();
+    assert!(matches!(
+      profile_db
+        .update_key_with(
+          rotated,
+          key_data("example.com", "alice", 1),
+          unique(UniquenessCheck::Derivation)
+        )
+        .await,
+      Err(Error::DuplicateKey { key_id: conflict, check: UniquenessCheck::Derivation }) if conflict == key_id
+    ));
+
+    profile_db.delete_key(key_id).await.unwrap();
+    profile_db
+      .update_key_with(
+        rotated,
+        key_data("example.com", "alice", 1),
+        unique(UniquenessCheck::Account),
+      )
+      .await
+      .unwrap();
+    assert_eq!(1, profile_db.get_keys(false).await.unwrap().len());
+  }
+
+  #[tokio::test]
+  async fn account_check_folds_unicode() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db
+      .insert_key(key_data("Çağrı.example  ", "ÇAĞAN  Y", 1))
+      .await
+      .unwrap();
+
+    assert!(matches!(
+      profile_db
+        .insert_key_with(
+          key_data("çağrı.EXAMPLE", "çağan y", 2),
+          unique(UniquenessCheck::Account)
+        )
+        .await,
+      Err(Error::DuplicateKey { key_id: conflict, check: UniquenessCheck::Account }) if conflict == key_id
+    ));
+  }
+
+  #[tokio::test]
+  async fn account_params_backfill() {
+    let db_dir = std::env::temp_dir().join("keywich_account_backfill_test");
+    let migration_dir = db_dir.join("migrations");
+    let _ = std::fs::remove_dir_all(&db_dir);
+    std::fs::create_dir_all(&migration_dir).unwrap();
+
+    for migration in [
+      "01_initial.sql",
+      "02_unambiguous_charsets.sql",
+      "03_key_list_indexes.sql",
+    ] {
+      std::fs::copy(
+        std::path::Path::new("src/migrations").join(migration),
+        migration_dir.join(migration),
+      )
+      .unwrap();
+    }
+
+    let connection_str = format!("sqlite:{}?mode=rwc", db_dir.join("profile.db").display());
+    let pool = sqlx::SqlitePool::connect(&connection_str).await.unwrap();
+    sqlx::migrate::Migrator::new(migration_dir.as_path())
+      .await
+      .unwrap()
+      .run(&pool)
+      .await
+      .unwrap();
+    sqlx::query(
+      "INSERT INTO keys (id, target_size, revision, charset, domain, username, created_at, version)
+       VALUES (1, 12, 1, 'a..z', 'ŞEKER.com', 'ÇAĞAN', 0, 'kw_scrypt:v1');",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool.close().await;
+
+    let profile_db = ProfileDB::connect(&connection_str).await.unwrap();
+    assert!(matches!(
+      profile_db
+        .insert_key_with(
+          key_data("şeker.com", "çağan", 2),
+          unique(UniquenessCheck::Account)
+        )
+        .await,
+      Err(Error::DuplicateKey { key_id: 1, .. })
+    ));
+
+    drop(profile_db);
+    let _ = std::fs::remove_dir_all(&db_dir);
+  }
+}
@@ -0,0 +1,185 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::profile::backup::{BackupOptions, BackupTarget, RestoreOptions};
+  use keywich_lib::profile::charsets::CharsetItem;
+  use keywich_lib::profile::events::{ProfileEvent, Receiver};
+  use keywich_lib::profile::keys::{KeyBulkAction, KeyData};
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+
+  fn key_data(domain: &str) -> KeyData {
+    KeyData {
+      domain: domain.into(),
+      username: "username".into(),
+      tags: TagList::from(["tag"]),
+      ..Default::default()
+    }
+  }
+
+  fn received(receiver: &mut Receiver<ProfileEvent>) -> Vec<ProfileEvent> {
+    let mut events = Vec::new();
+
+    while let Ok(event) = receiver.try_recv() {
+      events.push(event);
+    }
+
+    events
+  }
+
+  #[tokio::test]
+  async fn key_events() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let mut receiver = profile_db.subscribe();
+
+    let key_id = profile_db
+      .insert_key(key_data("example.com"))
+      .await
+      .unwrap();
+    profile_db
+      .update_key(key_id, key_data("example.org"))
+      .await
+      .unwrap();
+    profile_db.update_pin_status(key_id, true).await.unwrap();
+    profile_db.rotate_key(key_id).await.unwrap();
+    profile_db.delete_key(key_id).await.unwrap();
+    profile_db.delete_key(key_id).await.unwrap();
+    profile_db.restore_key(key_id).await.unwrap();
+    profile_db
+      .bulk_update_keys(&[key_id], KeyBulkAction::Unpin)
+      .await
+      .unwrap();
+    profile_db
+      .bulk_update_keys(&[key_id], KeyBulkAction::Delete)
+      .await
+      .unwrap();
+    profile_db.purge_key(key_id).await.unwrap();
+
+    assert_eq!(
+      vec![
+        ProfileEvent::KeyCreated { key_id },
+        ProfileEvent::KeyUpdated { key_id },
+        ProfileEvent::KeyPinned {
+          key_id,
+          pinned: true
+        },
+        ProfileEvent::KeyUpdated { key_id },
+        ProfileEvent::KeyDeleted { key_id },
+        ProfileEvent::KeyUpdated { key_id },
+        ProfileEvent::KeyPinned {
+          key_id,
+          pinned: false
+        },
+        ProfileEvent::KeyDeleted { key_id },
+        ProfileEvent::KeyDeleted { key_id },
+      ],
+      received(&mut receiver)
+    );
+
+    assert!(profile_db
+      .update_key(key_id, key_data("example.org"))
+      .await
+      .is_err());
+    assert!(received(&mut receiver).is_empty());
+  }
+
+  #[tokio::test]
+  async fn tag_and_collection_events() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let first = profile_db
+      .insert_key(key_data("example.com"))
+      .await
+      .unwrap();
+    let second = profile_db
+      .insert_key(KeyData {
+        tags: TagList::from(["other"]),
+        ..key_data("example.org")
+      })
+      .await
+      .unwrap();
+    let collection_id = profile_db.create_collection("Work", None).await.unwrap();
+    let mut receiver = profile_db.subscribe();
+
+    profile_db.rename_tag("tag", "renamed").await.unwrap();
+    profile_db
+      .merge_tags(&["renamed", "other"], "merged")
+      .await
+      .unwrap();
+    profile_db.delete_tag("merged").await.unwrap();
+    profile_db.delete_tag("missing").await.unwrap();
+    profile_db
+      .move_keys_to_collection(&[second, 999], Some(collection_id))
+      .await
+      .unwrap();
+    profile_db.delete_collection(collection_id).await.unwrap();
+
+    assert_eq!(
+      vec![
+        ProfileEvent::KeyUpdated { key_id: first },
+        ProfileEvent::KeyUpdated { key_id: first },
+        ProfileEvent::KeyUpdated { key_id: second },
+        ProfileEvent::KeyUpdated { key_id: first },
+        ProfileEvent::KeyUpdated { key_id: second },
+        ProfileEvent::KeyUpdated { key_id: second },
+        ProfileEvent::KeyUpdated { key_id: second },
+      ],
+      received(&mut receiver)
+    );
+  }
+
+  #[tokio::test]
+  async fn charset_and_restore_events() {
+    let content_dir = std::env::temp_dir().join("keywich_profile_event_backup");
+    std::fs::create_dir_all(&content_dir).unwrap();
+    let backup_path = content_dir.join("backup.kwb");
+    let _ = std::fs::remove_file(&backup_path);
+
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    profile_db
+      .insert_key(key_data("example.com"))
+      .await
+      .unwrap();
+    let mut receiver = profile_db.subscribe();
+
+    profile_db
+      .insert_charset(CharsetItem {
+        name: "Hex".into(),
+        charset: "0..9a..f".into(),
+        description: None,
+      })
+      .await
+      .unwrap();
+    profile_db.delete_charset("Hex", false).await.unwrap();
+    profile_db.delete_charset("Hex", false).await.unwrap();
+
+    profile_db
+      .backup(BackupOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+    profile_db
+      .restore(RestoreOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    assert_eq!(
+      vec![
+        ProfileEvent::CharsetChanged { name: "Hex".into() },
+        ProfileEvent::CharsetChanged { name: "Hex".into() },
+        ProfileEvent::RestoreCompleted,
+      ],
+      received(&mut receiver)
+    );
+
+    drop(profile_db);
+    assert!(receiver.try_recv().is_err());
+
+    let _ = std::fs::remove_dir_all(&content_dir);
+  }
+}
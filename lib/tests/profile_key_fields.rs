@@ -0,0 +1,192 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::errors::Error;
+  use keywich_lib::profile::backup::{BackupOptions, BackupTarget, RestoreOptions};
+  use keywich_lib::profile::keys::{KeyData, KeyField, KeyFieldType, KeyListOptions, SearchQuery};
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+
+  fn key_field(name: &str, field_type: KeyFieldType, value: &str) -> KeyField {
+    KeyField {
+      name: name.into(),
+      field_type,
+      value: value.into(),
+    }
+  }
+
+  fn key_data(domain: &str, fields: Vec<KeyField>) -> KeyData {
+    KeyData {
+      domain: domain.into(),
+      username: "username".into(),
+      tags: TagList::from(["tag1"]),
+      fields,
+      ..Default::default()
+    }
+  }
+
+  fn bank_fields() -> Vec<KeyField> {
+    vec![
+      key_field("Account no", KeyFieldType::Number, "12345678"),
+      key_field("Recovery", KeyFieldType::Email, "recovery@example.com"),
+      key_field("Opened", KeyFieldType::Date, "2020-02-29"),
+      key_field("Branch", KeyFieldType::Url, "https://branch.example.com"),
+      key_field("First pet", KeyFieldType::Hidden, "marmalade"),
+      key_field("Advisor", KeyFieldType::Text, "Jane Doe"),
+    ]
+  }
+
+  async fn search_ids(profile_db: &ProfileDB, query: &str) -> Vec<i64> {
+    profile_db
+      .search_keys(SearchQuery::new(query))
+      .await
+      .unwrap()
+      .iter()
+      .map(|e| e.id)
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn insert_and_search_fields() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db
+      .insert_key(key_data("bank.com", bank_fields()))
+      .await
+      .unwrap();
+
+    let key = profile_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!(bank_fields(), key.fields);
+
+    assert_eq!(vec![key_id], search_ids(&profile_db, "12345678").await);
+    assert_eq!(vec![key_id], search_ids(&profile_db, "recovery").await);
+    assert_eq!(vec![key_id], search_ids(&profile_db, "jane").await);
+    assert!(search_ids(&profile_db, "marmalade").await.is_empty());
+    assert!(search_ids(&profile_db, "marmelade").await.is_empty());
+
+    profile_db
+      .update_key(
+        key_id,
+        key_data(
+          "bank.com",
+          vec![key_field("Advisor", KeyFieldType::Hidden, "Jane Doe")],
+        ),
+      )
+      .await
+      .unwrap();
+
+    let key = profile_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!(1, key.fields.len());
+    assert!(search_ids(&profile_db, "jane").await.is_empty());
+    assert!(search_ids(&profile_db, "12345678").await.is_empty());
+
+    profile_db.delete_key(key_id).await.unwrap();
+    profile_db.purge_key(key_id).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn field_highlights() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    profile_db
+      .insert_key(key_data("bank.com", bank_fields()))
+      .await
+      .unwrap();
+
+    let page = profile_db
+      .search_keys_page(SearchQuery::new("jane"), KeyListOptions::default())
+      .await
+      .unwrap();
+    let highlights = &page.items[0].highlights;
+    let matched: Vec<&str> = highlights
+      .fields
+      .iter()
+      .filter(|e| e.matched)
+      .map(|e| e.text.as_str())
+      .collect();
+    assert_eq!(vec!["Jane"], matched);
+
+    let text: String = highlights.fields.iter().map(|e| e.text.as_str()).collect();
+    assert!(text.contains("recovery@example.com"));
+    assert!(!text.contains("marmalade"));
+
+    let filter_page = profile_db
+      .search_keys_page(SearchQuery::new("pinned:false"), KeyListOptions::default())
+      .await
+      .unwrap();
+    let fields = &filter_page.items[0].highlights.fields;
+    assert!(fields.iter().all(|e| !e.matched));
+    assert_eq!(text, fields[0].text);
+  }
+
+  #[tokio::test]
+  async fn validate_fields() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let invalid_fields = [
+      key_field("Account no", KeyFieldType::Number, "12a"),
+      key_field("Recovery", KeyFieldType::Email, "recovery.example.com"),
+      key_field("Opened", KeyFieldType::Date, "2021-02-29"),
+      key_field("Branch", KeyFieldType::Url, "not a url"),
+      key_field(" ", KeyFieldType::Text, "value"),
+    ];
+
+    for field in invalid_fields {
+      assert!(matches!(
+        profile_db
+          .insert_key(key_data("bank.com", vec![field]))
+          .await,
+        Err(Error::ValidationError(_))
+      ));
+    }
+
+    assert!(matches!(
+      profile_db
+        .insert_key(key_data(
+          "bank.com",
+          vec![
+            key_field("Advisor", KeyFieldType::Text, "Jane Doe"),
+            key_field("ADVISOR", KeyFieldType::Text, "John Doe")
+          ]
+        ))
+        .await,
+      Err(Error::ValidationError(_))
+    ));
+    assert!(profile_db.get_keys(false).await.unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn backup_keeps_fields() {
+    let content_dir = std::env::temp_dir().join("keywich_key_field_backup");
+    std::fs::create_dir_all(&content_dir).unwrap();
+    let backup_path = content_dir.join("backup.kwb");
+    let _ = std::fs::remove_file(&backup_path);
+
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db
+      .insert_key(key_data("bank.com", bank_fields()))
+      .await
+      .unwrap();
+    profile_db
+      .backup(BackupOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let restored_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    restored_db
+      .restore(RestoreOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let key = restored_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!(bank_fields(), key.fields);
+    assert_eq!(vec![key_id], search_ids(&restored_db, "12345678").await);
+    assert!(search_ids(&restored_db, "marmalade").await.is_empty());
+
+    let _ = std::fs::remove_dir_all(&content_dir);
+  }
+}
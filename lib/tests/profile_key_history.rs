@@ -0,0 +1,168 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::hash::HashAlgorithm;
+  use keywich_lib::profile::backup::{BackupOptions, BackupTarget, RestoreOptions};
+  use keywich_lib::profile::keys::KeyData;
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+  use keywich_lib::{generate_password, PasswordConfig};
+
+  fn key_data(domain: &str, revision: i64) -> KeyData {
+    KeyData {
+      domain: domain.into(),
+      username: "username".into(),
+      revision,
+      tags: TagList::from(["tag1"]),
+      ..Default::default()
+    }
+  }
+
+  fn password_of(
+    domain: &str,
+    username: &str,
+    charset: &str,
+    revision: i64,
+    target_size: i64,
+  ) -> String {
+    let config = PasswordConfig {
+      domain,
+      password: "test",
+      username,
+      charset,
+      revision,
+      target_len: target_size as usize,
+    };
+
+    generate_password(config, HashAlgorithm::KwScryptV1)
+      .unwrap()
+      .pass
+  }
+
+  #[tokio::test]
+  async fn history_snapshots() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db.insert_key(key_data("first", 1)).await.unwrap();
+
+    assert!(profile_db.get_key_history(key_id).await.unwrap().is_empty());
+    let original = profile_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert!(original.updated_at.is_none());
+
+    profile_db
+      .update_key(key_id, key_data("second", 2))
+      .await
+      .unwrap();
+    profile_db
+      .update_key(key_id, key_data("third", 3))
+      .await
+      .unwrap();
+
+    let history = profile_db.get_key_history(key_id).await.unwrap();
+    assert_eq!(2, history.len());
+    assert_eq!("second", history[0].domain);
+    assert_eq!(2, history[0].revision);
+    assert_eq!("first", history[1].domain);
+    assert_eq!(1, history[1].revision);
+
+    let updated = profile_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!("third", updated.domain);
+    assert!(updated.updated_at.is_some());
+
+    let snapshot = profile_db
+      .get_key_history_item(history[1].id)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(key_id, snapshot.key_id);
+    assert_eq!(
+      password_of(
+        &original.domain,
+        &original.username,
+        &original.charset,
+        original.revision,
+        original.target_size
+      ),
+      password_of(
+        &snapshot.domain,
+        &snapshot.username,
+        &snapshot.charset,
+        snapshot.revision,
+        snapshot.target_size
+      )
+    );
+  }
+
+  #[tokio::test]
+  async fn failed_update_keeps_history() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db.insert_key(key_data("first", 1)).await.unwrap();
+
+    let mut invalid = key_data("second", 2);
+    invalid.charset = String::new();
+    assert!(profile_db.update_key(key_id, invalid).await.is_err());
+    assert!(profile_db.update_key(999, key_data("x", 1)).await.is_err());
+
+    assert!(profile_db.get_key_history(key_id).await.unwrap().is_empty());
+    assert!(profile_db.get_key_history(999).await.unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn purge_key_removes_history() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db.insert_key(key_data("first", 1)).await.unwrap();
+    profile_db
+      .update_key(key_id, key_data("second", 2))
+      .await
+      .unwrap();
+    let history = profile_db.get_key_history(key_id).await.unwrap();
+
+    assert!(profile_db.delete_key(key_id).await.unwrap());
+    assert_eq!(1, profile_db.get_key_history(key_id).await.unwrap().len());
+
+    profile_db.purge_key(key_id).await.unwrap();
+    assert!(profile_db.get_key_history(key_id).await.unwrap().is_empty());
+    assert!(profile_db
+      .get_key_history_item(history[0].id)
+      .await
+      .unwrap()
+      .is_none());
+  }
+
+  #[tokio::test]
+  async fn backup_keeps_history() {
+    let content_dir = std::env::temp_dir().join("keywich_history_backup");
+    std::fs::create_dir_all(&content_dir).unwrap();
+    let backup_path = content_dir.join("backup.kwb");
+    let _ = std::fs::remove_file(&backup_path);
+
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db.insert_key(key_data("first", 1)).await.unwrap();
+    profile_db
+      .update_key(key_id, key_data("second", 2))
+      .await
+      .unwrap();
+    profile_db
+      .backup(BackupOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let restored_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    restored_db
+      .restore(RestoreOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let history = restored_db.get_key_history(key_id).await.unwrap();
+    assert_eq!(1, history.len());
+    assert_eq!("first", history[0].domain);
+
+    let _ = std::fs::remove_dir_all(&content_dir);
+  }
+}
@@ -0,0 +1,264 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::errors::Error;
+  use keywich_lib::profile::backup::file_backup::FileBackupReader;
+  use keywich_lib::profile::backup::reader::BackupReader;
+  use keywich_lib::profile::backup::{BackupOptions, BackupTarget, RestoreOptions};
+  use keywich_lib::profile::keys::{KeyData, KeyKind, SearchQuery};
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+
+  fn key_data(domain: &str, kind: KeyKind, secret: Option<&str>) -> KeyData {
+    KeyData {
+      domain: domain.into(),
+      username: "username".into(),
+      tags: TagList::from(["wifi"]),
+      kind,
+      secret: secret.map(String::from),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn debug_output_hides_secret() {
+    let data = key_data("home-router", KeyKind::Stored, Some("wifi-pass"));
+    let debug_text = format!("{:?}", data);
+
+    assert!(!debug_text.contains("wifi-pass"));
+    assert!(debug_text.contains("home-router"));
+    assert!(debug_text.contains("<redacted>"));
+  }
+
+  #[tokio::test]
+  async fn insert_stored_key() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let stored = profile_db
+      .insert_key(key_data(
+        "home-router",
+        KeyKind::Stored,
+        Some("correct-horse-battery"),
+      ))
+      .await
+      .unwrap();
+    let derived = profile_db
+      .insert_key(key_data("example.com", KeyKind::Derived, None))
+      .await
+      .unwrap();
+
+    let key = profile_db.get_key_by_id(stored).await.unwrap().unwrap();
+    assert_eq!(KeyKind::Stored, key.kind);
+    assert_eq!(
+      Some(String::from("correct-horse-battery")),
+      profile_db.get_key_secret(stored).await.unwrap()
+    );
+    assert_eq!(None, profile_db.get_key_secret(derived).await.unwrap());
+
+    let keys = profile_db.get_keys(false).await.unwrap();
+    assert_eq!(2, keys.len());
+    assert_eq!(
+      vec![stored, derived],
+      profile_db
+        .search_keys(SearchQuery::new("tag:wifi"))
+        .await
+        .unwrap()
+        .iter()
+        .map(|e| e.id)
+        .collect::<Vec<i64>>()
+    );
+    assert!(profile_db
+      .search_keys(SearchQuery::new("battery"))
+      .await
+      .unwrap()
+      .is_empty());
+
+    assert!(matches!(
+      profile_db
+        .insert_key(key_data("home-router", KeyKind::Stored, None))
+        .await,
+      Err(Error::ValidationError(_))
+    ));
+    assert!(matches!(
+      profile_db
+        .insert_key(key_data("home-router", KeyKind::Stored, Some("")))
+        .await,
+      Err(Error::ValidationError(_))
+    ));
+  }
+
+  #[tokio::test]
+  async fn update_stored_key() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db
+      .insert_key(key_data("api", KeyKind::Stored, Some("token-1")))
+      .await
+      .unwrap();
+
+    profile_db
+      .update_key(key_id, key_data("api-token", KeyKind::Stored, None))
+      .await
+      .unwrap();
+    assert_eq!(
+      Some(String::from("token-1")),
+      profile_db.get_key_secret(key_id).await.unwrap()
+    );
+
+    profile_db
+      .update_key(
+        key_id,
+        key_data("api-token", KeyKind::Stored, Some("token-2")),
+      )
+      .await
+      .unwrap();
+    assert_eq!(
+      Some(String::from("token-2")),
+      profile_db.get_key_secret(key_id).await.unwrap()
+    );
+    let history = profile_db.get_key_history(key_id).await.unwrap();
+    assert_eq!(1, history.len());
+    assert_eq!(KeyKind::Stored, history[0].kind);
+    assert_eq!(
+      Some(String::from("token-1")),
+      profile_db
+        .get_key_history_secret(history[0].id)
+        .await
+        .unwrap()
+    );
+
+    profile_db
+      .update_key(
+        key_id,
+        key_data("api-token", KeyKind::Stored, Some("token-2")),
+      )
+      .await
+      .unwrap();
+    assert_eq!(1, profile_db.get_key_history(key_id).await.unwrap().len());
+
+    profile_db
+      .update_key(key_id, key_data("api-token", KeyKind::Derived, None))
+      .await
+      .unwrap();
+    assert_eq!(None, profile_db.get_key_secret(key_id).await.unwrap());
+    let history = profile_db.get_key_history(key_id).await.unwrap();
+    assert_eq!(2, history.len());
+    assert_eq!(
+      Some(String::from("token-2")),
+      profile_db
+        .get_key_history_secret(history[0].id)
+        .await
+        .unwrap()
+    );
+
+    assert!(matches!(
+      profile_db
+        .update_key(key_id, key_data("api-token", KeyKind::Stored, None))
+        .await,
+      Err(Error::ValidationError(_))
+    ));
+
+    profile_db
+      .update_key(
+        key_id,
+        key_data("api-token", KeyKind::Stored, Some("token-3")),
+      )
+      .await
+      .unwrap();
+    let history = profile_db.get_key_history(key_id).await.unwrap();
+    assert_eq!(3, history.len());
+    assert_eq!(KeyKind::Derived, history[0].kind);
+    assert_eq!(
+      None,
+      profile_db
+        .get_key_history_secret(history[0].id)
+        .await
+        .unwrap()
+    );
+
+    profile_db.delete_key(key_id).await.unwrap();
+    profile_db.purge_key(key_id).await.unwrap();
+    assert_eq!(None, profile_db.get_key_secret(key_id).await.unwrap());
+    assert_eq!(
+      None,
+      profile_db
+        .get_key_history_secret(history[2].id)
+        .await
+        .unwrap()
+    );
+  }
+
+  #[tokio::test]
+  async fn backup_keeps_secrets() {
+    let content_dir = std::env::temp_dir().join("keywich_key_secret_backup");
+    std::fs::create_dir_all(&content_dir).unwrap();
+    let backup_path = content_dir.join("backup.kwb");
+    let _ = std::fs::remove_file(&backup_path);
+
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db
+      .insert_key(key_data("home-router", KeyKind::Stored, Some("old-pass")))
+      .await
+      .unwrap();
+    profile_db
+      .update_key(
+        key_id,
+        key_data("home-router", KeyKind::Stored, Some("wifi-pass")),
+      )
+      .await
+      .unwrap();
+    profile_db
+      .backup(BackupOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let raw_backup = std::fs::read(&backup_path).unwrap();
+    assert!(!raw_backup.windows(9).any(|e| e == b"wifi-pass"));
+    assert!(!raw_backup.windows(8).any(|e| e == b"old-pass"));
+
+    let mut reader = FileBackupReader::new(std::fs::File::open(&backup_path).unwrap()).unwrap();
+    let mut section: Vec<u8> = Vec::new();
+    reader.copy_section_to("key_secrets", &mut section).unwrap();
+    assert!(!section.windows(9).any(|e| e == b"wifi-pass"));
+    assert!(reader.verify_digest(b"sign_key").unwrap());
+
+    let restored_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    assert!(matches!(
+      restored_db
+        .restore(RestoreOptions {
+          content_dir: content_dir.clone(),
+          target: BackupTarget::File(backup_path.clone()),
+          sign_key: Box::from(b"other_key".as_slice()),
+        })
+        .await,
+      Err(Error::BackupError { .. })
+    ));
+    restored_db
+      .restore(RestoreOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let key = restored_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!(KeyKind::Stored, key.kind);
+    assert_eq!(
+      Some(String::from("wifi-pass")),
+      restored_db.get_key_secret(key_id).await.unwrap()
+    );
+    let history = restored_db.get_key_history(key_id).await.unwrap();
+    assert_eq!(KeyKind::Stored, history[0].kind);
+    assert_eq!(
+      Some(String::from("old-pass")),
+      restored_db
+        .get_key_history_secret(history[0].id)
+        .await
+        .unwrap()
+    );
+
+    let _ = std::fs::remove_dir_all(&content_dir);
+  }
+}
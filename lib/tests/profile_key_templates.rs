@@ -0,0 +1,204 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::errors::Error;
+  use keywich_lib::profile::backup::{BackupOptions, BackupTarget, RestoreOptions};
+  use keywich_lib::profile::keys::{KeyData, UniquenessCheck};
+  use keywich_lib::profile::templates::{KeyTemplateData, TemplateKeyData};
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+
+  fn template_data(name: &str) -> KeyTemplateData {
+    KeyTemplateData {
+      name: name.into(),
+      charset: "0..9".into(),
+      target_size: 6,
+      version: "kw_scrypt:v1".into(),
+      exclude_lookalikes: true,
+      uniqueness: UniquenessCheck::Account,
+      tags: TagList::from(["bank"]),
+      rotation_days: Some(90),
+    }
+  }
+
+  fn key_data(domain: &str) -> TemplateKeyData {
+    TemplateKeyData {
+      domain: domain.into(),
+      username: "username".into(),
+      notes: None,
+      custom_icon: None,
+      revision: 1,
+      tags: TagList::from(["finance"]),
+      urls: Vec::new(),
+      fields: Vec::new(),
+    }
+  }
+
+  #[tokio::test]
+  async fn template_crud() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let pin = profile_db
+      .insert_template(template_data("Bank PIN"))
+      .await
+      .unwrap();
+    profile_db
+      .insert_template(KeyTemplateData {
+        charset: "a..z".into(),
+        ..template_data("Alphabet")
+      })
+      .await
+      .unwrap();
+
+    let names: Vec<String> = profile_db
+      .get_templates()
+      .await
+      .unwrap()
+      .into_iter()
+      .map(|e| e.name)
+      .collect();
+    assert_eq!(vec!["Alphabet", "Bank PIN"], names);
+
+    assert!(matches!(
+      profile_db.insert_template(template_data("bank pin")).await,
+      Err(Error::DuplicateTemplate(_))
+    ));
+    assert!(matches!(
+      profile_db
+        .insert_template(KeyTemplateData {
+          target_size: 0,
+          ..template_data("Invalid")
+        })
+        .await,
+      Err(Error::ValidationError(_))
+    ));
+
+    profile_db
+      .update_template(
+        pin,
+        KeyTemplateData {
+          target_size: 8,
+          ..template_data("Bank PIN")
+        },
+      )
+      .await
+      .unwrap();
+    let template = profile_db.get_template(pin).await.unwrap().unwrap();
+    assert_eq!(8, template.target_size);
+    assert_eq!("bank", template.tags.join(' '));
+    assert_eq!(UniquenessCheck::Account, template.uniqueness);
+
+    assert!(profile_db.delete_template(pin).await.unwrap());
+    assert!(!profile_db.delete_template(pin).await.unwrap());
+    assert!(matches!(
+      profile_db.update_template(pin, template_data("Bank PIN")).await,
+      Err(Error::TemplateNotFound { template_id }) if template_id == pin
+    ));
+  }
+
+  #[tokio::test]
+  async fn insert_key_from_template() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let template_id = profile_db
+      .insert_template(template_data("Bank PIN"))
+      .await
+      .unwrap();
+
+    let key_id = profile_db
+      .insert_key_from_template(template_id, key_data("bank.com"))
+      .await
+      .unwrap();
+    let key = profile_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!("0..9", key.charset);
+    assert_eq!(6, key.target_size);
+    assert_eq!("kw_scrypt:v1", key.version);
+    assert!(key.exclude_lookalikes);
+    assert_eq!(Some(90), key.rotation_days);
+    let mut tags: Vec<String> = key.tags.iter().map(|e| e.to_string()).collect();
+    tags.sort();
+    assert_eq!(vec!["bank", "finance"], tags);
+
+    assert!(matches!(
+      profile_db
+        .insert_key_from_template(template_id, key_data(" BANK.com"))
+        .await,
+      Err(Error::DuplicateKey { key_id: conflict, .. }) if conflict == key_id
+    ));
+    assert!(matches!(
+      profile_db
+        .insert_key_from_template(template_id + 1, key_data("other.com"))
+        .await,
+      Err(Error::TemplateNotFound { .. })
+    ));
+    assert_eq!(1, profile_db.get_keys(false).await.unwrap().len());
+
+    profile_db.delete_template(template_id).await.unwrap();
+    assert!(profile_db.get_key_by_id(key_id).await.unwrap().is_some());
+  }
+
+  #[tokio::test]
+  async fn template_key_revision_default() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let template_id = profile_db
+      .insert_template(template_data("Bank PIN"))
+      .await
+      .unwrap();
+
+    let data: TemplateKeyData =
+      serde_json::from_str(r#"{"domain": "bank.com", "username": "username"}"#).unwrap();
+    assert_eq!(KeyData::default().revision, data.revision);
+
+    let key_id = profile_db
+      .insert_key_from_template(template_id, data)
+      .await
+      .unwrap();
+    let key = profile_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!(1, key.revision);
+  }
+
+  #[tokio::test]
+  async fn backup_keeps_templates() {
+    let content_dir = std::env::temp_dir().join("keywich_key_template_backup");
+    std::fs::create_dir_all(&content_dir).unwrap();
+    let backup_path = content_dir.join("backup.kwb");
+    let _ = std::fs::remove_file(&backup_path);
+
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let template_id = profile_db
+      .insert_template(template_data("Bank PIN"))
+      .await
+      .unwrap();
+    profile_db
+      .insert_key_from_template(template_id, key_data("bank.com"))
+      .await
+      .unwrap();
+    profile_db
+      .backup(BackupOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let restored_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    restored_db
+      .restore(RestoreOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let template = restored_db
+      .get_template(template_id)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!("Bank PIN", template.name);
+    assert_eq!(Some(90), template.rotation_days);
+    assert_eq!(UniquenessCheck::Account, template.uniqueness);
+    assert_eq!("bank", template.tags.join(' '));
+
+    let _ = std::fs::remove_dir_all(&content_dir);
+  }
+}
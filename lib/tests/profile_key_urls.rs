@@ -0,0 +1,373 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::errors::Error;
+  use keywich_lib::profile::backup::{BackupOptions, BackupTarget, RestoreOptions};
+  use keywich_lib::profile::keys::{
+    HighlightSegment, KeyData, KeyListOptions, KeyUrl, SearchQuery, UrlMatchKind, UrlMatchRule,
+  };
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+
+  fn key_url(url: &str, match_rule: UrlMatchRule) -> KeyUrl {
+    KeyUrl {
+      url: url.into(),
+      match_rule,
+    }
+  }
+
+  fn key_data(domain: &str, urls: Vec<KeyUrl>) -> KeyData {
+    KeyData {
+      domain: domain.into(),
+      username: "username".into(),
+      tags: TagList::from(["tag1"]),
+      urls,
+      ..Default::default()
+    }
+  }
+
+  async fn search_ids(profile_db: &ProfileDB, query: &str) -> Vec<i64> {
+    profile_db
+      .search_keys(SearchQuery::new(query))
+      .await
+      .unwrap()
+      .iter()
+      .map(|e| e.id)
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn insert_and_search_urls() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let urls = vec![
+      key_url("youtube.com", UrlMatchRule::BaseDomain),
+      key_url("accounts.google.com", UrlMatchRule::ExactHost),
+      key_url("android://com.google.android.gm", UrlMatchRule::Prefix),
+    ];
+    let key_id = profile_db
+      .insert_key(key_data("google.com", urls.clone()))
+      .await
+      .unwrap();
+    profile_db
+      .insert_key(key_data("example.org", Vec::new()))
+      .await
+      .unwrap();
+
+    let key = profile_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!("google.com", key.domain);
+    assert_eq!(urls, key.urls);
+
+    assert_eq!(vec![key_id], search_ids(&profile_db, "youtube").await);
+    assert_eq!(vec![key_id], search_ids(&profile_db, "youtub").await);
+    assert!(search_ids(&profile_db, "domain:youtube").await.is_empty());
+
+    let keys = profile_db.get_keys(false).await.unwrap();
+    let other = keys.iter().find(|e| e.id != key_id).unwrap();
+    assert!(other.urls.is_empty());
+  }
+
+  #[tokio::test]
+  async fn update_urls() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db
+      .insert_key(key_data(
+        "google.com",
+        vec![key_url("youtube.com", UrlMatchRule::BaseDomain)],
+      ))
+      .await
+      .unwrap();
+
+    profile_db
+      .update_key(
+        key_id,
+        key_data(
+          "google.com",
+          vec![key_url("gmail.com", UrlMatchRule::ExactHost)],
+        ),
+      )
+      .await
+      .unwrap();
+
+    let key = profile_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!(
+      vec![key_url("gmail.com", UrlMatchRule::ExactHost)],
+      key.urls
+    );
+    assert!(search_ids(&profile_db, "youtube").await.is_empty());
+    assert_eq!(vec![key_id], search_ids(&profile_db, "gmail").await);
+
+    assert!(matches!(
+      profile_db
+        .update_key(
+          key_id,
+          key_data(
+            "google.com",
+            vec![
+              key_url("gmail.com", UrlMatchRule::ExactHost),
+              key_url("GMAIL.com", UrlMatchRule::BaseDomain)
+            ],
+          ),
+        )
+        .await,
+      Err(Error::ValidationError(_))
+    ));
+    assert!(matches!(
+      profile_db
+        .insert_key(key_data(
+          "google.com",
+          vec![key_url(" ", UrlMatchRule::BaseDomain)]
+        ))
+        .await,
+      Err(Error::ValidationError(_))
+    ));
+
+    profile_db.delete_key(key_id).await.unwrap();
+    profile_db.purge_key(key_id).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn url_highlights() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let urls = vec![
+      key_url("youtube.com", UrlMatchRule::BaseDomain),
+      key_url("accounts.google.com", UrlMatchRule::ExactHost),
+    ];
+    profile_db
+      .insert_key(key_data("google.com", urls))
+      .await
+      .unwrap();
+
+    let page = profile_db
+      .search_keys_page(SearchQuery::new("youtube"), KeyListOptions::default())
+      .await
+      .unwrap();
+    let highlights = &page.items[0].highlights;
+    assert!(highlights.domain.iter().all(|e| !e.matched));
+    assert_eq!(
+      vec![
+        HighlightSegment {
+          text: "youtube".into(),
+          matched: true
+        },
+        HighlightSegment {
+          text: ".com accounts.google.com".into(),
+          matched: false
+        },
+      ],
+      highlights.urls
+    );
+
+    let filter_page = profile_db
+      .search_keys_page(SearchQuery::new("pinned:false"), KeyListOptions::default())
+      .await
+      .unwrap();
+    assert_eq!(
+      vec![HighlightSegment {
+        text: "youtube.com accounts.google.com".into(),
+        matched: false
+      }],
+      filter_page.items[0].highlights.urls
+    );
+  }
+
+  #[tokio::test]
+  async fn find_keys_for_url() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let google = profile_db
+      .insert_key(key_data(
+        "google.com",
+        vec![key_url("youtube.com", UrlMatchRule::BaseDomain)],
+      ))
+      .await
+      .unwrap();
+    let accounts = profile_db
+      .insert_key(key_data(
+        "work-account",
+        vec![key_url("accounts.google.com", UrlMatchRule::ExactHost)],
+      ))
+      .await
+      .unwrap();
+    let mail = profile_db
+      .insert_key(key_data("mail.google.com", Vec::new()))
+      .await
+      .unwrap();
+    let admin = profile_db
+      .insert_key(key_data(
+        "example-admin",
+        vec![key_url("example.com/admin", UrlMatchRule::Prefix)],
+      ))
+      .await
+      .unwrap();
+    let user_page = profile_db
+      .insert_key(key_data("alice.github.io", Vec::new()))
+      .await
+      .unwrap();
+    let trashed = profile_db
+      .insert_key(key_data("google.com", Vec::new()))
+      .await
+      .unwrap();
+    profile_db.delete_key(trashed).await.unwrap();
+
+    let matches: Vec<(i64, UrlMatchKind)> = profile_db
+      .find_keys_for_url("https://accounts.google.com/signin?continue=1")
+      .await
+      .unwrap()
+      .iter()
+      .map(|e| (e.key.id, e.match_kind))
+      .collect();
+    assert_eq!(
+      vec![
+        (accounts, UrlMatchKind::ExactHost),
+        (google, UrlMatchKind::Subdomain),
+        (mail, UrlMatchKind::BaseDomain)
+      ],
+      matches
+    );
+
+    let matches = profile_db
+      .find_keys_for_url("m.youtube.com/watch")
+      .await
+      .unwrap();
+    assert_eq!(1, matches.len());
+    assert_eq!(google, matches[0].key.id);
+    assert_eq!(UrlMatchKind::Subdomain, matches[0].match_kind);
+
+    let matches = profile_db
+      .find_keys_for_url("http://EXAMPLE.com/admin/users")
+      .await
+      .unwrap();
+    assert_eq!(
+      vec![admin],
+      matches.iter().map(|e| e.key.id).collect::<Vec<i64>>()
+    );
+    assert!(profile_db
+      .find_keys_for_url("https://example.com/login")
+      .await
+      .unwrap()
+      .is_empty());
+
+    assert!(profile_db
+      .find_keys_for_url("https://bob.github.io")
+      .await
+      .unwrap()
+      .is_empty());
+    let matches = profile_db
+      .find_keys_for_url("https://alice.github.io/blog")
+      .await
+      .unwrap();
+    assert_eq!(user_page, matches[0].key.id);
+
+    assert!(profile_db
+      .find_keys_for_url("not a url")
+      .await
+      .unwrap()
+      .is_empty());
+  }
+
+  #[tokio::test]
+  async fn find_keys_for_multi_level_suffix() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let google = profile_db
+      .insert_key(key_data("google.co.uk", Vec::new()))
+      .await
+      .unwrap();
+    profile_db
+      .insert_key(key_data("bbc.co.uk", Vec::new()))
+      .await
+      .unwrap();
+
+    let matches = profile_db
+      .find_keys_for_url("https://accounts.google.co.uk/signin")
+      .await
+      .unwrap();
+    assert_eq!(
+      vec![(google, UrlMatchKind::Subdomain)],
+      matches
+        .iter()
+        .map(|e| (e.key.id, e.match_kind))
+        .collect::<Vec<(i64, UrlMatchKind)>>()
+    );
+    assert!(profile_db
+      .find_keys_for_url("https://example.co.uk")
+      .await
+      .unwrap()
+      .is_empty());
+  }
+
+  #[tokio::test]
+  async fn find_keys_for_idn_url() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let unicode = profile_db
+      .insert_key(key_data("münchen.de", Vec::new()))
+      .await
+      .unwrap();
+    let punycode = profile_db
+      .insert_key(key_data(
+        "city-account",
+        vec![key_url("xn--mnchen-3ya.de", UrlMatchRule::BaseDomain)],
+      ))
+      .await
+      .unwrap();
+
+    for url in [
+      "https://www.münchen.de/login",
+      "https://www.xn--mnchen-3ya.de",
+    ] {
+      let matches: Vec<(i64, UrlMatchKind)> = profile_db
+        .find_keys_for_url(url)
+        .await
+        .unwrap()
+        .iter()
+        .map(|e| (e.key.id, e.match_kind))
+        .collect();
+      assert_eq!(
+        vec![
+          (unicode, UrlMatchKind::Subdomain),
+          (punycode, UrlMatchKind::Subdomain)
+        ],
+        matches
+      );
+    }
+  }
+
+  #[tokio::test]
+  async fn backup_keeps_urls() {
+    let content_dir = std::env::temp_dir().join("keywich_key_url_backup");
+    std::fs::create_dir_all(&content_dir).unwrap();
+    let backup_path = content_dir.join("backup.kwb");
+    let _ = std::fs::remove_file(&backup_path);
+
+    let urls = vec![
+      key_url("youtube.com", UrlMatchRule::BaseDomain),
+      key_url("accounts.google.com", UrlMatchRule::ExactHost),
+    ];
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db
+      .insert_key(key_data("google.com", urls.clone()))
+      .await
+      .unwrap();
+    profile_db
+      .backup(BackupOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let restored_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    restored_db
+      .restore(RestoreOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let key = restored_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!(urls, key.urls);
+    assert_eq!(vec![key_id], search_ids(&restored_db, "youtube").await);
+
+    let _ = std::fs::remove_dir_all(&content_dir);
+  }
+}
@@ -1,6 +1,8 @@
 #[cfg(all(test, feature = "profile"))]
 mod tests {
-  use keywich_lib::profile::keys::{KeyData, SearchQuery};
+  use keywich_lib::profile::keys::{
+    HighlightSegment, KeyData, KeyListOptions, KeySort, SearchQuery,
+  };
   use keywich_lib::profile::utils::tag_list::TagList;
   use keywich_lib::profile::ProfileDB;
 
@@ -16,6 +18,7 @@ mod tests {
         revision: 12,
         target_size: 12,
         tags: TagList::from(["tag1", "tag2", "tag3", "tag4", "tag5", "tag6"]),
+        ..Default::default()
       }
     };
   }
@@ -31,6 +34,152 @@ mod tests {
     assert_eq!(2, keys.len());
   }
 
+  async fn insert_domains(profile_db: &ProfileDB, domains: &[&str]) -> Vec<i64> {
+    let mut ids = Vec::new();
+
+    for domain in domains {
+      let mut key = generate_key!();
+      key.domain = String::from(*domain);
+      ids.push(profile_db.insert_key(key).await.unwrap());
+    }
+
+    ids
+  }
+
+  #[tokio::test]
+  async fn key_pages() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let ids = insert_domains(&profile_db, &["a", "b", "c", "d", "e"]).await;
+
+    let mut options = KeyListOptions {
+      limit: 2,
+      ..KeyListOptions::default()
+    };
+    let mut page_ids = Vec::new();
+
+    loop {
+      let page = profile_db.get_keys_page(options.clone()).await.unwrap();
+      assert_eq!(5, page.total);
+      assert!(page.items.len() <= 2);
+      page_ids.extend(page.items.iter().map(|e| e.id));
+
+      match page.next_cursor {
+        Some(cursor) => options.cursor = Some(cursor),
+        None => break,
+      }
+    }
+
+    let expected: Vec<i64> = ids.into_iter().rev().collect();
+    assert_eq!(expected, page_ids);
+  }
+
+  #[tokio::test]
+  async fn key_pages_sorted_by_domain() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    insert_domains(&profile_db, &["c:3", "A", "b", "a", "C:1"]).await;
+
+    let options = KeyListOptions {
+      limit: 3,
+      sort: Some(KeySort::Domain),
+      ..KeyListOptions::default()
+    };
+    let first_page = profile_db.get_keys_page(options.clone()).await.unwrap();
+    let second_page = profile_db
+      .get_keys_page(KeyListOptions {
+        cursor: first_page.next_cursor.clone(),
+        ..options
+      })
+      .await
+      .unwrap();
+
+    let domains: Vec<String> = first_page
+      .items
+      .into_iter()
+      .chain(second_page.items)
+      .map(|e| e.domain)
+      .collect();
+
+    assert_eq!(vec!["A", "a", "b", "C:1", "c:3"], domains);
+    assert!(second_page.next_cursor.is_none());
+  }
+
+  #[tokio::test]
+  async fn key_pages_pinned_first() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let ids = insert_domains(&profile_db, &["a", "b", "c"]).await;
+    profile_db.update_pin_status(ids[0], true).await.unwrap();
+
+    let page = profile_db
+      .get_keys_page(KeyListOptions {
+        sort: Some(KeySort::PinnedFirst),
+        ..KeyListOptions::default()
+      })
+      .await
+      .unwrap();
+    let pinned = profile_db
+      .get_keys_page(KeyListOptions {
+        pinned_only: true,
+        ..KeyListOptions::default()
+      })
+      .await
+      .unwrap();
+
+    let page_ids: Vec<i64> = page.items.iter().map(|e| e.id).collect();
+    assert_eq!(vec![ids[0], ids[2], ids[1]], page_ids);
+    assert_eq!(1, pinned.total);
+    assert_eq!(1, profile_db.count_keys(true).await.unwrap());
+    assert_eq!(3, profile_db.count_keys(false).await.unwrap());
+  }
+
+  #[tokio::test]
+  async fn search_key_pages() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    insert_domains(&profile_db, &["search", "search", "search", "other"]).await;
+
+    let first_page = profile_db
+      .search_keys_page(
+        SearchQuery::new("domain:search"),
+        KeyListOptions {
+          limit: 2,
+          ..KeyListOptions::default()
+        },
+      )
+      .await
+      .unwrap();
+    let second_page = profile_db
+      .search_keys_page(
+        SearchQuery::new("domain:search"),
+        KeyListOptions {
+          limit: 2,
+          cursor: first_page.next_cursor.clone(),
+          ..KeyListOptions::default()
+        },
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(3, first_page.total);
+    assert_eq!(2, first_page.items.len());
+    assert_eq!(1, second_page.items.len());
+    assert!(second_page.next_cursor.is_none());
+  }
+
+  #[tokio::test]
+  async fn invalid_page_cursor() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let result = profile_db
+      .get_keys_page(KeyListOptions {
+        cursor: Some("x:1".into()),
+        ..KeyListOptions::default()
+      })
+      .await;
+
+    assert!(matches!(
+      result,
+      Err(keywich_lib::errors::Error::InvalidCursor(_))
+    ));
+  }
+
   #[tokio::test]
   async fn invalid_data() {
     let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
@@ -44,6 +193,7 @@ mod tests {
       revision: 12,
       target_size: 900,
       tags: TagList::from(["tag1", "tag2", "tag3", "tag4", "tag5", "tag6"]),
+      ..Default::default()
     };
 
     if let Err(keywich_lib::errors::Error::ValidationError(errors)) =
@@ -55,6 +205,21 @@ mod tests {
     }
   }
 
+  #[tokio::test]
+  async fn lookalike_charset_without_symbols() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let mut key_data = generate_key!();
+    key_data.charset = "0..1".into();
+    key_data.exclude_lookalikes = true;
+
+    let result = profile_db.insert_key(key_data).await;
+
+    assert!(matches!(
+      result,
+      Err(keywich_lib::errors::Error::ValidationError(errors)) if errors.field_errors().contains_key("charset")
+    ));
+  }
+
   #[tokio::test]
   async fn empty_search() {
     let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
@@ -134,6 +299,7 @@ mod tests {
           revision: 13,
           target_size: 13,
           tags: TagList::from(["tag4", "tag5", "tag6", "tag7", "tag8"]),
+          ..Default::default()
         },
       )
       .await
@@ -142,6 +308,17 @@ mod tests {
     assert!(true);
   }
 
+  #[tokio::test]
+  async fn update_non_existing_key() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let result = profile_db.update_key(999, generate_key!()).await;
+
+    assert!(matches!(
+      result,
+      Err(keywich_lib::errors::Error::KeyNotFound { key_id: 999 })
+    ));
+  }
+
   #[tokio::test]
   async fn get_by_id() {
     let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
@@ -166,9 +343,249 @@ mod tests {
     let query =
       SearchQuery::new("tag:tag1 tag:tag2 domain:test username:user\" test query like a tag");
     if let Some(qtext) = query.to_fts_query() {
-      assert_eq!("({domain username tags notes}: \"test\" OR \"query\" OR \"like\" OR \"a\" OR \"tag\") AND ({username}: \"user\"\"\") AND ({domain}: \"test\") AND ({tags}: \"tag1\" OR \"tag2\")", &qtext)
+      assert_eq!("{tags}: \"tag1\" AND {tags}: \"tag2\" AND {domain}: \"test\" AND {username}: \"user\"\"\" AND \"test\" AND \"query\" AND \"like\" AND \"a\" AND \"tag\"", &qtext)
     } else {
       assert!(false);
     }
   }
+
+  #[tokio::test]
+  async fn search_with_query_language() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let ids = insert_domains(&profile_db, &["my bank", "bank", "other"]).await;
+    profile_db.update_pin_status(ids[2], true).await.unwrap();
+
+    let search = |query: &'static str| {
+      let profile_db = &profile_db;
+      async move {
+        let mut result: Vec<i64> = profile_db
+          .search_keys(SearchQuery::new(query))
+          .await
+          .unwrap()
+          .into_iter()
+          .map(|e| e.id)
+          .collect();
+        result.sort();
+        result
+      }
+    };
+
+    assert_eq!(vec![ids[0]], search("domain:\"my bank\"").await);
+    assert_eq!(vec![ids[1]], search("bank -my").await);
+    assert_eq!(vec![ids[0], ids[1]], search("ban*").await);
+    assert_eq!(vec![ids[2]], search("pinned:true").await);
+    assert_eq!(vec![ids[1], ids[2]], search("-my").await);
+    assert_eq!(vec![ids[0], ids[2]], search("my OR pinned:yes").await);
+    assert_eq!(
+      vec![ids[0], ids[1], ids[2]],
+      search("charset:a..z0..9 created:>2000-01-01").await
+    );
+    assert!(search("created:<2000-01-01").await.is_empty());
+    assert!(search("charset:Numeric").await.is_empty());
+  }
+
+  #[tokio::test]
+  async fn typo_tolerant_search() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let ids = insert_domains(
+      &profile_db,
+      &["github.com", "İstanbul Kart", "münchen.de", "example.org"],
+    )
+    .await;
+
+    let search = |query: &'static str| {
+      let profile_db = &profile_db;
+      async move {
+        profile_db
+          .search_keys(SearchQuery::new(query))
+          .await
+          .unwrap()
+          .into_iter()
+          .map(|e| e.id)
+          .collect::<Vec<i64>>()
+      }
+    };
+
+    assert_eq!(vec![ids[0]], search("gihub").await);
+    assert_eq!(vec![ids[0]], search("hub").await);
+    assert_eq!(vec![ids[1]], search("istanbul").await);
+    assert_eq!(vec![ids[2]], search("munchen").await);
+    assert!(search("zzzzzz").await.is_empty());
+
+    let page = profile_db
+      .search_keys_page(SearchQuery::new("exmaple"), KeyListOptions::default())
+      .await
+      .unwrap();
+    assert_eq!(1, page.total);
+    assert_eq!(ids[3], page.items[0].key.id);
+    assert!(page.next_cursor.is_none());
+
+    insert_domains(&profile_db, &["example.net"]).await;
+    let page = profile_db
+      .search_keys_page(
+        SearchQuery::new("exmaple"),
+        KeyListOptions {
+          limit: 1,
+          ..Default::default()
+        },
+      )
+      .await
+      .unwrap();
+    assert_eq!(2, page.total);
+    assert_eq!(1, page.items.len());
+  }
+
+  #[tokio::test]
+  async fn trigram_index_follows_updates() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let ids = insert_domains(&profile_db, &["github.com"]).await;
+
+    let mut key = generate_key!();
+    key.domain = String::from("gitlab.com");
+    profile_db.update_key(ids[0], key).await.unwrap();
+
+    let result = profile_db
+      .search_keys(SearchQuery::new("gitlb"))
+      .await
+      .unwrap();
+    assert_eq!(ids[0], result[0].id);
+    assert!(profile_db
+      .search_keys(SearchQuery::new("gihub"))
+      .await
+      .unwrap()
+      .is_empty());
+
+    profile_db.delete_key(ids[0]).await.unwrap();
+    assert!(profile_db
+      .search_keys(SearchQuery::new("gitlb"))
+      .await
+      .unwrap()
+      .is_empty());
+  }
+
+  #[tokio::test]
+  async fn search_highlights() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let mut key = generate_key!();
+    key.domain = String::from("my bank");
+    key.notes = Some(String::from(
+      "security questions are stored in the bank vault",
+    ));
+    let key_id = profile_db.insert_key(key).await.unwrap();
+
+    let page = profile_db
+      .search_keys_page(SearchQuery::new("bank"), KeyListOptions::default())
+      .await
+      .unwrap();
+    let result = &page.items[0];
+
+    assert_eq!(key_id, result.key.id);
+    assert!(result.score > 0.0);
+    assert_eq!(
+      vec![
+        HighlightSegment {
+          text: "my ".into(),
+          matched: false
+        },
+        HighlightSegment {
+          text: "bank".into(),
+          matched: true
+        },
+      ],
+      result.highlights.domain
+    );
+    assert!(result.highlights.username.iter().all(|e| !e.matched));
+
+    let notes = result.highlights.notes.as_ref().unwrap();
+    let matched: Vec<&str> = notes
+      .iter()
+      .filter(|e| e.matched)
+      .map(|e| e.text.as_str())
+      .collect();
+    assert_eq!(vec!["bank"], matched);
+
+    let filter_page = profile_db
+      .search_keys_page(SearchQuery::new("pinned:false"), KeyListOptions::default())
+      .await
+      .unwrap();
+    assert_eq!(0.0, filter_page.items[0].score);
+    assert!(filter_page.items[0]
+      .highlights
+      .domain
+      .iter()
+      .all(|e| !e.matched));
+  }
+
+  #[tokio::test]
+  async fn key_usage() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let ids = insert_domains(&profile_db, &["a", "b", "c"]).await;
+
+    for key_id in [ids[1], ids[1], ids[1], ids[2]] {
+      profile_db.record_key_usage(key_id).await.unwrap();
+    }
+
+    let key = profile_db.get_key_by_id(ids[1]).await.unwrap().unwrap();
+    assert_eq!(3, key.use_count);
+    assert!(key.last_used_at.is_some());
+
+    let unused = profile_db.get_key_by_id(ids[0]).await.unwrap().unwrap();
+    assert_eq!(0, unused.use_count);
+    assert!(unused.last_used_at.is_none());
+
+    assert!(matches!(
+      profile_db.record_key_usage(999).await,
+      Err(keywich_lib::errors::Error::KeyNotFound { key_id: 999 })
+    ));
+  }
+
+  #[tokio::test]
+  async fn frecency_pages() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let ids = insert_domains(&profile_db, &["a", "b", "c"]).await;
+
+    for key_id in [ids[1], ids[1], ids[1], ids[2]] {
+      profile_db.record_key_usage(key_id).await.unwrap();
+    }
+
+    let options = KeyListOptions {
+      limit: 2,
+      sort: Some(KeySort::Frecency),
+      ..KeyListOptions::default()
+    };
+    let first_page = profile_db.get_keys_page(options.clone()).await.unwrap();
+    let second_page = profile_db
+      .get_keys_page(KeyListOptions {
+        cursor: first_page.next_cursor.clone(),
+        ..options
+      })
+      .await
+      .unwrap();
+
+    let first_ids: Vec<i64> = first_page.items.iter().map(|e| e.id).collect();
+    let second_ids: Vec<i64> = second_page.items.iter().map(|e| e.id).collect();
+    assert_eq!(vec![ids[1], ids[2]], first_ids);
+    assert_eq!(vec![ids[0]], second_ids);
+    assert!(second_page.next_cursor.is_none());
+  }
+
+  #[tokio::test]
+  async fn stale_keys() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let ids = insert_domains(&profile_db, &["a", "b"]).await;
+
+    assert!(profile_db.get_stale_keys(30).await.unwrap().is_empty());
+
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    profile_db.record_key_usage(ids[1]).await.unwrap();
+
+    let stale: Vec<i64> = profile_db
+      .get_stale_keys(0)
+      .await
+      .unwrap()
+      .into_iter()
+      .map(|e| e.id)
+      .collect();
+    assert_eq!(vec![ids[0]], stale);
+  }
 }
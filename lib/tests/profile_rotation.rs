@@ -0,0 +1,200 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::errors::Error;
+  use keywich_lib::profile::backup::{BackupOptions, BackupTarget, RestoreOptions};
+  use keywich_lib::profile::keys::KeyData;
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+
+  fn key_data(domain: &str, revision: i64, tags: &[&str]) -> KeyData {
+    KeyData {
+      domain: domain.into(),
+      username: "username".into(),
+      revision,
+      tags: TagList::from(tags),
+      ..Default::default()
+    }
+  }
+
+  #[tokio::test]
+  async fn revision_changes_are_recorded() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db
+      .insert_key(key_data("github.com", 1, &[]))
+      .await
+      .unwrap();
+
+    profile_db
+      .update_key(key_id, key_data("github.com", 1, &["work"]))
+      .await
+      .unwrap();
+    assert_eq!(1, profile_db.get_key_rotations(key_id).await.unwrap().len());
+
+    profile_db
+      .update_key(key_id, key_data("github.com", 5, &["work"]))
+      .await
+      .unwrap();
+    assert_eq!(6, profile_db.rotate_key(key_id).await.unwrap());
+
+    let rotations = profile_db.get_key_rotations(key_id).await.unwrap();
+    assert_eq!(
+      vec![6, 5, 1],
+      rotations.iter().map(|e| e.revision).collect::<Vec<i64>>()
+    );
+
+    let key = profile_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!(6, key.revision);
+    assert_eq!(
+      5,
+      profile_db.get_key_history(key_id).await.unwrap()[0].revision
+    );
+
+    assert!(matches!(
+      profile_db.rotate_key(999).await,
+      Err(Error::KeyNotFound { .. })
+    ));
+  }
+
+  #[tokio::test]
+  async fn due_for_rotation() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let tagged = profile_db
+      .insert_key(key_data("github.com", 1, &["work", "code"]))
+      .await
+      .unwrap();
+    let own_policy = profile_db
+      .insert_key(key_data("example.org", 1, &["work"]))
+      .await
+      .unwrap();
+    profile_db
+      .insert_key(key_data("untracked.org", 1, &["personal"]))
+      .await
+      .unwrap();
+
+    profile_db.set_tag_rotation("work", Some(90)).await.unwrap();
+    profile_db.set_tag_rotation("code", Some(30)).await.unwrap();
+    profile_db
+      .set_key_rotation(own_policy, Some(365))
+      .await
+      .unwrap();
+
+    assert!(profile_db
+      .get_keys_due_for_rotation(0)
+      .await
+      .unwrap()
+      .is_empty());
+
+    let due = profile_db.get_keys_due_for_rotation(30).await.unwrap();
+    assert_eq!(1, due.len());
+    assert_eq!(tagged, due[0].key.id);
+    assert_eq!(30, due[0].rotation_days);
+    assert_eq!(due[0].last_rotated_at + 30 * 24 * 60 * 60, due[0].due_at);
+
+    let due = profile_db.get_keys_due_for_rotation(365).await.unwrap();
+    assert_eq!(
+      vec![tagged, own_policy],
+      due.iter().map(|e| e.key.id).collect::<Vec<i64>>()
+    );
+
+    profile_db.set_tag_rotation("code", None).await.unwrap();
+    let due = profile_db.get_keys_due_for_rotation(90).await.unwrap();
+    assert_eq!(90, due[0].rotation_days);
+
+    profile_db.delete_key(tagged).await.unwrap();
+    assert!(profile_db
+      .get_keys_due_for_rotation(90)
+      .await
+      .unwrap()
+      .is_empty());
+
+    assert!(matches!(
+      profile_db.set_key_rotation(own_policy, Some(0)).await,
+      Err(Error::ValidationError(errors)) if errors.field_errors().contains_key("rotation_days")
+    ));
+    assert!(matches!(
+      profile_db.set_tag_rotation("work", Some(0)).await,
+      Err(Error::ValidationError(errors)) if errors.field_errors().contains_key("rotation_days")
+    ));
+    assert!(matches!(
+      profile_db.set_tag_rotation("", Some(30)).await,
+      Err(Error::ValidationError(errors)) if errors.field_errors().contains_key("tag")
+    ));
+    assert!(matches!(
+      profile_db.set_key_rotation(tagged, Some(30)).await,
+      Err(Error::KeyNotFound { .. })
+    ));
+  }
+
+  #[tokio::test]
+  async fn tag_policies_ignore_case() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db
+      .insert_key(key_data("github.com", 1, &["work"]))
+      .await
+      .unwrap();
+
+    profile_db.set_tag_rotation("Work", Some(90)).await.unwrap();
+    profile_db.set_tag_rotation("WORK", Some(30)).await.unwrap();
+
+    let policies = profile_db.get_rotation_policies().await.unwrap();
+    assert_eq!(1, policies.len());
+    assert_eq!(30, policies[0].rotation_days);
+
+    let due = profile_db.get_keys_due_for_rotation(30).await.unwrap();
+    assert_eq!(
+      vec![key_id],
+      due.iter().map(|e| e.key.id).collect::<Vec<i64>>()
+    );
+
+    profile_db.set_tag_rotation("work", None).await.unwrap();
+    assert!(profile_db.get_rotation_policies().await.unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn backup_keeps_rotations() {
+    let content_dir = std::env::temp_dir().join("keywich_rotation_backup");
+    std::fs::create_dir_all(&content_dir).unwrap();
+    let backup_path = content_dir.join("backup.kwb");
+    let _ = std::fs::remove_file(&backup_path);
+
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db
+      .insert_key(key_data("github.com", 1, &["work"]))
+      .await
+      .unwrap();
+    profile_db.rotate_key(key_id).await.unwrap();
+    profile_db.set_key_rotation(key_id, Some(45)).await.unwrap();
+    profile_db.set_tag_rotation("work", Some(90)).await.unwrap();
+    profile_db
+      .backup(BackupOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    let restored_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    restored_db
+      .restore(RestoreOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    assert_eq!(
+      2,
+      restored_db.get_key_rotations(key_id).await.unwrap().len()
+    );
+    let key = restored_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert_eq!(Some(45), key.rotation_days);
+
+    let policies = restored_db.get_rotation_policies().await.unwrap();
+    assert_eq!("work", policies[0].tag);
+    assert_eq!(90, policies[0].rotation_days);
+
+    let _ = std::fs::remove_dir_all(&content_dir);
+  }
+}
@@ -0,0 +1,167 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::profile::keys::{KeyData, SearchIndexReport, SearchQuery};
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+
+  fn key_data(domain: &str, tags: &[&str]) -> KeyData {
+    KeyData {
+      notes: Some("Notes".into()),
+      domain: domain.into(),
+      username: "Çağrı".into(),
+      tags: TagList::from(tags),
+      ..Default::default()
+    }
+  }
+
+  async fn search_ids(profile_db: &ProfileDB, query: &str) -> Vec<i64> {
+    let mut ids: Vec<i64> = profile_db
+      .search_keys(SearchQuery::new(query))
+      .await
+      .unwrap()
+      .iter()
+      .map(|e| e.id)
+      .collect();
+    ids.sort();
+    ids
+  }
+
+  #[tokio::test]
+  async fn valid_index() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db
+      .insert_key(key_data("github.com", &["work", "dev"]))
+      .await
+      .unwrap();
+    profile_db
+      .insert_key(key_data("gitlab.com", &[]))
+      .await
+      .unwrap();
+    profile_db
+      .update_key(key_id, key_data("github.com", &["dev", "personal"]))
+      .await
+      .unwrap();
+    profile_db.delete_key(key_id).await.unwrap();
+
+    let report = profile_db.verify_search_index().await.unwrap();
+
+    assert!(report.is_valid());
+  }
+
+  #[tokio::test]
+  async fn broken_index() {
+    let db_dir = std::env::temp_dir().join("keywich_search_index_test");
+    std::fs::create_dir_all(&db_dir).unwrap();
+    let db_path = db_dir.join("profile.db");
+    let _ = std::fs::remove_file(&db_path);
+    let connection_str = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    let profile_db = ProfileDB::connect(&connection_str).await.unwrap();
+    let first_key = profile_db
+      .insert_key(key_data("github.com", &["dev"]))
+      .await
+      .unwrap();
+    let second_key = profile_db
+      .insert_key(key_data("gitlab.com", &["dev"]))
+      .await
+      .unwrap();
+
+    let pool = sqlx::SqlitePool::connect(&connection_str).await.unwrap();
+    sqlx::query("DELETE FROM search_index WHERE ROWID = ?")
+      .bind(first_key)
+      .execute(&pool)
+      .await
+      .unwrap();
+    sqlx::query("UPDATE search_trigram SET domain = 'bitbucket.org' WHERE ROWID = ?")
+      .bind(second_key)
+      .execute(&pool)
+      .await
+      .unwrap();
+    sqlx::query(
+      "INSERT INTO search_index (ROWID, domain, username, tags) VALUES (999, 'a', 'b', 'c')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool.close().await;
+
+    assert_eq!(
+      SearchIndexReport {
+        missing: vec![first_key],
+        stale: vec![second_key],
+        orphaned: vec![999],
+      },
+      profile_db.verify_search_index().await.unwrap()
+    );
+    assert_eq!(vec![second_key], search_ids(&profile_db, "dev").await);
+
+    profile_db.rebuild_search_index().await.unwrap();
+
+    assert!(profile_db.verify_search_index().await.unwrap().is_valid());
+    assert_eq!(
+      vec![first_key, second_key],
+      search_ids(&profile_db, "dev").await
+    );
+
+    drop(profile_db);
+    let _ = std::fs::remove_dir_all(&db_dir);
+  }
+
+  #[tokio::test]
+  async fn trigram_backfill_on_upgrade() {
+    let db_dir = std::env::temp_dir().join("keywich_trigram_backfill_test");
+    let migration_dir = db_dir.join("migrations");
+    let _ = std::fs::remove_dir_all(&db_dir);
+    std::fs::create_dir_all(&migration_dir).unwrap();
+
+    for migration in [
+      "01_initial.sql",
+      "02_unambiguous_charsets.sql",
+      "03_key_list_indexes.sql",
+    ] {
+      std::fs::copy(
+        std::path::Path::new("src/migrations").join(migration),
+        migration_dir.join(migration),
+      )
+      .unwrap();
+    }
+
+    let db_path = db_dir.join("profile.db");
+    let connection_str = format!("sqlite:{}?mode=rwc", db_path.display());
+    let pool = sqlx::SqlitePool::connect(&connection_str).await.unwrap();
+    sqlx::migrate::Migrator::new(migration_dir.as_path())
+      .await
+      .unwrap()
+      .run(&pool)
+      .await
+      .unwrap();
+    sqlx::query(
+      "INSERT INTO keys (id, target_size, revision, charset, domain, username, created_at, version)
+       VALUES (1, 12, 1, 'a..z', 'Şirket.com', 'Çağrı', 0, 'kw_scrypt:v1');
+       INSERT INTO search_index (ROWID, domain, username, notes, tags)
+       VALUES (1, 'Şirket.com', 'Çağrı', NULL, '');",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool.close().await;
+
+    let profile_db = ProfileDB::connect(&connection_str).await.unwrap();
+
+    assert!(profile_db.verify_search_index().await.unwrap().is_valid());
+    assert_eq!(vec![1], search_ids(&profile_db, "cagr").await);
+    assert_eq!(vec![1], search_ids(&profile_db, "sirke").await);
+
+    drop(profile_db);
+    let _ = std::fs::remove_dir_all(&db_dir);
+  }
+
+  #[tokio::test]
+  async fn rebuild_empty_profile() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+
+    profile_db.rebuild_search_index().await.unwrap();
+
+    assert!(profile_db.verify_search_index().await.unwrap().is_valid());
+  }
+}
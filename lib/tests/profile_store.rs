@@ -0,0 +1,373 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::errors::Error;
+  use keywich_lib::profile::backup::{BackupOptions, BackupTarget, RestoreOptions};
+  use keywich_lib::profile::charsets::CharsetItem;
+  use keywich_lib::profile::keys::{KeyData, KeyKind, KeyUrl, SearchQuery};
+  use keywich_lib::profile::store::{MemoryProfileStore, ProfileStore};
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+  use std::path::PathBuf;
+
+  fn key_data(domain: &str, username: &str, tags: &[&str]) -> KeyData {
+    KeyData {
+      domain: domain.into(),
+      username: username.into(),
+      charset: "a..z0..9".into(),
+      tags: TagList::from(tags),
+      ..Default::default()
+    }
+  }
+
+  /// Result order is store specific, ids are sorted.
+  async fn search_ids<S: ProfileStore>(store: &S, query: &str) -> Vec<i64> {
+    let mut ids: Vec<i64> = store
+      .search_keys(SearchQuery::new(query))
+      .await
+      .unwrap()
+      .iter()
+      .map(|e| e.id)
+      .collect();
+    ids.sort();
+    ids
+  }
+
+  async fn key_operations<S: ProfileStore>(store: S) {
+    let github = store
+      .insert_key(key_data("github.com", "octocat", &["dev"]))
+      .await
+      .unwrap();
+    let router = store
+      .insert_key(KeyData {
+        kind: KeyKind::Stored,
+        secret: Some("wifi-pass".into()),
+        ..key_data("home-router", "admin", &["home"])
+      })
+      .await
+      .unwrap();
+    let bank = store
+      .insert_key(key_data("bank.com", "octocat", &["finance"]))
+      .await
+      .unwrap();
+
+    assert!(matches!(
+      store
+        .insert_key(KeyData {
+          target_size: 0,
+          ..key_data("invalid.com", "user", &[])
+        })
+        .await,
+      Err(Error::ValidationError(_))
+    ));
+
+    store.update_pin_status(bank, true).await.unwrap();
+    assert_eq!(
+      vec![bank],
+      store
+        .get_keys(true)
+        .await
+        .unwrap()
+        .iter()
+        .map(|e| e.id)
+        .collect::<Vec<i64>>()
+    );
+    assert_eq!(
+      Some(String::from("wifi-pass")),
+      store.get_key_secret(router).await.unwrap()
+    );
+    assert_eq!(None, store.get_key_secret(github).await.unwrap());
+
+    store
+      .update_key(github, key_data("github.com", "monalisa", &["dev", "work"]))
+      .await
+      .unwrap();
+    let key = store.get_key_by_id(github).await.unwrap().unwrap();
+    assert_eq!("monalisa", key.username);
+    assert!(key.updated_at.is_some());
+    assert!(matches!(
+      store
+        .update_key(999, key_data("missing.com", "user", &[]))
+        .await,
+      Err(Error::KeyNotFound { key_id: 999 })
+    ));
+
+    assert!(store.delete_key(router).await.unwrap());
+    assert!(!store.delete_key(router).await.unwrap());
+    assert_eq!(2, store.get_keys(false).await.unwrap().len());
+    assert!(store
+      .get_key_by_id(router)
+      .await
+      .unwrap()
+      .unwrap()
+      .deleted_at
+      .is_some());
+  }
+
+  async fn search<S: ProfileStore>(store: S) {
+    let github = store
+      .insert_key(KeyData {
+        notes: Some("Work account".into()),
+        urls: vec![KeyUrl {
+          url: "gist.github.com".into(),
+          match_rule: Default::default(),
+        }],
+        ..key_data("github.com", "octocat", &["dev"])
+      })
+      .await
+      .unwrap();
+    let gitlab = store
+      .insert_key(key_data("gitlab.com", "Çağrı", &["dev"]))
+      .await
+      .unwrap();
+    let bank = store
+      .insert_key(KeyData {
+        charset: "0..9".into(),
+        ..key_data("bank.com", "octocat", &["finance"])
+      })
+      .await
+      .unwrap();
+    store.update_pin_status(bank, true).await.unwrap();
+
+    assert_eq!(vec![github, gitlab, bank], search_ids(&store, "").await);
+    assert_eq!(vec![github, gitlab], search_ids(&store, "tag:dev").await);
+    assert_eq!(vec![github], search_ids(&store, "gist").await);
+    assert_eq!(vec![github], search_ids(&store, "\"work account\"").await);
+    assert_eq!(vec![gitlab], search_ids(&store, "cagri").await);
+    assert_eq!(vec![github, gitlab], search_ids(&store, "git*").await);
+    assert_eq!(vec![gitlab], search_ids(&store, "git* -octocat").await);
+    assert_eq!(
+      vec![gitlab, bank],
+      search_ids(&store, "tag:finance OR domain:gitlab").await
+    );
+    assert_eq!(vec![bank], search_ids(&store, "pinned:true").await);
+    assert_eq!(vec![bank], search_ids(&store, "charset:Numeric").await);
+    assert_eq!(vec![bank], search_ids(&store, "charset:numeric").await);
+    assert_eq!(
+      vec![github],
+      search_ids(&store, "octocat charset:a..z0..9").await
+    );
+  }
+
+  async fn charsets<S: ProfileStore>(store: S) {
+    let names = |charsets: Vec<CharsetItem>| {
+      let mut names: Vec<String> = charsets.into_iter().map(|e| e.name).collect();
+      names.sort();
+      names
+    };
+    assert_eq!(
+      vec![
+        "Alpha",
+        "Alpha (unambiguous)",
+        "Alpha Numeric",
+        "Alpha Numeric (unambiguous)",
+        "Numeric",
+        "Numeric (unambiguous)"
+      ],
+      names(store.get_charsets().await.unwrap())
+    );
+
+    store
+      .insert_charset(CharsetItem {
+        name: "Hex".into(),
+        charset: "0..9a..f".into(),
+        description: None,
+      })
+      .await
+      .unwrap();
+    assert!(matches!(
+      store
+        .insert_charset(CharsetItem {
+          name: "Digits".into(),
+          charset: "0..9".into(),
+          description: None,
+        })
+        .await,
+      Err(Error::DuplicateCharset(name)) if name == "Numeric"
+    ));
+    assert!(matches!(
+      store
+        .insert_charset(CharsetItem {
+          name: "HEX".into(),
+          charset: "g..k".into(),
+          description: None,
+        })
+        .await,
+      Err(Error::DuplicateCharsetName(name)) if name == "HEX"
+    ));
+
+    store
+      .update_charset(CharsetItem {
+        name: "hex".into(),
+        charset: "0..9A..F".into(),
+        description: Some("Uppercase".into()),
+      })
+      .await
+      .unwrap();
+    store.rename_charset("Hex", "Hexadecimal").await.unwrap();
+    assert!(matches!(
+      store.rename_charset("Hexadecimal", "alpha").await,
+      Err(Error::DuplicateCharsetName(_))
+    ));
+    assert!(matches!(
+      store.rename_charset("Hex", "Other").await,
+      Err(Error::CharsetNotFound(_))
+    ));
+
+    let key_id = store
+      .insert_key(KeyData {
+        charset: "A..F0..9".into(),
+        ..key_data("router", "admin", &[])
+      })
+      .await
+      .unwrap();
+    let usage = store.get_charset_usage().await.unwrap();
+    assert_eq!(
+      vec![key_id],
+      usage
+        .into_iter()
+        .find(|e| e.name == "Hexadecimal")
+        .unwrap()
+        .key_ids
+    );
+    assert!(matches!(
+      store.delete_charset("Hexadecimal", false).await,
+      Err(Error::CharsetInUse { key_count: 1, .. })
+    ));
+    store.delete_key(key_id).await.unwrap();
+
+    assert_eq!(1, store.delete_charset("Hexadecimal", false).await.unwrap());
+    assert_eq!(0, store.delete_charset("Hexadecimal", false).await.unwrap());
+    assert_eq!(6, store.get_charsets().await.unwrap().len());
+  }
+
+  async fn backup_to<S: ProfileStore, T: ProfileStore>(source: S, target: T, name: &str) {
+    let content_dir: PathBuf = std::env::temp_dir().join(name);
+    std::fs::create_dir_all(&content_dir).unwrap();
+    let backup_path = content_dir.join("backup.kwb");
+    let _ = std::fs::remove_file(&backup_path);
+
+    let github = source
+      .insert_key(key_data("github.com", "octocat", &["dev"]))
+      .await
+      .unwrap();
+    let router = source
+      .insert_key(KeyData {
+        kind: KeyKind::Stored,
+        secret: Some("wifi-pass".into()),
+        ..key_data("home-router", "admin", &["home"])
+      })
+      .await
+      .unwrap();
+    source.delete_key(router).await.unwrap();
+    source
+      .insert_charset(CharsetItem {
+        name: "Hex".into(),
+        charset: "0..9a..f".into(),
+        description: None,
+      })
+      .await
+      .unwrap();
+    source
+      .backup(BackupOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    target
+      .restore(RestoreOptions {
+        content_dir: content_dir.clone(),
+        target: BackupTarget::File(backup_path.clone()),
+        sign_key: Box::from(b"sign_key".as_slice()),
+      })
+      .await
+      .unwrap();
+
+    assert_eq!(
+      vec![github],
+      target
+        .get_keys(false)
+        .await
+        .unwrap()
+        .iter()
+        .map(|e| e.id)
+        .collect::<Vec<i64>>()
+    );
+    assert_eq!(vec![github], search_ids(&target, "tag:dev").await);
+    assert!(target
+      .get_key_by_id(router)
+      .await
+      .unwrap()
+      .unwrap()
+      .deleted_at
+      .is_some());
+    assert_eq!(
+      Some(String::from("wifi-pass")),
+      target.get_key_secret(router).await.unwrap()
+    );
+    assert_eq!(7, target.get_charsets().await.unwrap().len());
+
+    let next_key = target
+      .insert_key(key_data("bank.com", "octocat", &[]))
+      .await
+      .unwrap();
+    assert!(next_key > router);
+
+    let _ = std::fs::remove_dir_all(&content_dir);
+  }
+
+  #[tokio::test]
+  async fn sqlite_store() {
+    key_operations(ProfileDB::connect("sqlite::memory:").await.unwrap()).await;
+    search(ProfileDB::connect("sqlite::memory:").await.unwrap()).await;
+    charsets(ProfileDB::connect("sqlite::memory:").await.unwrap()).await;
+  }
+
+  #[tokio::test]
+  async fn default_charsets() {
+    let sorted = |mut charsets: Vec<CharsetItem>| {
+      charsets.sort_by(|a, b| a.name.cmp(&b.name));
+      charsets
+        .into_iter()
+        .map(|e| (e.name, e.charset, e.description))
+        .collect::<Vec<(String, String, Option<String>)>>()
+    };
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+
+    assert_eq!(
+      sorted(profile_db.get_charsets().await.unwrap()),
+      sorted(MemoryProfileStore::new().get_charsets().await.unwrap())
+    );
+  }
+
+  #[tokio::test]
+  async fn memory_store() {
+    key_operations(MemoryProfileStore::new()).await;
+    search(MemoryProfileStore::new()).await;
+    charsets(MemoryProfileStore::new()).await;
+  }
+
+  #[tokio::test]
+  async fn backup_between_stores() {
+    backup_to(
+      MemoryProfileStore::new(),
+      ProfileDB::connect("sqlite::memory:").await.unwrap(),
+      "keywich_memory_to_sqlite_backup",
+    )
+    .await;
+    backup_to(
+      ProfileDB::connect("sqlite::memory:").await.unwrap(),
+      MemoryProfileStore::new(),
+      "keywich_sqlite_to_memory_backup",
+    )
+    .await;
+    backup_to(
+      MemoryProfileStore::new(),
+      MemoryProfileStore::new(),
+      "keywich_memory_to_memory_backup",
+    )
+    .await;
+  }
+}
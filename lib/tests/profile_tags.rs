@@ -0,0 +1,139 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::errors::Error;
+  use keywich_lib::profile::keys::{KeyData, SearchQuery};
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+
+  fn key_data(domain: &str, tags: &[&str]) -> KeyData {
+    KeyData {
+      domain: domain.into(),
+      username: "username".into(),
+      tags: TagList::from(tags),
+      ..Default::default()
+    }
+  }
+
+  fn sorted_tags(tags: &TagList) -> Vec<String> {
+    let mut tags: Vec<String> = tags.iter().map(|e| e.to_string()).collect();
+    tags.sort();
+    tags
+  }
+
+  async fn search_ids(profile_db: &ProfileDB, query: &str) -> Vec<i64> {
+    let mut ids: Vec<i64> = profile_db
+      .search_keys(SearchQuery::new(query))
+      .await
+      .unwrap()
+      .iter()
+      .map(|e| e.id)
+      .collect();
+    ids.sort();
+    ids
+  }
+
+  #[tokio::test]
+  async fn list_tags() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    profile_db
+      .insert_key(key_data("github.com", &["work", "code"]))
+      .await
+      .unwrap();
+    profile_db
+      .insert_key(key_data("gitlab.com", &["work"]))
+      .await
+      .unwrap();
+    let trashed = profile_db
+      .insert_key(key_data("example.org", &["work", "old"]))
+      .await
+      .unwrap();
+    profile_db.delete_key(trashed).await.unwrap();
+
+    let tags = profile_db.list_tags().await.unwrap();
+    assert_eq!(
+      vec![("code", 1), ("work", 2)],
+      tags
+        .iter()
+        .map(|e| (e.name.as_str(), e.key_count))
+        .collect::<Vec<(&str, i64)>>()
+    );
+  }
+
+  #[tokio::test]
+  async fn rename_tag() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let first = profile_db
+      .insert_key(key_data("github.com", &["wrk"]))
+      .await
+      .unwrap();
+    let second = profile_db
+      .insert_key(key_data("gitlab.com", &["wrk", "work"]))
+      .await
+      .unwrap();
+    profile_db.set_tag_rotation("wrk", Some(30)).await.unwrap();
+
+    assert_eq!(2, profile_db.rename_tag("wrk", "work").await.unwrap());
+
+    for key_id in [first, second] {
+      let key = profile_db.get_key_by_id(key_id).await.unwrap().unwrap();
+      assert_eq!(vec!["work"], sorted_tags(&key.tags));
+    }
+
+    assert_eq!(
+      vec![first, second],
+      search_ids(&profile_db, "tag:work").await
+    );
+    assert!(search_ids(&profile_db, "tag:wrk").await.is_empty());
+
+    let policies = profile_db.get_rotation_policies().await.unwrap();
+    assert_eq!(1, policies.len());
+    assert_eq!("work", policies[0].tag);
+
+    assert_eq!(2, profile_db.rename_tag("WORK", "Work").await.unwrap());
+    let key = profile_db.get_key_by_id(first).await.unwrap().unwrap();
+    assert_eq!(vec!["Work"], sorted_tags(&key.tags));
+
+    assert!(matches!(
+      profile_db.rename_tag("Work", " ").await,
+      Err(Error::ValidationError(errors)) if errors.field_errors().contains_key("target")
+    ));
+    assert!(matches!(
+      profile_db.merge_tags(&[], "Work").await,
+      Err(Error::ValidationError(errors)) if errors.field_errors().contains_key("sources")
+    ));
+  }
+
+  #[tokio::test]
+  async fn merge_and_delete_tags() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let first = profile_db
+      .insert_key(key_data("github.com", &["dev", "code"]))
+      .await
+      .unwrap();
+    let second = profile_db
+      .insert_key(key_data("gitlab.com", &["code", "other"]))
+      .await
+      .unwrap();
+
+    assert_eq!(
+      2,
+      profile_db
+        .merge_tags(&["dev", "code"], "development")
+        .await
+        .unwrap()
+    );
+
+    let key = profile_db.get_key_by_id(first).await.unwrap().unwrap();
+    assert_eq!(vec!["development"], sorted_tags(&key.tags));
+    let key = profile_db.get_key_by_id(second).await.unwrap().unwrap();
+    assert_eq!(vec!["development", "other"], sorted_tags(&key.tags));
+
+    assert_eq!(1, profile_db.delete_tag("other").await.unwrap());
+    assert_eq!(0, profile_db.delete_tag("missing").await.unwrap());
+    assert!(search_ids(&profile_db, "tag:other").await.is_empty());
+    assert_eq!(
+      vec![first, second],
+      search_ids(&profile_db, "tag:development").await
+    );
+  }
+}
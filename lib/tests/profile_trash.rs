@@ -0,0 +1,146 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::errors::Error;
+  use keywich_lib::profile::keys::{KeyData, KeyListOptions, SearchQuery};
+  use keywich_lib::profile::utils::tag_list::TagList;
+  use keywich_lib::profile::ProfileDB;
+
+  fn key_data(domain: &str) -> KeyData {
+    KeyData {
+      domain: domain.into(),
+      username: "username".into(),
+      tags: TagList::from(["tag1"]),
+      ..Default::default()
+    }
+  }
+
+  #[tokio::test]
+  async fn trashed_keys_are_hidden() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let kept = profile_db.insert_key(key_data("github.com")).await.unwrap();
+    let trashed = profile_db
+      .insert_key(key_data("example.org"))
+      .await
+      .unwrap();
+
+    assert!(profile_db.delete_key(trashed).await.unwrap());
+    assert!(!profile_db.delete_key(trashed).await.unwrap());
+
+    let keys = profile_db.get_keys(false).await.unwrap();
+    assert_eq!(vec![kept], keys.iter().map(|e| e.id).collect::<Vec<i64>>());
+
+    let page = profile_db
+      .get_keys_page(KeyListOptions::default())
+      .await
+      .unwrap();
+    assert_eq!(1, page.total);
+    assert_eq!(1, profile_db.count_keys(false).await.unwrap());
+
+    assert!(profile_db
+      .search_keys(SearchQuery::new("example"))
+      .await
+      .unwrap()
+      .is_empty());
+    assert!(profile_db
+      .search_keys(SearchQuery::new("exmaple"))
+      .await
+      .unwrap()
+      .is_empty());
+
+    let trashed_key = profile_db.get_key_by_id(trashed).await.unwrap().unwrap();
+    assert!(trashed_key.deleted_at.is_some());
+  }
+
+  #[tokio::test]
+  async fn restore_key() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db.insert_key(key_data("github.com")).await.unwrap();
+
+    assert!(matches!(
+      profile_db.restore_key(key_id).await,
+      Err(Error::KeyNotFound { .. })
+    ));
+
+    profile_db.delete_key(key_id).await.unwrap();
+    let trash = profile_db.get_trashed_keys().await.unwrap();
+    assert_eq!(1, trash.len());
+    assert_eq!(key_id, trash[0].id);
+
+    profile_db.restore_key(key_id).await.unwrap();
+    assert!(profile_db.get_trashed_keys().await.unwrap().is_empty());
+
+    let result = profile_db
+      .search_keys(SearchQuery::new("github"))
+      .await
+      .unwrap();
+    assert_eq!(key_id, result[0].id);
+    assert_eq!("tag1", result[0].tags.join(' '));
+  }
+
+  #[tokio::test]
+  async fn purge_trash() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db.insert_key(key_data("github.com")).await.unwrap();
+    let active = profile_db.insert_key(key_data("gitlab.com")).await.unwrap();
+    profile_db.delete_key(key_id).await.unwrap();
+
+    assert!(profile_db.purge_trash(30).await.unwrap().is_empty());
+    assert_eq!(1, profile_db.get_trashed_keys().await.unwrap().len());
+
+    let purged = profile_db.purge_trash(0).await.unwrap();
+    assert_eq!(
+      vec![key_id],
+      purged.iter().map(|e| e.id).collect::<Vec<i64>>()
+    );
+    assert!(profile_db.get_trashed_keys().await.unwrap().is_empty());
+    assert!(profile_db.get_key_by_id(key_id).await.unwrap().is_none());
+    assert!(profile_db.get_key_by_id(active).await.unwrap().is_some());
+  }
+
+  #[tokio::test]
+  async fn purge_live_key() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db.insert_key(key_data("github.com")).await.unwrap();
+
+    assert!(matches!(
+      profile_db.purge_key(key_id).await,
+      Err(Error::KeyNotFound { .. })
+    ));
+
+    let key = profile_db.get_key_by_id(key_id).await.unwrap().unwrap();
+    assert!(key.deleted_at.is_none());
+    assert_eq!("tag1", key.tags.join(' '));
+    assert_eq!(
+      vec![key_id],
+      profile_db
+        .search_keys(SearchQuery::new("github"))
+        .await
+        .unwrap()
+        .iter()
+        .map(|e| e.id)
+        .collect::<Vec<i64>>()
+    );
+  }
+
+  #[tokio::test]
+  async fn trashed_keys_are_read_only() {
+    let profile_db = ProfileDB::connect("sqlite::memory:").await.unwrap();
+    let key_id = profile_db.insert_key(key_data("github.com")).await.unwrap();
+    profile_db.delete_key(key_id).await.unwrap();
+
+    assert!(matches!(
+      profile_db.update_key(key_id, key_data("gitlab.com")).await,
+      Err(Error::KeyNotFound { .. })
+    ));
+    assert!(matches!(
+      profile_db.update_pin_status(key_id, true).await,
+      Err(Error::KeyNotFound { .. })
+    ));
+
+    let trashed = profile_db.get_trashed_keys().await.unwrap();
+    assert_eq!(1, trashed.len());
+    assert_eq!("github.com", trashed[0].domain);
+    assert!(!trashed[0].pinned);
+    assert!(profile_db.get_key_history(key_id).await.unwrap().is_empty());
+  }
+}
@@ -0,0 +1,198 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::profile::search_query::{
+    Comparison, SearchExpr, SearchField, SearchFilter, SearchQuery, SearchTerm, TermKind,
+  };
+
+  fn term(field: Option<SearchField>, text: &str, kind: TermKind) -> SearchExpr {
+    SearchExpr::Term(SearchTerm {
+      field,
+      text: text.into(),
+      kind,
+    })
+  }
+
+  fn word(text: &str) -> SearchExpr {
+    term(None, text, TermKind::Word)
+  }
+
+  #[test]
+  fn blank_query() {
+    assert_eq!(None, SearchQuery::new("").expr);
+    assert_eq!(None, SearchQuery::new("  ( ) - ").expr);
+    assert_eq!(None, SearchQuery::new("").to_fts_query());
+  }
+
+  #[test]
+  fn implicit_and() {
+    let query = SearchQuery::new("bank account");
+    assert_eq!(
+      Some(SearchExpr::And(vec![word("bank"), word("account")])),
+      query.expr
+    );
+  }
+
+  #[test]
+  fn or_precedence() {
+    let query = SearchQuery::new("a b OR c AND d");
+    assert_eq!(
+      Some(SearchExpr::Or(vec![
+        SearchExpr::And(vec![word("a"), word("b")]),
+        SearchExpr::And(vec![word("c"), word("d")]),
+      ])),
+      query.expr
+    );
+  }
+
+  #[test]
+  fn parentheses() {
+    let query = SearchQuery::new("(a OR b) c");
+    assert_eq!(
+      Some(SearchExpr::And(vec![
+        SearchExpr::Or(vec![word("a"), word("b")]),
+        word("c"),
+      ])),
+      query.expr
+    );
+  }
+
+  #[test]
+  fn unbalanced_parentheses() {
+    assert_eq!(
+      Some(SearchExpr::Or(vec![word("a"), word("b")])),
+      SearchQuery::new("(a OR b").expr
+    );
+    assert_eq!(
+      Some(SearchExpr::And(vec![word("a"), word("b")])),
+      SearchQuery::new("a ) b").expr
+    );
+  }
+
+  #[test]
+  fn quoted_phrase() {
+    let query = SearchQuery::new("\"my bank\" domain:\"example com\"");
+    assert_eq!(
+      Some(SearchExpr::And(vec![
+        term(None, "my bank", TermKind::Phrase),
+        term(Some(SearchField::Domain), "example com", TermKind::Phrase),
+      ])),
+      query.expr
+    );
+  }
+
+  #[test]
+  fn unclosed_phrase() {
+    let query = SearchQuery::new("\"my bank");
+    assert_eq!(Some(term(None, "my bank", TermKind::Phrase)), query.expr);
+  }
+
+  #[test]
+  fn negation() {
+    let query = SearchQuery::new("-tag:old NOT work");
+    assert_eq!(
+      Some(SearchExpr::And(vec![
+        SearchExpr::Not(Box::new(term(
+          Some(SearchField::Tag),
+          "old",
+          TermKind::Word
+        ))),
+        SearchExpr::Not(Box::new(word("work"))),
+      ])),
+      query.expr
+    );
+  }
+
+  #[test]
+  fn hyphenated_word() {
+    let query = SearchQuery::new("my-site");
+    assert_eq!(Some(word("my-site")), query.expr);
+  }
+
+  #[test]
+  fn prefix() {
+    let query = SearchQuery::new("goo* username:adm*");
+    assert_eq!(
+      Some(SearchExpr::And(vec![
+        term(None, "goo", TermKind::Prefix),
+        term(Some(SearchField::Username), "adm", TermKind::Prefix),
+      ])),
+      query.expr
+    );
+  }
+
+  #[test]
+  fn filters() {
+    let query = SearchQuery::new("pinned:true created:>=2024-01-01 charset:\"Alpha Numeric\"");
+    assert_eq!(
+      Some(SearchExpr::And(vec![
+        SearchExpr::Filter(SearchFilter::Pinned(true)),
+        SearchExpr::Filter(SearchFilter::Created(Comparison::Ge, 1704067200)),
+        SearchExpr::Filter(SearchFilter::Charset("Alpha Numeric".into())),
+      ])),
+      query.expr
+    );
+  }
+
+  #[test]
+  fn invalid_filter_values() {
+    let query = SearchQuery::new("pinned:maybe created:2024-02-30");
+    assert_eq!(
+      Some(SearchExpr::And(vec![
+        word("pinned:maybe"),
+        word("created:2024-02-30"),
+      ])),
+      query.expr
+    );
+  }
+
+  #[test]
+  fn out_of_range_dates() {
+    let query = SearchQuery::new("created:999999999999-01-01 created:<0000-01-01");
+    assert_eq!(
+      Some(SearchExpr::And(vec![
+        word("created:999999999999-01-01"),
+        word("created:<0000-01-01"),
+      ])),
+      query.expr
+    );
+
+    let query = SearchQuery::new("created:<=9999-12-31");
+    assert_eq!(
+      Some(SearchExpr::Filter(SearchFilter::Created(
+        Comparison::Le,
+        253402214400
+      ))),
+      query.expr
+    );
+  }
+
+  #[test]
+  fn unknown_field() {
+    let query = SearchQuery::new("https://example.com");
+    assert_eq!(Some(word("https://example.com")), query.expr);
+  }
+
+  #[test]
+  fn fts_query() {
+    let query = SearchQuery::new("tag:work (domain:bank OR \"my notes\") goo* -tag:old");
+    assert_eq!(
+      Some(
+        "({tags}: \"work\" AND ({domain}: \"bank\" OR \"my notes\") AND \"goo\" *) NOT {tags}: \"old\""
+          .into()
+      ),
+      query.to_fts_query()
+    );
+  }
+
+  #[test]
+  fn fts_query_without_filters() {
+    let query = SearchQuery::new("pinned:true bank");
+    assert_eq!(Some("\"bank\"".into()), query.to_fts_query());
+
+    let query = SearchQuery::new("pinned:true OR bank");
+    assert_eq!(None, query.to_fts_query());
+
+    let query = SearchQuery::new("-bank");
+    assert_eq!(None, query.to_fts_query());
+  }
+}
@@ -0,0 +1,25 @@
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+  use keywich_lib::profile::utils::text_fold::fold_text;
+
+  #[test]
+  fn fold_latin_diacritics() {
+    assert_eq!("munchen", fold_text("München"));
+    assert_eq!("strasse", fold_text("Straße"));
+    assert_eq!("creme brulee", fold_text("Crème Brûlée"));
+  }
+
+  #[test]
+  fn fold_turkish_letters() {
+    assert_eq!("istanbul", fold_text("İstanbul"));
+    assert_eq!("isik", fold_text("IŞIK"));
+    assert_eq!("sisli muzesi", fold_text("Şişli Müzesi"));
+    assert_eq!("cagri", fold_text("çağrı"));
+  }
+
+  #[test]
+  fn keep_other_scripts() {
+    assert_eq!("пароль", fold_text("Пароль"));
+    assert_eq!("github.com", fold_text("GitHub.com"));
+  }
+}
@@ -1,8 +1,12 @@
 pub mod backup;
 pub mod charsets;
+pub mod collections;
 pub mod keys;
 pub mod login;
+pub mod maintenance;
 pub mod password;
+pub mod tags;
+pub mod templates;
 pub mod utilities;
 
 #[macro_export]
@@ -11,9 +15,14 @@ macro_rules! generate_keywich_handler {
     tauri::generate_handler![
       $crate::commands::password::generate_password,
       $crate::commands::password::generate_password_from,
+      $crate::commands::password::generate_password_from_history,
       $crate::commands::charsets::get_charsets,
+      $crate::commands::charsets::get_charset_usage,
       $crate::commands::charsets::insert_charset,
+      $crate::commands::charsets::update_charset,
+      $crate::commands::charsets::rename_charset,
       $crate::commands::charsets::delete_charset,
+      $crate::commands::charsets::analyze_charset,
       $crate::commands::keys::get_keys,
       $crate::commands::keys::get_pinned_keys,
       $crate::commands::keys::search_keys,
@@ -23,6 +32,37 @@ macro_rules! generate_keywich_handler {
       $crate::commands::keys::pin_key,
       $crate::commands::keys::unpin_key,
       $crate::commands::keys::get_key_by_id,
+      $crate::commands::keys::get_key_history,
+      $crate::commands::keys::get_stale_keys,
+      $crate::commands::keys::find_keys_for_url,
+      $crate::commands::keys::find_duplicate_keys,
+      $crate::commands::keys::get_trashed_keys,
+      $crate::commands::keys::restore_key,
+      $crate::commands::keys::purge_key,
+      $crate::commands::keys::empty_trash,
+      $crate::commands::keys::rotate_key,
+      $crate::commands::keys::get_key_rotations,
+      $crate::commands::keys::get_keys_due_for_rotation,
+      $crate::commands::keys::set_key_rotation,
+      $crate::commands::keys::get_rotation_policies,
+      $crate::commands::keys::set_tag_rotation,
+      $crate::commands::keys::bulk_update_keys,
+      $crate::commands::tags::get_tags,
+      $crate::commands::tags::rename_tag,
+      $crate::commands::tags::merge_tags,
+      $crate::commands::tags::delete_tag,
+      $crate::commands::collections::get_collections,
+      $crate::commands::collections::create_collection,
+      $crate::commands::collections::create_collection_path,
+      $crate::commands::collections::rename_collection,
+      $crate::commands::collections::move_collection,
+      $crate::commands::collections::delete_collection,
+      $crate::commands::collections::move_keys_to_collection,
+      $crate::commands::templates::get_templates,
+      $crate::commands::templates::insert_template,
+      $crate::commands::templates::update_template,
+      $crate::commands::templates::delete_template,
+      $crate::commands::templates::insert_key_from_template,
       $crate::commands::utilities::get_config_path,
       $crate::commands::utilities::get_locale_path,
       $crate::commands::utilities::process_icon,
@@ -31,7 +71,9 @@ macro_rules! generate_keywich_handler {
       $crate::commands::login::lock_db,
       $crate::commands::backup::backup_profile_db,
       $crate::commands::backup::restore_profile_db,
-      $crate::commands::backup::verify_backup
+      $crate::commands::backup::verify_backup,
+      $crate::commands::maintenance::verify_search_index,
+      $crate::commands::maintenance::rebuild_search_index
     ]
   };
 }
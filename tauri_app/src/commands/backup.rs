@@ -3,7 +3,10 @@ use keywich_lib::profile::backup::{
   file_backup::FileBackupReader, reader::BackupReader, BackupOptions, BackupTarget, RestoreOptions,
 };
 use serde::Serialize;
-use std::{ops::Deref, path::Path};
+use std::{
+  ops::Deref,
+  path::{Path, PathBuf},
+};
 use tauri::{AppHandle, State};
 
 #[derive(Debug, Serialize)]
@@ -57,6 +60,7 @@ pub async fn backup_profile_db(
 #[tauri::command(rename_all = "snake_case")]
 pub async fn restore_profile_db(
   state: State<'_, AppDbState>,
+  key_state: State<'_, KeyState>,
   app: AppHandle,
   import_path: String,
 ) -> Result<(), AppErrors> {
@@ -73,10 +77,23 @@ pub async fn restore_profile_db(
     std::fs::create_dir(&content_dir).map_err(|_| AppErrors::ContentPathFailed)?;
   }
 
+  // Stored secrets are encrypted with the key used while creating the backup.
+  let password = match key_state.entry.get_password() {
+    Ok(password) => password,
+    Err(err @ keyring::Error::NoEntry) => {
+      let _ = app.emit_unlock_required();
+      return Err(err.into());
+    }
+    Err(err) => {
+      return Err(err.into());
+    }
+  };
+
   if let Some(db) = read_lock.deref() {
     let options = RestoreOptions {
       content_dir,
       target: BackupTarget::File(import_path.into()),
+      sign_key: Box::from(password.as_bytes()),
     };
 
     db.restore(options).await.log_err()?;
@@ -106,7 +123,13 @@ pub async fn verify_backup(
   };
 
   let fd = std::fs::File::open(&import_path)
-    .map_err(|err| AppErrors::BackupError(err.to_string()))
+    .map_err(|err| {
+      AppErrors::BackupError(keywich_lib::errors::Error::BackupError {
+        reason: String::from("Unable to open backup file."),
+        path: Some(PathBuf::from(&import_path)),
+        source: Some(Box::new(err)),
+      })
+    })
     .log_err()?;
   let mut reader = FileBackupReader::new(fd)?;
   let result = reader.verify_digest(password.as_bytes())?;
@@ -1,9 +1,10 @@
 use crate::errors::AppErrors;
+use crate::result_log::ResultLog;
 use crate::{AppDbState, DbNotifier};
-use keywich_lib::profile::charsets::CharsetItem;
+use keywich_lib::charset::analysis::CharsetAnalysis;
+use keywich_lib::profile::charsets::{CharsetItem, CharsetUsage};
 use std::ops::Deref;
 use tauri::{AppHandle, State};
-use crate::result_log::ResultLog;
 
 #[tauri::command(rename_all = "snake_case")]
 pub async fn get_charsets(
@@ -21,6 +22,22 @@ pub async fn get_charsets(
   }
 }
 
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_charset_usage(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+) -> Result<Vec<CharsetUsage>, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let result = profile_db.get_charset_usage().await.log_err()?;
+    Ok(result)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
 #[tauri::command(rename_all = "snake_case")]
 pub async fn insert_charset(
   state: State<'_, AppDbState>,
@@ -38,19 +55,67 @@ pub async fn insert_charset(
   }
 }
 
+#[tauri::command(rename_all = "snake_case")]
+pub async fn update_charset(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  charset: CharsetItem,
+) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    profile_db.update_charset(charset).await.log_err()?;
+    Ok(())
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn rename_charset(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  name: String,
+  new_name: String,
+) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    profile_db
+      .rename_charset(&name, &new_name)
+      .await
+      .log_err()?;
+    Ok(())
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
 #[tauri::command(rename_all = "snake_case")]
 pub async fn delete_charset(
   state: State<'_, AppDbState>,
   app: AppHandle,
   name: String,
+  force: Option<bool>,
 ) -> Result<(), AppErrors> {
   let read_lock = state.profile_db.read().await;
 
   if let Some(profile_db) = read_lock.deref() {
-    profile_db.delete_charset(&name).await.log_err()?;
+    profile_db
+      .delete_charset(&name, force.unwrap_or(false))
+      .await
+      .log_err()?;
     Ok(())
   } else {
     let _ = app.emit_unlock_required();
     Err(AppErrors::DbNotInitialized)
   }
 }
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn analyze_charset(charset: String) -> Result<CharsetAnalysis, AppErrors> {
+  let analysis = keywich_lib::charset::analysis::analyze_charset(&charset).log_err()?;
+  Ok(analysis)
+}
@@ -0,0 +1,143 @@
+use crate::errors::AppErrors;
+use crate::result_log::ResultLog;
+use crate::{AppDbState, DbNotifier};
+use keywich_lib::profile::collections::CollectionItem;
+use std::ops::Deref;
+use tauri::{AppHandle, State};
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_collections(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+) -> Result<Vec<CollectionItem>, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let collections = profile_db.get_collections().await.log_err()?;
+    Ok(collections)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn create_collection(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  name: String,
+  parent_id: Option<i64>,
+) -> Result<i64, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let collection_id = profile_db
+      .create_collection(&name, parent_id)
+      .await
+      .log_err()?;
+    Ok(collection_id)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn create_collection_path(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  path: String,
+) -> Result<i64, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let collection_id = profile_db.create_collection_path(&path).await.log_err()?;
+    Ok(collection_id)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn rename_collection(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  collection_id: i64,
+  name: String,
+) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    profile_db
+      .rename_collection(collection_id, &name)
+      .await
+      .log_err()?;
+    Ok(())
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn move_collection(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  collection_id: i64,
+  parent_id: Option<i64>,
+) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    profile_db
+      .move_collection(collection_id, parent_id)
+      .await
+      .log_err()?;
+    Ok(())
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn delete_collection(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  collection_id: i64,
+) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    profile_db
+      .delete_collection(collection_id)
+      .await
+      .log_err()?;
+    Ok(())
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn move_keys_to_collection(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  key_ids: Vec<i64>,
+  collection_id: Option<i64>,
+) -> Result<u64, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let updated = profile_db
+      .move_keys_to_collection(&key_ids, collection_id)
+      .await
+      .log_err()?;
+    Ok(updated)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
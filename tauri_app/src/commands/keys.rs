@@ -1,7 +1,13 @@
 use crate::errors::AppErrors;
 use crate::result_log::ResultLog;
 use crate::{AppDbState, DbNotifier};
-use keywich_lib::profile::keys::{KeyData, KeyItem};
+use keywich_lib::profile::key_history::KeyHistoryItem;
+use keywich_lib::profile::keys::{
+  DuplicateGroup, KeyBulkAction, KeyData, KeyItem, KeyListOptions, KeyPage, KeyRotationItem,
+  KeyWriteOptions, RotationDueItem, RotationPolicy, SearchResult, UrlMatchItem,
+};
+use keywich_lib::profile::ProfileDB;
+use log::info;
 use std::ops::Deref;
 use tauri::{AppHandle, State};
 
@@ -9,11 +15,15 @@ use tauri::{AppHandle, State};
 pub async fn get_keys(
   state: State<'_, AppDbState>,
   app: AppHandle,
-) -> Result<Vec<KeyItem>, AppErrors> {
+  options: Option<KeyListOptions>,
+) -> Result<KeyPage, AppErrors> {
   let read_lock = state.profile_db.read().await;
 
   if let Some(profile_db) = read_lock.deref() {
-    let keys = profile_db.get_keys(false).await.log_err()?;
+    let keys = profile_db
+      .get_keys_page(options.unwrap_or_default())
+      .await
+      .log_err()?;
     Ok(keys)
   } else {
     let _ = app.emit_unlock_required();
@@ -37,16 +47,87 @@ pub async fn get_pinned_keys(
   }
 }
 
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_key_history(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  key_id: i64,
+) -> Result<Vec<KeyHistoryItem>, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let history = profile_db.get_key_history(key_id).await.log_err()?;
+    Ok(history)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_stale_keys(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  unused_days: u32,
+) -> Result<Vec<KeyItem>, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let keys = profile_db.get_stale_keys(unused_days).await.log_err()?;
+    Ok(keys)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn find_keys_for_url(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  url: String,
+) -> Result<Vec<UrlMatchItem>, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let keys = profile_db.find_keys_for_url(&url).await.log_err()?;
+    Ok(keys)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn find_duplicate_keys(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+) -> Result<Vec<DuplicateGroup>, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let groups = profile_db.find_duplicates().await.log_err()?;
+    Ok(groups)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
 #[tauri::command(rename_all = "snake_case")]
 pub async fn search_keys(
   state: State<'_, AppDbState>,
   app: AppHandle,
   query: String,
-) -> Result<Vec<KeyItem>, AppErrors> {
+  options: Option<KeyListOptions>,
+) -> Result<KeyPage<SearchResult>, AppErrors> {
   let read_lock = state.profile_db.read().await;
 
   if let Some(profile_db) = read_lock.deref() {
-    let keys = profile_db.search_keys(query.into()).await.log_err()?;
+    let keys = profile_db
+      .search_keys_page(query.into(), options.unwrap_or_default())
+      .await
+      .log_err()?;
     Ok(keys)
   } else {
     let _ = app.emit_unlock_required();
@@ -63,11 +144,7 @@ pub async fn delete_key(
   let read_lock = state.profile_db.read().await;
 
   if let Some(profile_db) = read_lock.deref() {
-    if let Some(key_data) = profile_db.get_key_by_id(key_id).await.log_err()? {
-      if let Some(icon_name) = key_data.custom_icon {
-        delete_icon(&app, &icon_name).log_err()?;
-      }
-      profile_db.delete_key(key_id).await.log_err()?;
+    if profile_db.delete_key(key_id).await.log_err()? {
       Ok(())
     } else {
       Err(AppErrors::KeyNotFound)
@@ -83,11 +160,15 @@ pub async fn insert_key(
   state: State<'_, AppDbState>,
   app: AppHandle,
   data: KeyData,
+  options: Option<KeyWriteOptions>,
 ) -> Result<i64, AppErrors> {
   let read_lock = state.profile_db.read().await;
 
   if let Some(profile_db) = read_lock.deref() {
-    let result = profile_db.insert_key(data).await.log_err()?;
+    let result = profile_db
+      .insert_key_with(data, options.unwrap_or_default())
+      .await
+      .log_err()?;
     Ok(result)
   } else {
     let _ = app.emit_unlock_required();
@@ -101,6 +182,7 @@ pub async fn update_key(
   state: State<'_, AppDbState>,
   key_id: i64,
   data: KeyData,
+  options: Option<KeyWriteOptions>,
 ) -> Result<(), AppErrors> {
   let read_lock = state.profile_db.read().await;
 
@@ -112,7 +194,10 @@ pub async fn update_key(
         }
         _ => {}
       }
-      profile_db.update_key(key_id, data).await.log_err()?;
+      profile_db
+        .update_key_with(key_id, data, options.unwrap_or_default())
+        .await
+        .log_err()?;
       Ok(())
     } else {
       Err(AppErrors::KeyNotFound)
@@ -180,6 +265,231 @@ pub async fn get_key_by_id(
   }
 }
 
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_trashed_keys(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+) -> Result<Vec<KeyItem>, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let keys = profile_db.get_trashed_keys().await.log_err()?;
+    Ok(keys)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn restore_key(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  key_id: i64,
+) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    profile_db.restore_key(key_id).await.log_err()?;
+    Ok(())
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn purge_key(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  key_id: i64,
+) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let trashed_keys = profile_db.get_trashed_keys().await.log_err()?;
+
+    if let Some(key_data) = trashed_keys.into_iter().find(|key| key.id == key_id) {
+      profile_db.purge_key(key_id).await.log_err()?;
+
+      if let Some(icon_name) = key_data.custom_icon {
+        delete_icon(&app, &icon_name).log_err()?;
+      }
+
+      Ok(())
+    } else {
+      Err(AppErrors::KeyNotFound)
+    }
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn empty_trash(state: State<'_, AppDbState>, app: AppHandle) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    purge_trash(&app, profile_db, 0).await
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn rotate_key(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  key_id: i64,
+) -> Result<i64, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let revision = profile_db.rotate_key(key_id).await.log_err()?;
+    Ok(revision)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_key_rotations(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  key_id: i64,
+) -> Result<Vec<KeyRotationItem>, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let rotations = profile_db.get_key_rotations(key_id).await.log_err()?;
+    Ok(rotations)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_keys_due_for_rotation(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  within_days: Option<u32>,
+) -> Result<Vec<RotationDueItem>, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let keys = profile_db
+      .get_keys_due_for_rotation(within_days.unwrap_or(0))
+      .await
+      .log_err()?;
+    Ok(keys)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn set_key_rotation(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  key_id: i64,
+  rotation_days: Option<u32>,
+) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    profile_db
+      .set_key_rotation(key_id, rotation_days)
+      .await
+      .log_err()?;
+    Ok(())
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_rotation_policies(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+) -> Result<Vec<RotationPolicy>, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let policies = profile_db.get_rotation_policies().await.log_err()?;
+    Ok(policies)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn set_tag_rotation(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  tag: String,
+  rotation_days: Option<u32>,
+) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    profile_db
+      .set_tag_rotation(&tag, rotation_days)
+      .await
+      .log_err()?;
+    Ok(())
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn bulk_update_keys(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  key_ids: Vec<i64>,
+  action: KeyBulkAction,
+) -> Result<u64, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let updated = profile_db
+      .bulk_update_keys(&key_ids, action)
+      .await
+      .log_err()?;
+    Ok(updated)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+/// Purges the keys older than the retention period from trash and removes their icons.
+pub(crate) async fn purge_trash(
+  app: &AppHandle,
+  profile_db: &ProfileDB,
+  retention_days: u32,
+) -> Result<(), AppErrors> {
+  let purged = profile_db.purge_trash(retention_days).await.log_err()?;
+
+  for icon_name in purged.iter().flat_map(|key| key.custom_icon.as_deref()) {
+    delete_icon(app, icon_name).log_err()?;
+  }
+
+  if !purged.is_empty() {
+    info!("{} keys purged from trash.", purged.len());
+  }
+
+  Ok(())
+}
+
 fn delete_icon(handle: &AppHandle, icon_name: &str) -> Result<(), AppErrors> {
   let mut dest_path = handle
     .path_resolver()
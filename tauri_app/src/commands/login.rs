@@ -1,12 +1,15 @@
+use crate::commands::keys::purge_trash;
+use crate::commands::utilities::{read_config_file, DEFAULT_TRASH_RETENTION_DAYS};
 use crate::errors::AppErrors;
 use crate::result_log::ResultLog;
 use crate::{AppDbState, DbNotifier, KeyState};
 use keywich_lib::charset::Charset;
+use keywich_lib::profile::events::RecvError;
 use keywich_lib::profile::{ProfileDB, ProfileDBSqliteOptions, SqlitePassphrase};
 use keywich_lib::scrypt::{scrypt, Params};
 use log::{debug, error, info, warn};
 use std::path::Path;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, State};
 
 pub(super) const APP_DB_NAME: &str = "app.db";
 
@@ -50,6 +53,14 @@ pub async fn unlock_db(
     }
   };
 
+  let retention_days = read_config_file(&app)
+    .and_then(|config| config.trash_retention_days)
+    .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS);
+
+  // Purge failures should not block the unlock, they are already logged.
+  let _ = purge_trash(&app, &connection, retention_days).await;
+  forward_profile_events(&app, &connection);
+
   let mut db_connection = state.profile_db.write().await;
   *db_connection = Some(connection);
 
@@ -70,6 +81,33 @@ pub async fn lock_db(
   Ok(())
 }
 
+/// Emits the profile changes to all windows as `profile_event`. The task ends when the profile is
+/// locked and dropped.
+fn forward_profile_events(app: &AppHandle, profile_db: &ProfileDB) {
+  let mut receiver = profile_db.subscribe();
+  let app = app.clone();
+
+  tauri::async_runtime::spawn(async move {
+    loop {
+      match receiver.recv().await {
+        Ok(event) => {
+          if let Err(err) = app.emit_all("profile_event", event) {
+            warn!("Unable to emit profile event, {}", err);
+          }
+        }
+        Err(RecvError::Lagged(skipped)) => {
+          warn!(
+            "{} profile events skipped, windows are asked to reload.",
+            skipped
+          );
+          let _ = app.emit_all("profile_reload_required", ());
+        }
+        Err(RecvError::Closed) => break,
+      }
+    }
+  });
+}
+
 async fn login(conn_str: &str, master_pass: &str) -> Result<ProfileDB, AppErrors> {
   let passphrase = generate_phrase(master_pass.as_bytes()).log_err()?;
   let options = ProfileDBSqliteOptions {
@@ -149,7 +187,7 @@ macro_rules! hex_string {
 fn generate_hex_phrase(master_pass: &[u8]) -> Result<String, AppErrors> {
   let params = Params::new(PHRASE_LOG_N, PHRASE_R, PHRASE_P, 32).map_err(|e| {
     error!("Passphrase parameter error {}", e);
-    AppErrors::LibError(String::from(
+    AppErrors::PassphraseFailed(String::from(
       "Passphrase generation parameters are invalid.",
     ))
   })?;
@@ -158,7 +196,7 @@ fn generate_hex_phrase(master_pass: &[u8]) -> Result<String, AppErrors> {
 
   scrypt(master_pass, PHRASE_S, &params, &mut buffer).map_err(|e| {
     error!("Passphrase generator error {}", e);
-    AppErrors::LibError(String::from("Cannot create passphrase for sqlite."))
+    AppErrors::PassphraseFailed(String::from("Cannot create passphrase for sqlite."))
   })?;
 
   Ok(hex_string!(&buffer))
@@ -168,7 +206,7 @@ fn generate_phrase(master_pass: &[u8]) -> Result<String, AppErrors> {
   let params = Params::new(PHRASE_LOG_N, PHRASE_R, PHRASE_P, 64)
     .map_err(|e| {
       error!("Passphrase parameter error {}", e);
-      AppErrors::LibError(String::from(
+      AppErrors::PassphraseFailed(String::from(
         "Passphrase generation parameters are invalid.",
       ))
     })
@@ -179,7 +217,7 @@ fn generate_phrase(master_pass: &[u8]) -> Result<String, AppErrors> {
 
   scrypt(master_pass, PHRASE_S, &params, &mut buffer).map_err(|e| {
     error!("Passphrase generator error {}", e);
-    AppErrors::LibError(String::from("Cannot create passphrase for sqlite."))
+    AppErrors::PassphraseFailed(String::from("Cannot create passphrase for sqlite."))
   })?;
 
   Ok(charset.transform_bytes(&buffer))
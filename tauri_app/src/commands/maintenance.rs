@@ -0,0 +1,48 @@
+use crate::errors::AppErrors;
+use crate::result_log::ResultLog;
+use crate::{AppDbState, DbNotifier};
+use keywich_lib::profile::keys::SearchIndexReport;
+use log::{info, warn};
+use std::ops::Deref;
+use tauri::{AppHandle, State};
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn verify_search_index(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+) -> Result<SearchIndexReport, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let report = profile_db.verify_search_index().await.log_err()?;
+
+    if !report.is_valid() {
+      warn!(
+        "Search index is out of sync. Missing: {:?}, stale: {:?}, orphaned: {:?}",
+        report.missing, report.stale, report.orphaned
+      );
+    }
+
+    Ok(report)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn rebuild_search_index(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    profile_db.rebuild_search_index().await.log_err()?;
+    info!("Search index rebuilt.");
+    Ok(())
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
@@ -1,7 +1,10 @@
 use crate::errors::AppErrors;
 use crate::result_log::ResultLog;
 use crate::{AppDbState, DbNotifier, KeyState};
+use keywich_lib::charset::CharsetOptions;
 use keywich_lib::hash::HashAlgorithm;
+use keywich_lib::profile::keys::KeyKind;
+use keywich_lib::PasswordResult;
 use serde::Deserialize;
 use std::ops::Deref;
 use std::str::FromStr;
@@ -22,6 +25,12 @@ pub struct PasswordRequest {
   pub output_type: PasswordOutputType,
 }
 
+#[derive(Deserialize)]
+pub struct HistoryPasswordRequest {
+  pub history_id: i64,
+  pub output_type: PasswordOutputType,
+}
+
 #[derive(Deserialize)]
 pub struct PasswordGenerateRequest {
   pub content: String,
@@ -32,6 +41,8 @@ pub struct PasswordGenerateRequest {
   pub version: String,
   pub target_len: i64,
   pub output_type: PasswordOutputType,
+  #[serde(default)]
+  pub exclude_lookalikes: bool,
 }
 
 #[tauri::command(rename_all = "snake_case")]
@@ -46,21 +57,23 @@ pub async fn generate_password_from(
     output_type,
   } = request;
 
-  let password = match key_state.entry.get_password() {
-    Ok(password) => password,
-    Err(err @ keyring::Error::NoEntry) => {
-      let _ = app.emit_unlock_required();
-      return Err(err.into());
-    }
-    Err(err) => {
-      return Err(err.into());
-    }
-  };
-
+  let password = get_master_password(&key_state, &app)?;
   let read_lock = state.profile_db.read().await;
 
   if let Some(profile_db) = read_lock.deref() {
     if let Some(key) = profile_db.get_key_by_id(profile_id).await.log_err()? {
+      if key.kind == KeyKind::Stored {
+        let Some(secret) = profile_db.get_key_secret(key.id).await.log_err()? else {
+          return Err(AppErrors::KeyNotFound);
+        };
+
+        let password = format_password(stored_password(secret), output_type)?;
+        // Usage statistics are best effort, a failed update must not block the password.
+        let _ = profile_db.record_key_usage(key.id).await.log_err();
+
+        return Ok(password);
+      }
+
       let target_len = usize::try_from(key.target_size)
         .map_err(|_err| AppErrors::InvalidTargetLength)
         .log_err()?;
@@ -73,7 +86,15 @@ pub async fn generate_password_from(
         target_len,
       };
 
-      generate(config, output_type, Some(&key.version))
+      let password = generate(
+        config,
+        key.exclude_lookalikes,
+        output_type,
+        Some(&key.version),
+      )?;
+      let _ = profile_db.record_key_usage(key.id).await.log_err();
+
+      Ok(password)
     } else {
       Err(AppErrors::KeyNotFound)
     }
@@ -83,6 +104,68 @@ pub async fn generate_password_from(
   }
 }
 
+/// Regenerates the password of a key before one of its updates, stored keys return their
+/// previous secret.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn generate_password_from_history(
+  state: State<'_, AppDbState>,
+  key_state: State<'_, KeyState>,
+  app: AppHandle,
+  request: HistoryPasswordRequest,
+) -> Result<String, AppErrors> {
+  let HistoryPasswordRequest {
+    history_id,
+    output_type,
+  } = request;
+
+  let password = get_master_password(&key_state, &app)?;
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    if let Some(snapshot) = profile_db
+      .get_key_history_item(history_id)
+      .await
+      .log_err()?
+    {
+      if snapshot.kind == KeyKind::Stored {
+        let Some(secret) = profile_db
+          .get_key_history_secret(snapshot.id)
+          .await
+          .log_err()?
+        else {
+          return Err(AppErrors::KeyHistoryNotFound);
+        };
+
+        return format_password(stored_password(secret), output_type);
+      }
+
+      let target_len = usize::try_from(snapshot.target_size)
+        .map_err(|_err| AppErrors::InvalidTargetLength)
+        .log_err()?;
+      let config = keywich_lib::PasswordConfig {
+        password: &password,
+        revision: snapshot.revision,
+        domain: &snapshot.domain,
+        username: &snapshot.username,
+        charset: &snapshot.charset,
+        target_len,
+      };
+
+      generate(
+        config,
+        snapshot.exclude_lookalikes,
+        output_type,
+        Some(&snapshot.version),
+      )
+    } else {
+      Err(AppErrors::KeyHistoryNotFound)
+    }
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
 #[tauri::command(rename_all = "snake_case")]
 pub fn generate_password(request: PasswordGenerateRequest) -> Result<String, AppErrors> {
   let PasswordGenerateRequest {
@@ -94,6 +177,7 @@ pub fn generate_password(request: PasswordGenerateRequest) -> Result<String, App
     domain,
     revision,
     target_len,
+    exclude_lookalikes,
   } = request;
 
   let target_len = usize::try_from(target_len)
@@ -109,11 +193,23 @@ pub fn generate_password(request: PasswordGenerateRequest) -> Result<String, App
     target_len,
   };
 
-  generate(config, output_type, Some(&version))
+  generate(config, exclude_lookalikes, output_type, Some(&version))
+}
+
+fn get_master_password(key_state: &KeyState, app: &AppHandle) -> Result<String, AppErrors> {
+  match key_state.entry.get_password() {
+    Ok(password) => Ok(password),
+    Err(err @ keyring::Error::NoEntry) => {
+      let _ = app.emit_unlock_required();
+      Err(err.into())
+    }
+    Err(err) => Err(err.into()),
+  }
 }
 
 pub(crate) fn generate(
   config: keywich_lib::PasswordConfig,
+  exclude_lookalikes: bool,
   output_type: PasswordOutputType,
   algo: Option<&str>,
 ) -> Result<String, AppErrors> {
@@ -121,7 +217,26 @@ pub(crate) fn generate(
     None => HashAlgorithm::default(),
     Some(algo_name) => HashAlgorithm::from_str(algo_name).log_err()?,
   };
-  let pass_result = keywich_lib::generate_password(config, hash_algo).log_err()?;
+  let charset_options = CharsetOptions { exclude_lookalikes };
+  let pass_result =
+    keywich_lib::generate_password_with(config, hash_algo, charset_options).log_err()?;
+
+  format_password(pass_result, output_type)
+}
+
+/// Stored secrets use the same output types as the generated passwords.
+fn stored_password(secret: String) -> PasswordResult {
+  PasswordResult {
+    pass: secret,
+    alg: String::from("stored"),
+    ver: String::from("1"),
+  }
+}
+
+fn format_password(
+  pass_result: PasswordResult,
+  output_type: PasswordOutputType,
+) -> Result<String, AppErrors> {
   let string_response = match output_type {
     PasswordOutputType::PHC => pass_result.to_phc(),
     PasswordOutputType::Text => pass_result.pass,
@@ -0,0 +1,76 @@
+use crate::errors::AppErrors;
+use crate::result_log::ResultLog;
+use crate::{AppDbState, DbNotifier};
+use keywich_lib::profile::tags::TagItem;
+use std::ops::Deref;
+use tauri::{AppHandle, State};
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_tags(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+) -> Result<Vec<TagItem>, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let tags = profile_db.list_tags().await.log_err()?;
+    Ok(tags)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn rename_tag(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  name: String,
+  new_name: String,
+) -> Result<u64, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let updated = profile_db.rename_tag(&name, &new_name).await.log_err()?;
+    Ok(updated)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn merge_tags(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  sources: Vec<String>,
+  target: String,
+) -> Result<u64, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+    let updated = profile_db.merge_tags(&sources, &target).await.log_err()?;
+    Ok(updated)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn delete_tag(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  name: String,
+) -> Result<u64, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let updated = profile_db.delete_tag(&name).await.log_err()?;
+    Ok(updated)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
@@ -0,0 +1,101 @@
+use crate::errors::AppErrors;
+use crate::result_log::ResultLog;
+use crate::{AppDbState, DbNotifier};
+use keywich_lib::profile::templates::{KeyTemplateData, KeyTemplateItem, TemplateKeyData};
+use std::ops::Deref;
+use tauri::{AppHandle, State};
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_templates(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+) -> Result<Vec<KeyTemplateItem>, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let templates = profile_db.get_templates().await.log_err()?;
+    Ok(templates)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn insert_template(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  data: KeyTemplateData,
+) -> Result<i64, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let template_id = profile_db.insert_template(data).await.log_err()?;
+    Ok(template_id)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn update_template(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  template_id: i64,
+  data: KeyTemplateData,
+) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    profile_db
+      .update_template(template_id, data)
+      .await
+      .log_err()?;
+    Ok(())
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn delete_template(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  template_id: i64,
+) -> Result<(), AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    if profile_db.delete_template(template_id).await.log_err()? {
+      Ok(())
+    } else {
+      Err(AppErrors::TemplateNotFound)
+    }
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn insert_key_from_template(
+  state: State<'_, AppDbState>,
+  app: AppHandle,
+  template_id: i64,
+  data: TemplateKeyData,
+) -> Result<i64, AppErrors> {
+  let read_lock = state.profile_db.read().await;
+
+  if let Some(profile_db) = read_lock.deref() {
+    let key_id = profile_db
+      .insert_key_from_template(template_id, data)
+      .await
+      .log_err()?;
+    Ok(key_id)
+  } else {
+    let _ = app.emit_unlock_required();
+    Err(AppErrors::DbNotInitialized)
+  }
+}
@@ -15,8 +15,12 @@ pub struct ConfigFile {
   pub color_theme: Option<String>,
   pub locale: Option<String>,
   pub is_light_theme: Option<bool>,
+  /// Days before deleted keys are purged from trash.
+  pub trash_retention_days: Option<u32>,
 }
 
+pub(crate) const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
 #[derive(Serialize)]
 pub struct AppConfig {
   pub configs: Option<ConfigFile>,
@@ -155,6 +159,14 @@ pub async fn load_configs(
   Ok(app_details)
 }
 
+/// Reads config.json from app local data directory.
+pub(crate) fn read_config_file(app: &AppHandle) -> Option<ConfigFile> {
+  let local_data_dir = app.path_resolver().app_local_data_dir()?;
+  let config_file = Path::join(&local_data_dir, "config.json");
+
+  read_json_file::<ConfigFile, _>(config_file).ok()
+}
+
 enum FileErrors {
   UnableToOpenFile(String),
   DeserializerError(String),
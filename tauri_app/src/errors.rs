@@ -1,3 +1,5 @@
+use keywich_lib::charset::parser::ParseError;
+use keywich_lib::profile::keys::UniquenessCheck;
 use keywich_lib::ValidationErrors;
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
@@ -7,10 +9,11 @@ use std::fmt::{Debug, Display, Formatter};
 pub enum AppErrors {
   OutputError(String),
   InvalidTargetLength,
-  InvalidCharset,
-  LibError(String),
+  InvalidCharset(Option<ParseError>),
+  LibError(keywich_lib::errors::Error),
   UnsupportedHashFunc,
   ValidationError(ValidationErrors),
+  DuplicateCharset(String),
   KeyNotFound,
   LocalDataDirNotFound,
   TempFolderFailed,
@@ -23,7 +26,19 @@ pub enum AppErrors {
   KeyringFailure(String),
   NoKeyEntry,
   DuplicateKeyEntry,
-  BackupError(String),
+  BackupError(keywich_lib::errors::Error),
+  PassphraseFailed(String),
+  KeyHistoryNotFound,
+  DuplicateCollection(String),
+  InvalidCollectionParent,
+  CollectionNotFound,
+  DuplicateKey(i64),
+  DuplicateTemplate(String),
+  TemplateNotFound,
+  DuplicateCharsetName(String),
+  CharsetInUse(usize),
+  CharsetNotFound,
+  DuplicateDerivation(i64),
 }
 
 impl AppErrors {
@@ -32,8 +47,16 @@ impl AppErrors {
       // User errors (1-199)
       AppErrors::OutputError(_) => 1,
       AppErrors::InvalidTargetLength => 2,
-      AppErrors::InvalidCharset => 3,
+      AppErrors::InvalidCharset(_) => 3,
       AppErrors::ValidationError(_) => 4,
+      AppErrors::DuplicateCharset(_) => 5,
+      AppErrors::DuplicateCollection(_) => 6,
+      AppErrors::InvalidCollectionParent => 7,
+      AppErrors::DuplicateKey(_) => 8,
+      AppErrors::DuplicateTemplate(_) => 9,
+      AppErrors::DuplicateCharsetName(_) => 10,
+      AppErrors::CharsetInUse(_) => 11,
+      AppErrors::DuplicateDerivation(_) => 12,
 
       // System errors (200-399)
       AppErrors::LibError(_) => 200,
@@ -46,6 +69,11 @@ impl AppErrors {
       AppErrors::NoKeyEntry => 208,
       AppErrors::DuplicateKeyEntry => 209,
       AppErrors::BackupError(_) => 210,
+      AppErrors::PassphraseFailed(_) => 211,
+      AppErrors::KeyHistoryNotFound => 212,
+      AppErrors::CollectionNotFound => 213,
+      AppErrors::TemplateNotFound => 214,
+      AppErrors::CharsetNotFound => 215,
 
       // Potential OS issues (400-599)
       AppErrors::LocalDataDirNotFound => 400,
@@ -65,19 +93,31 @@ impl From<keywich_lib::errors::Error> for AppErrors {
       keywich_lib::errors::Error::InvalidHashOutput => {
         Self::OutputError(String::from("Invalid hash length."))
       }
-      keywich_lib::errors::Error::ParserInvalidRange => Self::InvalidCharset,
+      keywich_lib::errors::Error::ParserInvalidRange(details) => {
+        Self::InvalidCharset(Some(details))
+      }
+      keywich_lib::errors::Error::EmptyCharset => Self::InvalidCharset(None),
+      keywich_lib::errors::Error::DuplicateCharset(name) => Self::DuplicateCharset(name),
+      keywich_lib::errors::Error::DuplicateCharsetName(name) => Self::DuplicateCharsetName(name),
+      keywich_lib::errors::Error::CharsetInUse { key_count, .. } => Self::CharsetInUse(key_count),
+      keywich_lib::errors::Error::CharsetNotFound(_) => Self::CharsetNotFound,
       keywich_lib::errors::Error::InvalidInput => Self::InvalidTargetLength,
       keywich_lib::errors::Error::ValidationError(details) => Self::ValidationError(details),
-      keywich_lib::errors::Error::DatabaseError(detail) => Self::LibError(detail),
-      keywich_lib::errors::Error::DatabaseMigrateError(detail) => Self::LibError(detail),
-      keywich_lib::errors::Error::InvalidDatabasePath(detail) => {
-        Self::LibError(format!("Database not found at {:?}", detail))
-      }
-      keywich_lib::errors::Error::InvalidTime(detail) => Self::LibError(detail),
       keywich_lib::errors::Error::InvalidHashFuncVersion => Self::UnsupportedHashFunc,
-      keywich_lib::errors::Error::InvalidJsonError(detail) => Self::OutputError(detail),
-      keywich_lib::errors::Error::InvalidQrError(detail) => Self::OutputError(detail),
-      keywich_lib::errors::Error::BackupError(detail) => Self::BackupError(detail),
+      keywich_lib::errors::Error::InvalidJsonError(detail) => Self::OutputError(detail.to_string()),
+      keywich_lib::errors::Error::InvalidQrError(detail) => Self::OutputError(detail.to_string()),
+      keywich_lib::errors::Error::KeyNotFound { .. } => Self::KeyNotFound,
+      keywich_lib::errors::Error::DuplicateKey { key_id, check } => match check {
+        UniquenessCheck::Derivation => Self::DuplicateDerivation(key_id),
+        UniquenessCheck::Account | UniquenessCheck::Off => Self::DuplicateKey(key_id),
+      },
+      keywich_lib::errors::Error::CollectionNotFound { .. } => Self::CollectionNotFound,
+      keywich_lib::errors::Error::DuplicateCollection(name) => Self::DuplicateCollection(name),
+      keywich_lib::errors::Error::TemplateNotFound { .. } => Self::TemplateNotFound,
+      keywich_lib::errors::Error::DuplicateTemplate(name) => Self::DuplicateTemplate(name),
+      keywich_lib::errors::Error::InvalidCollectionParent { .. } => Self::InvalidCollectionParent,
+      err @ keywich_lib::errors::Error::BackupError { .. } => Self::BackupError(err),
+      err => Self::LibError(err),
     }
   }
 }
@@ -107,10 +147,20 @@ impl Display for AppErrors {
     match self {
       AppErrors::OutputError(err) => write!(f, "Password output generation failed. {}", err),
       AppErrors::InvalidTargetLength => write!(f, "Password target length is not valid."),
-      AppErrors::InvalidCharset => write!(f, "Provided charset syntax is not valid."),
+      AppErrors::InvalidCharset(Some(err)) => {
+        write!(f, "Provided charset syntax is not valid. {}", err)
+      }
+      AppErrors::InvalidCharset(None) => write!(f, "Provided charset syntax is not valid."),
       AppErrors::LibError(err) => write!(f, "Unexpected error, {}", err),
       AppErrors::UnsupportedHashFunc => write!(f, "Unsupported hash function received."),
       AppErrors::ValidationError(err) => write!(f, "Input validation failed {}", err),
+      AppErrors::DuplicateCharset(name) => {
+        write!(
+          f,
+          "Charset has the same symbols with the existing '{}' charset.",
+          name
+        )
+      }
       AppErrors::KeyNotFound => write!(f, "Requested key does not exists."),
       AppErrors::LocalDataDirNotFound => write!(f, "Local data directory not found."),
       AppErrors::TempFolderFailed => write!(f, "Unable to access app temp directory."),
@@ -123,12 +173,52 @@ impl Display for AppErrors {
       AppErrors::KeyringFailure(err) => write!(f, "OS keyring failed, {}", err),
       AppErrors::NoKeyEntry => write!(f, "No master key entry found."),
       AppErrors::DuplicateKeyEntry => write!(f, "Duplicate master key entry detected."),
-      AppErrors::BackupError(err) => write!(f, "Backup action failed, {}", err),
+      AppErrors::BackupError(err) => write!(f, "{}", err),
+      AppErrors::PassphraseFailed(err) => write!(f, "Passphrase generation failed, {}", err),
+      AppErrors::KeyHistoryNotFound => write!(f, "Requested key history does not exists."),
+      AppErrors::DuplicateCollection(name) => {
+        write!(
+          f,
+          "Collection '{}' already exists in the same parent.",
+          name
+        )
+      }
+      AppErrors::InvalidCollectionParent => {
+        write!(
+          f,
+          "Collection cannot be moved under itself or its sub collections."
+        )
+      }
+      AppErrors::CollectionNotFound => write!(f, "Requested collection does not exists."),
+      AppErrors::DuplicateKey(key_id) => {
+        write!(
+          f,
+          "Key {} already exists with the same domain and username.",
+          key_id
+        )
+      }
+      AppErrors::DuplicateTemplate(name) => write!(f, "Key template '{}' already exists.", name),
+      AppErrors::TemplateNotFound => write!(f, "Requested key template does not exists."),
+      AppErrors::DuplicateCharsetName(name) => write!(f, "Charset '{}' already exists.", name),
+      AppErrors::CharsetInUse(key_count) => {
+        write!(f, "Charset is used by {} key(s).", key_count)
+      }
+      AppErrors::CharsetNotFound => write!(f, "Requested charset does not exists."),
+      AppErrors::DuplicateDerivation(key_id) => {
+        write!(f, "Key {} already generates the same password.", key_id)
+      }
     }
   }
 }
 
-impl std::error::Error for AppErrors {}
+impl std::error::Error for AppErrors {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      AppErrors::LibError(err) | AppErrors::BackupError(err) => Some(err),
+      _ => None,
+    }
+  }
+}
 
 macro_rules! error_obj {
   ($serializer:expr, code = $code:expr, message = $message:expr) => {{
@@ -162,6 +252,28 @@ macro_rules! error_obj {
     error_obj.serialize_field("fields", v)?;
     error_obj.end()
   }};
+  ($serializer:expr, code = $code:expr, message = $message:expr, charset = $charset:expr) => {{
+    let c: &str = $code;
+    let m: &str = $message;
+    let p: &keywich_lib::charset::parser::ParseError = $charset;
+    let mut error_obj = $serializer.serialize_struct("Error", 3)?;
+
+    error_obj.serialize_field("code", c)?;
+    error_obj.serialize_field("message", m)?;
+    error_obj.serialize_field("charset", p)?;
+    error_obj.end()
+  }};
+  ($serializer:expr, code = $code:expr, message = $message:expr, source = $source:expr) => {{
+    let c: &str = $code;
+    let m: &str = $message;
+    let s: &keywich_lib::errors::Error = $source;
+    let mut error_obj = $serializer.serialize_struct("Error", 3)?;
+
+    error_obj.serialize_field("code", c)?;
+    error_obj.serialize_field("message", m)?;
+    error_obj.serialize_field("source", s)?;
+    error_obj.end()
+  }};
 }
 
 impl Serialize for AppErrors {
@@ -178,6 +290,14 @@ impl Serialize for AppErrors {
           validation = errors
         )
       }
+      AppErrors::InvalidCharset(Some(details)) => {
+        error_obj!(
+          serializer,
+          code = &self.code(),
+          message = &self.to_string(),
+          charset = details
+        )
+      }
       AppErrors::OutputError(details) => {
         error_obj!(
           serializer,
@@ -186,12 +306,12 @@ impl Serialize for AppErrors {
           details = details
         )
       }
-      AppErrors::LibError(details) => {
+      AppErrors::LibError(source) => {
         error_obj!(
           serializer,
           code = &self.code(),
           message = &self.to_string(),
-          details = details
+          source = source
         )
       }
       AppErrors::IconReadFailed(details) => {
@@ -202,7 +322,49 @@ impl Serialize for AppErrors {
           details = details
         )
       }
-      AppErrors::BackupError(details) => {
+      AppErrors::DuplicateCharset(details) => {
+        error_obj!(
+          serializer,
+          code = &self.code(),
+          message = &self.to_string(),
+          details = details
+        )
+      }
+      AppErrors::DuplicateCollection(details)
+      | AppErrors::DuplicateTemplate(details)
+      | AppErrors::DuplicateCharsetName(details) => {
+        error_obj!(
+          serializer,
+          code = &self.code(),
+          message = &self.to_string(),
+          details = details
+        )
+      }
+      AppErrors::DuplicateKey(key_id) | AppErrors::DuplicateDerivation(key_id) => {
+        error_obj!(
+          serializer,
+          code = &self.code(),
+          message = &self.to_string(),
+          details = &key_id.to_string()
+        )
+      }
+      AppErrors::CharsetInUse(key_count) => {
+        error_obj!(
+          serializer,
+          code = &self.code(),
+          message = &self.to_string(),
+          details = &key_count.to_string()
+        )
+      }
+      AppErrors::BackupError(source) => {
+        error_obj!(
+          serializer,
+          code = &self.code(),
+          message = &self.to_string(),
+          source = source
+        )
+      }
+      AppErrors::PassphraseFailed(details) => {
         error_obj!(
           serializer,
           code = &self.code(),
@@ -38,7 +38,8 @@ pub(crate) struct KeywichArgs {
 const DEFAULT_CONFIG: &[u8] = br#"{
     "is_light_theme": false,
     "color_theme": "crimson",
-    "locale": "en"
+    "locale": "en",
+    "trash_retention_days": 30
 }"#;
 
 pub(crate) struct AppDbState {